@@ -0,0 +1,194 @@
+//! [wasm-bindgen](https://github.com/rustwasm/wasm-bindgen) API for ttf2mesh, for generating
+//! text meshes in the browser. Fonts are loaded from memory, e.g. from a `fetch` response
+//!
+//! Usage (JavaScript):
+//! ```js
+//! const data = new Uint8Array(await (await fetch("FiraMono-Medium.ttf")).arrayBuffer());
+//! const font = new Font(data);
+//!
+//! const mesh = font.text_mesh("Hello", 20, 0.1);
+//! geometry.setAttribute("position", new THREE.BufferAttribute(mesh.vertices, 3));
+//! geometry.setAttribute("normal", new THREE.BufferAttribute(mesh.normals, 3));
+//! geometry.setIndex(new THREE.BufferAttribute(mesh.indices, 1));
+//! ```
+use ttf2mesh::{Error, Glyph, OwnedMesh, Quality, TTFFile};
+use wasm_bindgen::prelude::*;
+
+/// A font loaded from the bytes of a TrueType or OpenType file
+#[wasm_bindgen]
+pub struct Font {
+    ttf: TTFFile,
+}
+
+#[wasm_bindgen]
+impl Font {
+    /// Load a font from the bytes of a font file (`Uint8Array`)
+    #[wasm_bindgen(constructor)]
+    pub fn new(data: Vec<u8>) -> Result<Font, JsValue> {
+        Font::load(data).map_err(to_js)
+    }
+
+    /// Mesh of a character. `quality` is the count of segments per full turn of curves (8 to
+    /// 128), a 3d mesh is generated when `depth` is given
+    pub fn glyph_mesh(
+        &mut self,
+        char: char,
+        quality: u8,
+        depth: Option<f32>,
+    ) -> Result<TextMesh, JsValue> {
+        self.glyph(char, quality, depth).map_err(to_js)
+    }
+
+    /// Mesh of a text laid out in lines, see [`Font::glyph_mesh`]. Characters without an outline
+    /// (e.g. spaces) only advance the position
+    pub fn text_mesh(
+        &mut self,
+        text: &str,
+        quality: u8,
+        depth: Option<f32>,
+    ) -> Result<TextMesh, JsValue> {
+        self.text(text, quality, depth).map_err(to_js)
+    }
+}
+
+impl Font {
+    fn load(data: Vec<u8>) -> Result<Font, Error> {
+        Ok(Font {
+            ttf: TTFFile::from_buffer_vec(data)?,
+        })
+    }
+
+    fn glyph(&mut self, char: char, quality: u8, depth: Option<f32>) -> Result<TextMesh, Error> {
+        let mut glyph = self.ttf.glyph_from_char(char)?;
+        Ok(TextMesh::from(&owned_mesh(&mut glyph, quality, depth)?))
+    }
+
+    fn text(&mut self, text: &str, quality: u8, depth: Option<f32>) -> Result<TextMesh, Error> {
+        let mut mesh = OwnedMesh::default();
+
+        for placed in self.ttf.layout(text) {
+            let mut glyph = self.ttf.glyph_by_index(placed.glyph_index)?;
+            // glyphs without an outline can't be meshed, other failures are returned
+            if glyph.contours_info()?.is_empty() {
+                continue;
+            }
+            let glyph = owned_mesh(&mut glyph, quality, depth)?.translate(placed.x, placed.y, 0.0);
+
+            let offset = mesh.vertices.len() as i32;
+            mesh.vertices.extend(glyph.vertices);
+            mesh.normals.extend(glyph.normals);
+            mesh.faces.extend(
+                glyph
+                    .faces
+                    .into_iter()
+                    .map(|(a, b, c)| (a + offset, b + offset, c + offset)),
+            );
+        }
+
+        Ok(TextMesh::from(&mesh))
+    }
+}
+
+fn owned_mesh(glyph: &mut Glyph, quality: u8, depth: Option<f32>) -> Result<OwnedMesh, Error> {
+    let quality = Quality::Custom(quality);
+
+    Ok(match depth {
+        Some(depth) => glyph.to_3d_mesh(quality, depth)?.to_owned_mesh(),
+        None => glyph.to_2d_mesh(quality)?.to_owned_mesh(),
+    })
+}
+
+/// Mesh data as typed arrays
+#[wasm_bindgen]
+pub struct TextMesh {
+    vertices: Vec<f32>,
+    indices: Vec<u32>,
+    normals: Vec<f32>,
+}
+
+#[wasm_bindgen]
+impl TextMesh {
+    /// `x, y, z` of each vertex (`Float32Array`), in em units. `z` is zero for 2d meshes
+    #[wasm_bindgen(getter)]
+    pub fn vertices(&self) -> Vec<f32> {
+        self.vertices.clone()
+    }
+
+    /// Three vertex indices for each triangle (`Uint32Array`)
+    #[wasm_bindgen(getter)]
+    pub fn indices(&self) -> Vec<u32> {
+        self.indices.clone()
+    }
+
+    /// `x, y, z` of each vertex normal (`Float32Array`). Empty for 2d meshes
+    #[wasm_bindgen(getter)]
+    pub fn normals(&self) -> Vec<f32> {
+        self.normals.clone()
+    }
+}
+
+impl From<&OwnedMesh> for TextMesh {
+    fn from(mesh: &OwnedMesh) -> Self {
+        TextMesh {
+            vertices: mesh
+                .vertices
+                .iter()
+                .flat_map(|v| vec![v.0, v.1, v.2])
+                .collect(),
+            indices: mesh
+                .faces
+                .iter()
+                .flat_map(|f| vec![f.0 as u32, f.1 as u32, f.2 as u32])
+                .collect(),
+            normals: mesh
+                .normals
+                .iter()
+                .flat_map(|n| vec![n.0, n.1, n.2])
+                .collect(),
+        }
+    }
+}
+
+fn to_js(error: Error) -> JsValue {
+    JsValue::from_str(&format!("{:?}", error))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn font() -> Font {
+        Font::load(include_bytes!("../../../fonts/FiraMono-Medium.ttf").to_vec()).unwrap()
+    }
+
+    #[test]
+    fn test_glyph_mesh() {
+        let mut font = font();
+
+        let mesh = font.glyph('€', 20, None).unwrap();
+        assert_eq!(mesh.vertices.len() % 3, 0);
+        assert_eq!(mesh.indices.len() % 3, 0);
+        assert!(mesh.normals.is_empty());
+
+        let vertices = mesh.vertices.len() as u32 / 3;
+        assert!(mesh.indices.iter().all(|&index| index < vertices));
+
+        let mesh = font.glyph('€', 20, Some(0.5)).unwrap();
+        assert_eq!(mesh.normals.len(), mesh.vertices.len());
+    }
+
+    #[test]
+    fn test_text_mesh() {
+        let mut font = font();
+
+        let a = font.glyph('a', 20, None).unwrap();
+        let text = font.text("a a", 20, None).unwrap();
+
+        // the space has no mesh, the second glyph is further right
+        assert_eq!(text.vertices.len(), a.vertices.len() * 2);
+        assert_eq!(text.indices.len(), a.indices.len() * 2);
+        assert!(text.vertices[a.vertices.len()] > a.vertices[0]);
+
+        assert!(Font::load(b"not a font".to_vec()).is_err());
+    }
+}
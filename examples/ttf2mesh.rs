@@ -39,9 +39,14 @@ fn main() {
         }
     };
 
-    for char in utf8_string.chars() {
-        println!("Mesh data char {:?}", char);
-        let mut glyph = match font.glyph_from_char(char) {
+    // glyphs in visual order (right-to-left runs are reordered with the `bidi` feature)
+    let chars = utf8_string.chars().collect::<Vec<_>>();
+    for placed in font.layout(utf8_string) {
+        println!(
+            "Mesh data char {:?} at ({:.3}, {:.3})",
+            chars[placed.char_index], placed.x, placed.y
+        );
+        let mut glyph = match font.glyph_by_index(placed.glyph_index) {
             Ok(g) => g,
             Err(_) => {
                 println!("- can not find glyph in the font file");
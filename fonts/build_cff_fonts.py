@@ -0,0 +1,406 @@
+#!/usr/bin/env python3
+"""Build the CFF-flavored OpenType test fonts from FiraMono-Medium.ttf
+
+    python3 fonts/build_cff_fonts.py
+
+Writes a subset of the glyphs as `FiraMono-Medium-CFF.otf` (CFF outlines, with `Eacute` as a
+`seac` accented glyph) and `FiraMono-Medium-CFF2.otf` (CFF2 outlines with a `wght` axis whose
+`blend` deltas move the glyphs). Quadratic curves are written as the equivalent cubic curves, so
+the outlines match those of the TrueType font. Only the Python standard library is needed.
+"""
+import os
+import struct
+
+DIR = os.path.dirname(os.path.abspath(__file__))
+SOURCE = os.path.join(DIR, "FiraMono-Medium.ttf")
+
+# (name, source glyph, character, standard string id of the name)
+GLYPHS = [
+    (".notdef", 0, None, 0),
+    ("space", 3, 0x20, 1),
+    ("A", 4, 0x41, 34),
+    ("E", 27, 0x45, 38),
+    ("O", 74, 0x4F, 48),
+    ("o", 210, 0x6F, 80),
+    ("g", 170, 0x67, 72),
+    # acute.case, the accent of Eacute in FiraMono
+    ("acute", 1442, None, 125),
+]
+
+# Eacute (source glyph 28) as E and acute at (21, 0): standard encoding codes of E and acute
+SEAC = ("Eacute", 28, 0xC9, (21, 0, 0x45, 0xC2))
+
+# horizontal offset of the glyphs at the maximum of the wght axis
+WGHT_DELTA = 10
+
+
+class Font:
+    """The tables and glyphs of a TrueType font"""
+
+    def __init__(self, path):
+        data = open(path, "rb").read()
+        num_tables = struct.unpack(">H", data[4:6])[0]
+        self.tables = {}
+        for i in range(num_tables):
+            tag, _, offset, length = struct.unpack(">4sIII", data[12 + 16 * i : 28 + 16 * i])
+            self.tables[tag.decode("latin1")] = data[offset : offset + length]
+
+        self.loca_format = struct.unpack(">h", self.tables["head"][50:52])[0]
+        self.num_h_metrics = struct.unpack(">H", self.tables["hhea"][34:36])[0]
+
+    def metric(self, glyph):
+        hmtx = self.tables["hmtx"]
+        if glyph < self.num_h_metrics:
+            return struct.unpack(">Hh", hmtx[4 * glyph : 4 * glyph + 4])
+
+        advance = struct.unpack(">H", hmtx[4 * (self.num_h_metrics - 1) : 4 * self.num_h_metrics])[0]
+        offset = 4 * self.num_h_metrics + 2 * (glyph - self.num_h_metrics)
+        return advance, struct.unpack(">h", hmtx[offset : offset + 2])[0]
+
+    def contours(self, glyph):
+        """Contours of a simple glyph as lists of (x, y, on_curve)"""
+        loca = self.tables["loca"]
+        if self.loca_format == 0:
+            start, end = [2 * v for v in struct.unpack(">HH", loca[2 * glyph : 2 * glyph + 4])]
+        else:
+            start, end = struct.unpack(">II", loca[4 * glyph : 4 * glyph + 8])
+
+        data = self.tables["glyf"][start:end]
+        if not data:
+            return []
+
+        num_contours = struct.unpack(">h", data[:2])[0]
+        assert num_contours >= 0, "composite glyph %d" % glyph
+
+        ends = struct.unpack(">%dH" % num_contours, data[10 : 10 + 2 * num_contours])
+        num_points = ends[-1] + 1
+        pos = 10 + 2 * num_contours
+        pos += 2 + struct.unpack(">H", data[pos : pos + 2])[0]
+
+        flags = []
+        while len(flags) < num_points:
+            flag = data[pos]
+            pos += 1
+            flags.append(flag)
+            if flag & 8:
+                flags += [flag] * data[pos]
+                pos += 1
+
+        def coordinates(short, same):
+            nonlocal pos
+            values, value = [], 0
+            for flag in flags:
+                if flag & short:
+                    delta = data[pos]
+                    pos += 1
+                    value += delta if flag & same else -delta
+                elif not flag & same:
+                    value += struct.unpack(">h", data[pos : pos + 2])[0]
+                    pos += 2
+                values.append(value)
+            return values
+
+        xs = coordinates(2, 16)
+        ys = coordinates(4, 32)
+
+        contours, first = [], 0
+        for end in ends:
+            contours.append([(xs[i], ys[i], bool(flags[i] & 1)) for i in range(first, end + 1)])
+            first = end + 1
+        return contours
+
+
+def segments(contour):
+    """Closed contour of quadratic points as a start point and line / cubic segments"""
+    points = list(contour)
+    if not points[0][2]:
+        # start on a curve point, implied if needed
+        if points[-1][2]:
+            points = points[-1:] + points[:-1]
+        else:
+            x, y = (points[0][0] + points[-1][0]) / 2, (points[0][1] + points[-1][1]) / 2
+            points = [(x, y, True)] + points
+
+    start = points[0][:2]
+    result, current, control = [], start, None
+    for x, y, on_curve in points[1:] + [points[0]]:
+        if on_curve:
+            if control is None:
+                result.append(((x, y),))
+            else:
+                result.append(cubic(current, control, (x, y)))
+            current, control = (x, y), None
+        elif control is None:
+            control = (x, y)
+        else:
+            middle = ((control[0] + x) / 2, (control[1] + y) / 2)
+            result.append(cubic(current, control, middle))
+            current, control = middle, (x, y)
+
+    if control is not None:
+        result.append(cubic(current, control, start))
+
+    return start, result
+
+
+def cubic(p0, control, p3):
+    """Degree elevation of a quadratic curve"""
+    p1 = (p0[0] + 2 / 3 * (control[0] - p0[0]), p0[1] + 2 / 3 * (control[1] - p0[1]))
+    p2 = (p3[0] + 2 / 3 * (control[0] - p3[0]), p3[1] + 2 / 3 * (control[1] - p3[1]))
+    return (p1, p2, p3)
+
+
+def reverse(start, segs):
+    """Reverse the direction of a closed contour: CFF outer contours run counter-clockwise"""
+    ends = [start] + [seg[-1] for seg in segs[:-1]]
+    reversed_segs = []
+    for seg, end in zip(reversed(segs), reversed(ends)):
+        reversed_segs.append(tuple(reversed(seg[:-1])) + (end,))
+    return start, reversed_segs
+
+
+def number(value):
+    """Charstring operand, 16.16 fixed for fractions"""
+    fixed = round(value * 65536)
+    if fixed % 65536 == 0:
+        value = fixed // 65536
+        if -107 <= value <= 107:
+            return bytes([value + 139])
+        if 108 <= value <= 1131:
+            value -= 108
+            return bytes([(value >> 8) + 247, value & 0xFF])
+        if -1131 <= value <= -108:
+            value = -value - 108
+            return bytes([(value >> 8) + 251, value & 0xFF])
+        return b"\x1c" + struct.pack(">h", value)
+    return b"\xff" + struct.pack(">i", fixed)
+
+
+def charstring(contours, width=None, blend=False):
+    """Type 2 charstring. Points are rounded to integers, like CFF fonts converted from TrueType
+    usually are, and the closing line of a contour is left implicit"""
+    out = bytearray()
+    if width is not None:
+        out += number(width)
+
+    current = (0, 0)
+    first = True
+
+    def delta(point):
+        nonlocal current
+        point = (round(point[0]), round(point[1]))
+        d = (point[0] - current[0], point[1] - current[1])
+        current = point
+        return d
+
+    for contour in contours:
+        start, segs = reverse(*segments(contour))
+        dx, dy = delta(start)
+        out += number(dx) + number(dy)
+        if blend and first:
+            # deltas of the only region, for the two operands
+            out += number(WGHT_DELTA) + number(0) + number(2) + bytes([16])
+        out.append(21)  # rmoveto
+        first = False
+
+        if len(segs[-1]) == 1:
+            segs = segs[:-1]
+        for seg in segs:
+            args = b"".join(number(v) for point in seg for v in delta(point))
+            out += args + bytes([5 if len(seg) == 1 else 8])  # rlineto / rrcurveto
+
+    if width is not None:
+        out.append(14)  # endchar
+    return bytes(out)
+
+
+def index(items, count_size=2):
+    out = struct.pack(">I" if count_size == 4 else ">H", len(items))
+    if not items:
+        return out
+    out += b"\x04"
+    offset = 1
+    out += struct.pack(">I", offset)
+    for item in items:
+        offset += len(item)
+        out += struct.pack(">I", offset)
+    return out + b"".join(items)
+
+
+def dict_int(value):
+    """Fixed-size (5 byte) DICT integer, so that offsets can be filled in afterwards"""
+    return b"\x1d" + struct.pack(">i", value)
+
+
+def build_cff(font):
+    charstrings = [charstring(font.contours(source), font.metric(source)[0]) for (_, source, _, _) in GLYPHS]
+    width = font.metric(SEAC[1])[0]
+    charstrings.append(b"".join(number(v) for v in (width,) + SEAC[3]) + b"\x0e")
+
+    names = index([b"FiraMono-Medium-CFF"])
+    strings = index([SEAC[0].encode()])
+    global_subrs = index([])
+    charset = b"\x00" + b"".join(struct.pack(">H", sid) for (_, _, _, sid) in GLYPHS[1:])
+    charset += struct.pack(">H", 391)  # Eacute, the first custom string
+    char_strings = index(charstrings)
+    # defaultWidthX and nominalWidthX 0
+    private = b"\x8b\x14\x8b\x15"
+
+    def top_dict(charset_offset, char_strings_offset, private_offset):
+        return (
+            dict_int(charset_offset)
+            + b"\x0f"
+            + dict_int(char_strings_offset)
+            + b"\x11"
+            + dict_int(len(private))
+            + dict_int(private_offset)
+            + b"\x12"
+        )
+
+    header = b"\x01\x00\x04\x04"
+    start = len(header) + len(names) + len(index([top_dict(0, 0, 0)])) + len(strings) + len(global_subrs)
+    top = top_dict(start, start + len(charset), start + len(charset) + len(char_strings))
+
+    return header + names + index([top]) + strings + global_subrs + charset + char_strings + private
+
+
+def build_cff2(font):
+    charstrings = [charstring(font.contours(source), blend=True) for (_, source, _, _) in GLYPHS]
+
+    # variation store: a region at the maximum of the only axis, used by one variation data
+    region_list = struct.pack(">HH", 1, 1) + struct.pack(">hhh", 0, 16384, 16384)
+    variation_data = struct.pack(">HHHH", 0, 0, 1, 0)
+    store = struct.pack(">HIHI", 1, 12, 1, 12 + len(region_list)) + region_list + variation_data
+    store = struct.pack(">H", len(store)) + store
+
+    # the font DICT with an empty private DICT
+    global_subrs = index([], 4)
+    char_strings = index(charstrings, 4)
+
+    def top_dict(char_strings_offset, fd_array_offset, store_offset):
+        return (
+            dict_int(char_strings_offset)
+            + b"\x11"
+            + dict_int(fd_array_offset)
+            + b"\x0c\x24"
+            + dict_int(store_offset)
+            + b"\x18"
+        )
+
+    def font_dict(private_offset):
+        return dict_int(0) + dict_int(private_offset) + b"\x12"
+
+    top_size = len(top_dict(0, 0, 0))
+    header = struct.pack(">BBBH", 2, 0, 5, top_size)
+    store_offset = len(header) + top_size + len(global_subrs)
+    char_strings_offset = store_offset + len(store)
+    fd_array_offset = char_strings_offset + len(char_strings)
+    private_offset = fd_array_offset + len(index([font_dict(0)], 4))
+
+    return (
+        header
+        + top_dict(char_strings_offset, fd_array_offset, store_offset)
+        + global_subrs
+        + store
+        + char_strings
+        + index([font_dict(private_offset)], 4)
+    )
+
+
+def cmap(mapping):
+    """Format 4 subtable with a segment for each character"""
+    segments_list = sorted(mapping.items()) + [(0xFFFF, 0)]
+    seg_count = len(segments_list)
+    ends = b"".join(struct.pack(">H", c) for c, _ in segments_list)
+    starts = b"".join(struct.pack(">H", c) for c, _ in segments_list)
+    deltas = b"".join(struct.pack(">H", (g - c) & 0xFFFF if c != 0xFFFF else 1) for c, g in segments_list)
+    range_offsets = b"\x00\x00" * seg_count
+
+    search = 1
+    while search * 2 <= seg_count:
+        search *= 2
+    entry_selector = search.bit_length() - 1
+
+    body = struct.pack(">HHH", seg_count * 2, search * 2, entry_selector)
+    body += struct.pack(">H", seg_count * 2 - search * 2)
+    body += ends + b"\x00\x00" + starts + deltas + range_offsets
+    subtable = struct.pack(">HHH", 4, 6 + len(body), 0) + body
+
+    return struct.pack(">HHHHIHHI", 0, 2, 0, 3, 20, 3, 1, 20) + subtable
+
+
+def fvar():
+    axis = b"wght" + struct.pack(">iiiHH", 400 << 16, 500 << 16, 700 << 16, 0, 2)
+    return struct.pack(">HHHHHHHH", 1, 0, 16, 2, 1, 20, 0, 8) + axis
+
+
+def checksum(data):
+    data = data + b"\x00" * (-len(data) % 4)
+    return sum(struct.unpack(">%dI" % (len(data) // 4), data)) & 0xFFFFFFFF
+
+
+def sfnt(tables):
+    tags = sorted(tables)
+    entry_selector = max(1 << (len(tags).bit_length() - 1), 1)
+    out = b"OTTO" + struct.pack(
+        ">HHHH",
+        len(tags),
+        entry_selector * 16,
+        entry_selector.bit_length() - 1,
+        len(tags) * 16 - entry_selector * 16,
+    )
+
+    offset = 12 + 16 * len(tags)
+    body = b""
+    for tag in tags:
+        data = tables[tag]
+        out += tag.encode("latin1") + struct.pack(">III", checksum(data), offset + len(body), len(data))
+        body += data + b"\x00" * (-len(data) % 4)
+
+    font = bytearray(out + body)
+    head = 12 + 16 * len(tags) + sum(len(tables[t]) + (-len(tables[t]) % 4) for t in tags[: tags.index("head")])
+    font[head + 8 : head + 12] = struct.pack(">I", (0xB1B0AFBA - checksum(bytes(font))) & 0xFFFFFFFF)
+    return bytes(font)
+
+
+def tables(font, glyphs):
+    hmtx = b"".join(struct.pack(">Hh", *font.metric(source)) for (_, source, _, _) in glyphs)
+
+    head = bytearray(font.tables["head"])
+    head[8:12] = b"\x00\x00\x00\x00"
+    hhea = bytearray(font.tables["hhea"])
+    hhea[34:36] = struct.pack(">H", len(glyphs))
+    post = bytearray(font.tables["post"][:32])
+    post[0:4] = struct.pack(">I", 0x00030000)
+
+    mapping = {char: glyph for glyph, (_, _, char, _) in enumerate(glyphs) if char is not None}
+
+    return {
+        "head": bytes(head),
+        "hhea": bytes(hhea),
+        "maxp": struct.pack(">IH", 0x00005000, len(glyphs)),
+        "hmtx": hmtx,
+        "cmap": cmap(mapping),
+        "OS/2": font.tables["OS/2"],
+        "name": font.tables["name"],
+        "post": bytes(post),
+    }
+
+
+def main():
+    font = Font(SOURCE)
+
+    cff_tables = tables(font, GLYPHS + [SEAC[:3] + (391,)])
+    cff_tables["CFF "] = build_cff(font)
+    with open(os.path.join(DIR, "FiraMono-Medium-CFF.otf"), "wb") as f:
+        f.write(sfnt(cff_tables))
+
+    cff2_tables = tables(font, GLYPHS)
+    cff2_tables["CFF2"] = build_cff2(font)
+    cff2_tables["fvar"] = fvar()
+    with open(os.path.join(DIR, "FiraMono-Medium-CFF2.otf"), "wb") as f:
+        f.write(sfnt(cff2_tables))
+
+
+if __name__ == "__main__":
+    main()
@@ -2,7 +2,24 @@
 use libfuzzer_sys::fuzz_target;
 
 use rand::{thread_rng, Rng};
-use ttf2mesh::{Quality, TTFFile};
+use ttf2mesh::{OwnedMesh, Quality, TTFFile};
+
+/// Whatever the font, the mesher only produces faces within the vertex range and finite
+/// vertices. A repaired mesh only has problems that can't be fixed left, i.e. non-manifold
+/// edges and winding
+fn check_mesh(mesh: OwnedMesh) {
+    let report = mesh.validate();
+
+    assert!(report.out_of_bounds_faces.is_empty());
+    assert!(report.non_finite_vertices.is_empty());
+
+    let report = mesh.repair().validate();
+
+    assert!(report.out_of_bounds_faces.is_empty());
+    assert!(report.zero_area_faces.is_empty());
+    assert!(report.duplicate_vertices.is_empty());
+    assert!(report.non_finite_vertices.is_empty());
+}
 
 fuzz_target!(|data: &[u8]| {
     let mut font = match TTFFile::from_buffer_vec(data.to_vec()) {
@@ -28,12 +45,12 @@ fuzz_target!(|data: &[u8]| {
     };
 
     match glyph.to_2d_mesh(Quality::High) {
-        Ok(_) => (),  //println!("2d mesh ok"),
+        Ok(mesh) => check_mesh(mesh.to_owned_mesh()),
         Err(_) => (), //println!("To 2d mesh failed"),
     }
 
     match glyph.to_3d_mesh(Quality::High, 0.5) {
-        Ok(_) => (),  //println!("3d mesh ok"),
+        Ok(mesh) => check_mesh(mesh.to_owned_mesh()),
         Err(_) => (), // println!("To 3d mesh failed"),
     }
 });
@@ -0,0 +1,73 @@
+//! Unicode bidirectional algorithm (UAX #9) for text layout. Without the `bidi` feature, a line
+//! is laid out as a single run
+use std::ops::Range;
+
+#[cfg(feature = "bidi")]
+use unicode_bidi::ParagraphBidiInfo;
+
+/// Split a line into directional runs, in visual (left-to-right) order. Returns byte ranges of
+/// the line with a flag for right-to-left runs, whose characters are drawn in reverse order.
+/// Without the `bidi` feature, the direction is unknown (`None`)
+#[cfg(feature = "bidi")]
+pub(crate) fn visual_runs(line: &str) -> Vec<(Range<usize>, Option<bool>)> {
+    let info = ParagraphBidiInfo::new(line, None);
+    if info.is_pure_ltr {
+        return vec![(0..line.len(), Some(false))];
+    }
+
+    let (levels, runs) = info.visual_runs(0..line.len());
+
+    runs.into_iter()
+        .map(|run| {
+            let rtl = levels[run.start].is_rtl();
+            (run, Some(rtl))
+        })
+        .collect()
+}
+
+#[cfg(not(feature = "bidi"))]
+pub(crate) fn visual_runs(line: &str) -> Vec<(Range<usize>, Option<bool>)> {
+    vec![(0..line.len(), None)]
+}
+
+/// Mirrored form of a character in right-to-left runs, e.g. `(` becomes `)`
+#[cfg(feature = "bidi")]
+pub(crate) fn mirror(char: char) -> char {
+    unicode_bidi_mirroring::get_mirrored(char).unwrap_or(char)
+}
+
+#[cfg(not(feature = "bidi"))]
+pub(crate) fn mirror(char: char) -> char {
+    char
+}
+
+#[cfg(all(test, feature = "bidi"))]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_visual_runs() {
+        assert_eq!(visual_runs("abc"), vec![(0..3, Some(false))]);
+
+        // "ab אב cd": the Hebrew run stays between the Latin ones
+        let line = "ab \u{5d0}\u{5d1} cd";
+        assert_eq!(
+            visual_runs(line),
+            vec![
+                (0..3, Some(false)),
+                (3..7, Some(true)),
+                (7..10, Some(false))
+            ]
+        );
+
+        // a right-to-left paragraph puts the Latin run on the left
+        let line = "\u{5d0}\u{5d1} cd";
+        assert_eq!(
+            visual_runs(line),
+            vec![(5..7, Some(false)), (0..5, Some(true))]
+        );
+
+        assert_eq!(mirror('('), ')');
+        assert_eq!(mirror('a'), 'a');
+    }
+}
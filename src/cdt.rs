@@ -0,0 +1,799 @@
+//! Meshing of glyph outlines in Rust with a constrained Delaunay triangulation. Used instead of
+//! the C mesher with the `pure-rust` feature; fonts are still loaded by the C library
+use std::{
+    collections::{HashMap, HashSet},
+    f64::consts::PI,
+};
+
+use crate::{quality::contour_segments, Error};
+
+/// Outline points are snapped to a grid of `1 / GRID` em, so that equal points are merged and the
+/// geometric predicates work on exact values
+const GRID: f64 = 1048576.0;
+
+/// Points closer than this (in em units) to a segment split it
+const ON_SEGMENT: f64 = 1.0 / GRID;
+
+type Point = (f64, f64);
+type Triangle = [usize; 3];
+
+/// A 2d mesh: vertices and counter-clockwise faces
+pub(crate) type Mesh2d = (Vec<(f32, f32)>, Vec<(i32, i32, i32)>);
+
+/// A 3d mesh: vertices, faces and normals
+pub(crate) type Mesh3d = (
+    Vec<(f32, f32, f32)>,
+    Vec<(i32, i32, i32)>,
+    Vec<(f32, f32, f32)>,
+);
+
+/// Triangulate the area of a glyph outline (TrueType contour points, `true` for on-curve points)
+/// filled with the nonzero rule. Curves are split into `quality` segments per full turn, like
+/// the C mesher does. Fails when an outline segment can not be made an edge of the
+/// triangulation
+pub(crate) fn triangulate(
+    contours: &[Vec<(f32, f32, bool)>],
+    quality: u8,
+) -> Result<Mesh2d, Error> {
+    let polylines = contours
+        .iter()
+        .map(|contour| flatten(contour, quality))
+        .filter(|polyline| polyline.len() >= 3)
+        .collect::<Vec<_>>();
+
+    let mut cdt = Cdt::default();
+    let mut ids = HashMap::new();
+    let mut segments = Vec::new();
+
+    for polyline in &polylines {
+        let indices = polyline
+            .iter()
+            .map(|&p| *ids.entry(key(p)).or_insert_with(|| cdt.push_point(p)))
+            .collect::<Vec<_>>();
+
+        for (i, &a) in indices.iter().enumerate() {
+            let b = indices[(i + 1) % indices.len()];
+            if a != b {
+                segments.push((a, b));
+            }
+        }
+    }
+
+    if cdt.points.len() < 3 {
+        return Ok((Vec::new(), Vec::new()));
+    }
+
+    for p in segment_intersections(&cdt.points, &segments) {
+        ids.entry(key(p)).or_insert_with(|| cdt.push_point(p));
+    }
+    let segments = split_at_points(&cdt.points, &segments);
+
+    let real = cdt.points.len();
+    cdt.add_super_triangle();
+
+    // points are inserted along a z-order curve, so that the walks locating them are short
+    let origin = cdt.points[..real]
+        .iter()
+        .fold((i64::MAX, i64::MAX), |min, &p| {
+            let (x, y) = key(p);
+            (min.0.min(x), min.1.min(y))
+        });
+    let mut order = (0..real).collect::<Vec<_>>();
+    order.sort_by_key(|&index| z_order(cdt.points[index], origin));
+    for index in order {
+        cdt.insert_point(index);
+    }
+    for &(a, b) in &segments {
+        cdt.insert_constraint(a, b)?;
+    }
+
+    // keep the triangles inside the outline, with the vertices they use
+    let mut vertices = Vec::new();
+    let mut remap = HashMap::new();
+    let mut faces = Vec::new();
+
+    for region in cdt.regions() {
+        // the outline does not cross a region: all of its triangles have the winding number of
+        // the centroid of one of them
+        let triangle = cdt.triangles[region[0]].unwrap();
+        let p = |i: usize| cdt.points[triangle[i]];
+        let centroid = (
+            (p(0).0 + p(1).0 + p(2).0) / 3.0,
+            (p(0).1 + p(1).1 + p(2).1) / 3.0,
+        );
+        if winding_number(centroid, &polylines) == 0 {
+            continue;
+        }
+
+        for triangle in region.into_iter().filter_map(|index| cdt.triangles[index]) {
+            if triangle.iter().any(|&index| index >= real) {
+                continue;
+            }
+
+            let mut vertex = |index: usize| {
+                *remap.entry(index).or_insert_with(|| {
+                    let (x, y) = cdt.points[index];
+                    vertices.push((x as f32, y as f32));
+                    vertices.len() as i32 - 1
+                })
+            };
+            faces.push((
+                vertex(triangle[0]),
+                vertex(triangle[1]),
+                vertex(triangle[2]),
+            ));
+        }
+    }
+
+    Ok((vertices, faces))
+}
+
+/// Extrude a 2d mesh to `depth`, centered on `z = 0`: the front face at `z = depth / 2` and the
+/// back face at `z = -depth / 2`, joined by flat shaded walls along the mesh boundary. Returns
+/// vertices, faces and normals
+pub(crate) fn extrude((vertices, faces): &Mesh2d, depth: f32) -> Mesh3d {
+    let (front, back) = (depth / 2.0, -depth / 2.0);
+    let count = vertices.len() as i32;
+
+    let mut vertices_3d = Vec::new();
+    let mut normals = Vec::new();
+    for &(z, normal) in &[(front, 1.0), (back, -1.0)] {
+        for &(x, y) in vertices {
+            vertices_3d.push((x, y, z));
+            normals.push((0.0, 0.0, normal));
+        }
+    }
+
+    let mut faces_3d = faces.clone();
+    faces_3d.extend(
+        faces
+            .iter()
+            .map(|&(a, b, c)| (a + count, c + count, b + count)),
+    );
+
+    // edges of a single face, with the face on their left
+    let mut edges = HashSet::new();
+    for &(a, b, c) in faces {
+        for &edge in &[(a, b), (b, c), (c, a)] {
+            edges.insert(edge);
+        }
+    }
+
+    let mut boundary = edges
+        .iter()
+        .filter(|&&(a, b)| !edges.contains(&(b, a)))
+        .copied()
+        .collect::<Vec<_>>();
+    boundary.sort_unstable();
+
+    for (a, b) in boundary {
+        let (pa, pb) = (vertices[a as usize], vertices[b as usize]);
+        let normal = crate::owned_mesh::normalize((pb.1 - pa.1, pa.0 - pb.0, 0.0));
+
+        let first = vertices_3d.len() as i32;
+        for &(p, z) in &[(pa, back), (pb, back), (pb, front), (pa, front)] {
+            vertices_3d.push((p.0, p.1, z));
+            normals.push(normal);
+        }
+
+        faces_3d.push((first, first + 1, first + 2));
+        faces_3d.push((first, first + 2, first + 3));
+    }
+
+    (vertices_3d, faces_3d, normals)
+}
+
+/// Closed polyline of a contour, snapped to the grid and without repeated points
+fn flatten(contour: &[(f32, f32, bool)], quality: u8) -> Vec<Point> {
+    let mut points: Vec<Point> = Vec::new();
+    let mut push = |p: Point| {
+        let p = snap(p);
+        if points.last() != Some(&p) {
+            points.push(p);
+        }
+    };
+
+    for (start, control, end) in contour_segments(contour) {
+        let (p0, p2) = (to_f64(start), to_f64(end));
+        let p1 = match control {
+            Some(control) => to_f64(control),
+            None => {
+                push(p2);
+                continue;
+            }
+        };
+
+        // turning angle of the curve
+        let (u, v) = ((p1.0 - p0.0, p1.1 - p0.1), (p2.0 - p1.0, p2.1 - p1.1));
+        let angle = (u.0 * v.1 - u.1 * v.0).atan2(u.0 * v.0 + u.1 * v.1).abs();
+        let steps = ((angle / (2.0 * PI) * quality as f64).ceil() as usize).max(1);
+
+        for step in 1..=steps {
+            let t = step as f64 / steps as f64;
+            let s = 1.0 - t;
+            push((
+                s * s * p0.0 + 2.0 * s * t * p1.0 + t * t * p2.0,
+                s * s * p0.1 + 2.0 * s * t * p1.1 + t * t * p2.1,
+            ));
+        }
+    }
+
+    while points.len() > 1 && points.first() == points.last() {
+        points.pop();
+    }
+
+    points
+}
+
+fn to_f64(p: (f32, f32)) -> Point {
+    (p.0 as f64, p.1 as f64)
+}
+
+fn snap(p: Point) -> Point {
+    ((p.0 * GRID).round() / GRID, (p.1 * GRID).round() / GRID)
+}
+
+fn key(p: Point) -> (i64, i64) {
+    ((p.0 * GRID).round() as i64, (p.1 * GRID).round() as i64)
+}
+
+/// Position of a point on a z-order curve over the grid, starting from `origin`
+fn z_order(p: Point, origin: (i64, i64)) -> u64 {
+    let spread = |v: i64| {
+        let mut v = v as u64 & 0xFFFF_FFFF;
+        v = (v | v << 16) & 0x0000_FFFF_0000_FFFF;
+        v = (v | v << 8) & 0x00FF_00FF_00FF_00FF;
+        v = (v | v << 4) & 0x0F0F_0F0F_0F0F_0F0F;
+        v = (v | v << 2) & 0x3333_3333_3333_3333;
+        (v | v << 1) & 0x5555_5555_5555_5555
+    };
+
+    let (x, y) = key(p);
+    spread(x - origin.0) | spread(y - origin.1) << 1
+}
+
+/// Points where segments cross each other. Segments are swept by their x range, so that only
+/// the ones overlapping on x are compared
+fn segment_intersections(points: &[Point], segments: &[(usize, usize)]) -> Vec<Point> {
+    let x_range = |(a, b): (usize, usize)| {
+        let (x0, x1) = (points[a].0, points[b].0);
+        (x0.min(x1), x0.max(x1))
+    };
+
+    let mut order = (0..segments.len()).collect::<Vec<_>>();
+    order.sort_by(|&i, &j| {
+        let (x, y) = (x_range(segments[i]).0, x_range(segments[j]).0);
+        x.partial_cmp(&y).unwrap()
+    });
+
+    let mut intersections = Vec::new();
+
+    for (n, &i) in order.iter().enumerate() {
+        let (a, b) = segments[i];
+        let max_x = x_range(segments[i]).1;
+
+        for &j in &order[n + 1..] {
+            if x_range(segments[j]).0 > max_x {
+                break;
+            }
+
+            let (c, d) = segments[j];
+            if a == c || a == d || b == c || b == d {
+                continue;
+            }
+
+            let (p, q, r, s) = (points[a], points[b], points[c], points[d]);
+            if orient(p, q, r) * orient(p, q, s) >= 0.0 || orient(r, s, p) * orient(r, s, q) >= 0.0
+            {
+                continue;
+            }
+
+            let t = orient(r, s, p) / (orient(r, s, p) - orient(r, s, q));
+            intersections.push(snap((p.0 + t * (q.0 - p.0), p.1 + t * (q.1 - p.1))));
+        }
+    }
+
+    intersections
+}
+
+/// Split segments at the points lying on them. Only the points within the x range of a segment
+/// are tested
+fn split_at_points(points: &[Point], segments: &[(usize, usize)]) -> Vec<(usize, usize)> {
+    let mut by_x = (0..points.len()).collect::<Vec<_>>();
+    by_x.sort_by(|&i, &j| points[i].0.partial_cmp(&points[j].0).unwrap());
+
+    let mut split = HashSet::new();
+
+    for &(a, b) in segments {
+        let (p, q) = (points[a], points[b]);
+        let (dx, dy) = (q.0 - p.0, q.1 - p.1);
+        let length = dx * dx + dy * dy;
+
+        let (min_x, max_x) = (p.0.min(q.0) - ON_SEGMENT, p.0.max(q.0) + ON_SEGMENT);
+        let start = by_x.partition_point(|&index| points[index].0 < min_x);
+
+        let mut on_segment = by_x[start..]
+            .iter()
+            .take_while(|&&index| points[index].0 <= max_x)
+            .filter(|&&index| index != a && index != b)
+            .filter_map(|&index| {
+                let r = points[index];
+                let t = ((r.0 - p.0) * dx + (r.1 - p.1) * dy) / length;
+                let distance = orient(p, q, r).abs() / length.sqrt();
+
+                if t > 0.0 && t < 1.0 && distance <= ON_SEGMENT {
+                    Some((t, index))
+                } else {
+                    None
+                }
+            })
+            .collect::<Vec<_>>();
+        on_segment.sort_by(|x, y| x.0.partial_cmp(&y.0).unwrap());
+
+        let mut from = a;
+        for to in on_segment
+            .into_iter()
+            .map(|(_, index)| index)
+            .chain(Some(b))
+        {
+            split.insert((from.min(to), from.max(to)));
+            from = to;
+        }
+    }
+
+    let mut split = split.into_iter().collect::<Vec<_>>();
+    split.sort_unstable();
+    split
+}
+
+/// Nonzero winding number of the polylines around a point
+fn winding_number(p: Point, polylines: &[Vec<Point>]) -> i32 {
+    let mut winding = 0;
+
+    for polyline in polylines {
+        for (i, &a) in polyline.iter().enumerate() {
+            let b = polyline[(i + 1) % polyline.len()];
+
+            if a.1 <= p.1 && b.1 > p.1 && orient(a, b, p) > 0.0 {
+                winding += 1;
+            } else if a.1 > p.1 && b.1 <= p.1 && orient(a, b, p) < 0.0 {
+                winding -= 1;
+            }
+        }
+    }
+
+    winding
+}
+
+/// Twice the signed area of the triangle `abc`, positive when counter-clockwise
+fn orient(a: Point, b: Point, c: Point) -> f64 {
+    (b.0 - a.0) * (c.1 - a.1) - (b.1 - a.1) * (c.0 - a.0)
+}
+
+/// Positive when `d` is inside the circumcircle of the counter-clockwise triangle `abc`
+fn in_circle(a: Point, b: Point, c: Point, d: Point) -> f64 {
+    let (ax, ay) = (a.0 - d.0, a.1 - d.1);
+    let (bx, by) = (b.0 - d.0, b.1 - d.1);
+    let (cx, cy) = (c.0 - d.0, c.1 - d.1);
+
+    (ax * ax + ay * ay) * (bx * cy - cx * by) - (bx * bx + by * by) * (ax * cy - cx * ay)
+        + (cx * cx + cy * cy) * (ax * by - bx * ay)
+}
+
+/// Incremental Delaunay triangulation (Bowyer-Watson) with constrained edges inserted by
+/// retriangulating the triangles they cross. Points and edges are located by walking between
+/// neighbouring triangles
+#[derive(Default)]
+struct Cdt {
+    points: Vec<Point>,
+    /// Counter-clockwise triangles, `None` for removed ones
+    triangles: Vec<Option<Triangle>>,
+    /// Triangle of each directed edge
+    edges: HashMap<(usize, usize), usize>,
+    /// Constrained edges, lowest vertex first
+    constraints: HashSet<(usize, usize)>,
+    /// A triangle of each vertex
+    vertex_triangles: Vec<usize>,
+    /// The last added triangle, where point location starts
+    last: usize,
+}
+
+impl Cdt {
+    fn push_point(&mut self, p: Point) -> usize {
+        self.points.push(p);
+        self.points.len() - 1
+    }
+
+    /// A triangle around all points, to insert the points into
+    fn add_super_triangle(&mut self) {
+        let (mut min, mut max) = (self.points[0], self.points[0]);
+        for p in &self.points {
+            min = (min.0.min(p.0), min.1.min(p.1));
+            max = (max.0.max(p.0), max.1.max(p.1));
+        }
+
+        let size = (max.0 - min.0).max(max.1 - min.1) + 1.0;
+        let center = ((min.0 + max.0) / 2.0, (min.1 + max.1) / 2.0);
+
+        let first = self.points.len();
+        self.points.push((center.0 - 20.0 * size, center.1 - size));
+        self.points.push((center.0 + 20.0 * size, center.1 - size));
+        self.points.push((center.0, center.1 + 20.0 * size));
+        self.add_triangle([first, first + 1, first + 2]);
+    }
+
+    fn add_triangle(&mut self, triangle: Triangle) {
+        let index = self.triangles.len();
+        self.vertex_triangles.resize(self.points.len(), 0);
+        for i in 0..3 {
+            self.edges
+                .insert((triangle[i], triangle[(i + 1) % 3]), index);
+            self.vertex_triangles[triangle[i]] = index;
+        }
+        self.triangles.push(Some(triangle));
+        self.last = index;
+    }
+
+    fn remove_triangle(&mut self, index: usize) {
+        if let Some(triangle) = self.triangles[index].take() {
+            for i in 0..3 {
+                let edge = (triangle[i], triangle[(i + 1) % 3]);
+                if self.edges.get(&edge) == Some(&index) {
+                    self.edges.remove(&edge);
+                }
+            }
+        }
+    }
+
+    fn point(&self, triangle: Triangle, i: usize) -> Point {
+        self.points[triangle[i]]
+    }
+
+    /// Triangle containing a point, walking from the last added triangle across the edges the
+    /// point is behind. `None` when the walk does not end, e.g. with rounding errors
+    fn locate(&self, p: Point) -> Option<usize> {
+        let mut current = self.last;
+
+        for _ in 0..self.triangles.len() {
+            let triangle = self.triangles[current]?;
+            let behind = (0..3).find(|&i| {
+                orient(
+                    self.point(triangle, i),
+                    self.point(triangle, (i + 1) % 3),
+                    p,
+                ) < 0.0
+            });
+
+            match behind {
+                Some(i) => current = *self.edges.get(&(triangle[(i + 1) % 3], triangle[i]))?,
+                None => return Some(current),
+            }
+        }
+
+        None
+    }
+
+    /// Replace the triangles whose circumcircle contains the point with a fan around it
+    fn insert_point(&mut self, index: usize) {
+        let p = self.points[index];
+
+        let start = self.locate(p).or_else(|| {
+            self.triangles.iter().position(|triangle| match triangle {
+                Some(t) => {
+                    (0..3).all(|i| orient(self.point(*t, i), self.point(*t, (i + 1) % 3), p) >= 0.0)
+                }
+                None => false,
+            })
+        });
+        let start = match start {
+            Some(start) => start,
+            None => return,
+        };
+
+        let mut cavity = HashSet::new();
+        let mut stack = vec![start];
+        cavity.insert(start);
+
+        while let Some(current) = stack.pop() {
+            let triangle = self.triangles[current].unwrap();
+            for i in 0..3 {
+                let edge = (triangle[(i + 1) % 3], triangle[i]);
+                if let Some(&neighbour) = self.edges.get(&edge) {
+                    let t = self.triangles[neighbour].unwrap();
+                    if !cavity.contains(&neighbour)
+                        && in_circle(self.point(t, 0), self.point(t, 1), self.point(t, 2), p) > 0.0
+                    {
+                        cavity.insert(neighbour);
+                        stack.push(neighbour);
+                    }
+                }
+            }
+        }
+
+        let boundary = self.boundary(&cavity);
+        for &triangle in &cavity {
+            self.remove_triangle(triangle);
+        }
+        for (a, b) in boundary {
+            self.add_triangle([a, b, index]);
+        }
+    }
+
+    /// Make `a`-`b` an edge of the triangulation. The triangles crossed by the edge are found by
+    /// turning around `a` to the one the edge leaves through, then walking across the crossed
+    /// edges. Fails when they do not form a polygon which the edge splits
+    fn insert_constraint(&mut self, a: usize, b: usize) -> Result<(), Error> {
+        if a == b {
+            return Ok(());
+        }
+        if self.edges.contains_key(&(a, b)) || self.edges.contains_key(&(b, a)) {
+            self.constraints.insert((a.min(b), a.max(b)));
+            return Ok(());
+        }
+
+        let (pa, pb) = (self.points[a], self.points[b]);
+        let opposite = |triangle: Triangle, vertex: usize| {
+            let i = triangle.iter().position(|&v| v == vertex)?;
+            Some((triangle[(i + 1) % 3], triangle[(i + 2) % 3]))
+        };
+
+        // the triangle around `a` with the edge on its right vertex `u` and left vertex `v`
+        let first = self.vertex_triangles[a];
+        let mut current = first;
+        let (mut u, mut v) = loop {
+            let triangle = self.triangles[current].ok_or(Error::Glyph2MeshError)?;
+            let (u, v) = opposite(triangle, a).ok_or(Error::Glyph2MeshError)?;
+            let (pu, pv) = (self.points[u], self.points[v]);
+
+            // continue from a vertex exactly on the edge
+            if orient(pa, pb, pu) == 0.0
+                && (pu.0 - pa.0) * (pb.0 - pa.0) + (pu.1 - pa.1) * (pb.1 - pa.1) > 0.0
+            {
+                self.insert_constraint(a, u)?;
+                return self.insert_constraint(u, b);
+            }
+            if orient(pa, pb, pu) < 0.0 && orient(pa, pb, pv) > 0.0 {
+                break (u, v);
+            }
+
+            current = *self.edges.get(&(a, v)).ok_or(Error::Glyph2MeshError)?;
+            if current == first {
+                return Err(Error::Glyph2MeshError);
+            }
+        };
+
+        // walk across the crossed edges to `b`, or to a vertex exactly on the edge
+        let mut crossed = HashSet::new();
+        crossed.insert(current);
+        let end = loop {
+            let next = *self.edges.get(&(v, u)).ok_or(Error::Glyph2MeshError)?;
+            crossed.insert(next);
+
+            let triangle = self.triangles[next].ok_or(Error::Glyph2MeshError)?;
+            let (_, w) = opposite(triangle, v).ok_or(Error::Glyph2MeshError)?;
+            let side = orient(pa, pb, self.points[w]);
+
+            if w == b || side == 0.0 {
+                break w;
+            } else if side < 0.0 {
+                u = w;
+            } else {
+                v = w;
+            }
+        };
+
+        // the crossed triangles form a polygon, split by the edge into two chains of vertices
+        let next = self
+            .boundary(&crossed)
+            .into_iter()
+            .collect::<HashMap<_, _>>();
+        let (right, left) = match (chain(&next, a, end), chain(&next, end, a)) {
+            (Some(right), Some(left)) => (right, left),
+            _ => return Err(Error::Glyph2MeshError),
+        };
+
+        for &triangle in &crossed {
+            self.remove_triangle(triangle);
+        }
+        self.fill(&right);
+        self.fill(&left);
+
+        self.insert_constraint(a, end)?;
+        self.insert_constraint(end, b)
+    }
+
+    /// Groups of triangles connected by edges which are not constrained
+    fn regions(&self) -> Vec<Vec<usize>> {
+        let mut regions = Vec::new();
+        let mut visited = HashSet::new();
+
+        for start in (0..self.triangles.len()).filter(|&index| self.triangles[index].is_some()) {
+            if !visited.insert(start) {
+                continue;
+            }
+
+            let mut region = vec![start];
+            let mut stack = vec![start];
+            while let Some(current) = stack.pop() {
+                let triangle = self.triangles[current].unwrap();
+                for i in 0..3 {
+                    let (a, b) = (triangle[i], triangle[(i + 1) % 3]);
+                    if self.constraints.contains(&(a.min(b), a.max(b))) {
+                        continue;
+                    }
+                    if let Some(&neighbour) = self.edges.get(&(b, a)) {
+                        if visited.insert(neighbour) {
+                            region.push(neighbour);
+                            stack.push(neighbour);
+                        }
+                    }
+                }
+            }
+
+            regions.push(region);
+        }
+
+        regions
+    }
+
+    /// Directed edges of a set of triangles that are not shared between them
+    fn boundary(&self, triangles: &HashSet<usize>) -> Vec<(usize, usize)> {
+        let mut boundary = Vec::new();
+
+        for &index in triangles {
+            let triangle = self.triangles[index].unwrap();
+            for i in 0..3 {
+                let (a, b) = (triangle[i], triangle[(i + 1) % 3]);
+                match self.edges.get(&(b, a)) {
+                    Some(neighbour) if triangles.contains(neighbour) => (),
+                    _ => boundary.push((a, b)),
+                }
+            }
+        }
+
+        boundary
+    }
+
+    /// Triangulate a polygon of counter-clockwise vertices, closed by an edge from the last one
+    /// to the first one, choosing Delaunay triangles on the closing edge (Anglada's algorithm)
+    fn fill(&mut self, polygon: &[usize]) {
+        if polygon.len() < 3 {
+            return;
+        }
+
+        let (a, b) = (
+            self.points[polygon[0]],
+            self.points[polygon[polygon.len() - 1]],
+        );
+        let mut c = 1;
+        for i in 2..polygon.len() - 1 {
+            if in_circle(a, self.points[polygon[c]], b, self.points[polygon[i]]) > 0.0 {
+                c = i;
+            }
+        }
+
+        self.add_triangle([polygon[0], polygon[c], polygon[polygon.len() - 1]]);
+        self.fill(&polygon[..=c]);
+        self.fill(&polygon[c..]);
+    }
+}
+
+/// Vertices from `from` to `to` following the edges of a polygon
+fn chain(next: &HashMap<usize, usize>, from: usize, to: usize) -> Option<Vec<usize>> {
+    let mut chain = vec![from];
+
+    while chain[chain.len() - 1] != to {
+        if chain.len() > next.len() {
+            return None;
+        }
+        chain.push(*next.get(&chain[chain.len() - 1])?);
+    }
+
+    Some(chain)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::OwnedMesh;
+
+    /// Clockwise square contour, like TrueType outer contours
+    fn square(x: f32, y: f32, size: f32) -> Vec<(f32, f32, bool)> {
+        vec![
+            (x, y, true),
+            (x, y + size, true),
+            (x + size, y + size, true),
+            (x + size, y, true),
+        ]
+    }
+
+    fn area((vertices, faces): &Mesh2d) -> f32 {
+        faces
+            .iter()
+            .map(|&(a, b, c)| {
+                let (a, b, c) = (
+                    vertices[a as usize],
+                    vertices[b as usize],
+                    vertices[c as usize],
+                );
+                ((b.0 - a.0) * (c.1 - a.1) - (b.1 - a.1) * (c.0 - a.0)) / 2.0
+            })
+            .sum()
+    }
+
+    fn owned((vertices, faces): &Mesh2d) -> OwnedMesh {
+        OwnedMesh {
+            vertices: vertices.iter().map(|&(x, y)| (x, y, 0.0)).collect(),
+            faces: faces.clone(),
+            normals: Vec::new(),
+        }
+    }
+
+    #[test]
+    fn test_triangulate_hole() {
+        let mut hole = square(0.25, 0.25, 0.5);
+        hole.reverse();
+
+        let mesh = triangulate(&[square(0.0, 0.0, 1.0), hole], 20).unwrap();
+        assert!((area(&mesh) - 0.75).abs() < 1e-6);
+        assert_eq!(mesh.0.len(), 8);
+        assert!(owned(&mesh).validate().is_valid());
+    }
+
+    #[test]
+    fn test_triangulate_overlap() {
+        // two overlapping squares are filled as their union
+        let mesh = triangulate(&[square(0.0, 0.0, 1.0), square(0.5, 0.5, 1.0)], 20).unwrap();
+        assert!((area(&mesh) - 1.75).abs() < 1e-6);
+        assert!(owned(&mesh).validate().is_valid());
+    }
+
+    #[test]
+    fn test_triangulate_curves() {
+        // a rounded square: quadratic curves between the edge midpoints, with the corners as
+        // control points
+        let circle = vec![
+            (0.0, 0.0, false),
+            (0.0, 1.0, false),
+            (1.0, 1.0, false),
+            (1.0, 0.0, false),
+        ];
+
+        let coarse = triangulate(std::slice::from_ref(&circle), 10).unwrap();
+        let fine = triangulate(&[circle], 100).unwrap();
+        assert!(coarse.0.len() < fine.0.len());
+
+        // each corner cuts off a third of the triangle of its curve
+        assert!((area(&fine) - 5.0 / 6.0).abs() < 1e-3);
+        assert!(owned(&fine).validate().is_valid());
+
+        let (vertices, faces, normals) = extrude(&fine, 0.5);
+        assert_eq!(vertices.len(), normals.len());
+        assert_eq!(faces.len(), fine.1.len() * 2 + fine.0.len() * 2);
+    }
+
+    #[test]
+    fn test_triangulate_dense() {
+        // a grid of round dots at the highest quality: 25 contours of 256 points. Locating points
+        // and edges by scanning all triangles was about ten times slower
+        let contours = (0..25)
+            .map(|i| {
+                let (x, y) = ((i % 5) as f32, (i / 5) as f32);
+                vec![
+                    (x, y, false),
+                    (x, y + 0.9, false),
+                    (x + 0.9, y + 0.9, false),
+                    (x + 0.9, y, false),
+                ]
+            })
+            .collect::<Vec<_>>();
+
+        let start = std::time::Instant::now();
+        let mesh = triangulate(&contours, 255).unwrap();
+        assert!(start.elapsed().as_secs() < 10);
+
+        assert_eq!(mesh.0.len(), 25 * 256);
+        assert!((area(&mesh) - 25.0 * 0.81 * 5.0 / 6.0).abs() < 1e-2);
+        assert!(owned(&mesh).validate().is_valid());
+    }
+}
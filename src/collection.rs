@@ -0,0 +1,213 @@
+//! Font collections (`.ttc`): listing faces and extracting a single face
+use crate::{
+    sfnt::{self, Sfnt, SfntBuilder},
+    tables::name,
+    Error,
+};
+
+/// A face of a font file. See [`crate::TTFFile::collection_faces`]
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct FaceInfo {
+    /// Face index, to be used with [`crate::TTFFile::from_file_face`]
+    pub index: usize,
+    /// Family name, e.g. `Noto Sans CJK JP`
+    pub family: String,
+    /// Style name, e.g. `Bold`
+    pub style: String,
+    /// Full name, e.g. `Noto Sans CJK JP Bold`
+    pub full_name: String,
+    pub weight: Weight,
+    pub italic: Italic,
+}
+
+/// Font weight, as in the `OS/2` table (`usWeightClass`)
+///
+/// Usage:
+/// ```rust
+/// # use ttf2mesh::Weight;
+/// assert_eq!(Weight::from_value(500), Weight::Medium);
+/// assert_eq!(Weight::Custom(450).as_u16(), 450);
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum Weight {
+    /// 100
+    Thin,
+    /// 200
+    ExtraLight,
+    /// 300
+    Light,
+    /// 400
+    Regular,
+    /// 500
+    Medium,
+    /// 600
+    SemiBold,
+    /// 700
+    Bold,
+    /// 800
+    ExtraBold,
+    /// 900
+    Black,
+    /// Other weight, between 1 and 1000
+    Custom(u16),
+}
+
+impl Default for Weight {
+    fn default() -> Self {
+        Weight::Regular
+    }
+}
+
+impl Weight {
+    /// Named weight for multiples of 100, `Custom` otherwise
+    pub fn from_value(value: u16) -> Self {
+        match value {
+            100 => Weight::Thin,
+            200 => Weight::ExtraLight,
+            300 => Weight::Light,
+            400 => Weight::Regular,
+            500 => Weight::Medium,
+            600 => Weight::SemiBold,
+            700 => Weight::Bold,
+            800 => Weight::ExtraBold,
+            900 => Weight::Black,
+            value => Weight::Custom(value),
+        }
+    }
+
+    pub fn as_u16(&self) -> u16 {
+        match self {
+            Weight::Thin => 100,
+            Weight::ExtraLight => 200,
+            Weight::Light => 300,
+            Weight::Regular => 400,
+            Weight::Medium => 500,
+            Weight::SemiBold => 600,
+            Weight::Bold => 700,
+            Weight::ExtraBold => 800,
+            Weight::Black => 900,
+            Weight::Custom(value) => *value,
+        }
+    }
+}
+
+/// Font slant. Oblique faces count as italic
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum Italic {
+    No,
+    Yes,
+}
+
+impl Default for Italic {
+    fn default() -> Self {
+        Italic::No
+    }
+}
+
+/// List the faces of a font collection. Any other font is listed as a single face
+pub(crate) fn faces(data: &[u8]) -> Result<Vec<FaceInfo>, Error> {
+    let offsets = sfnt::collection_offsets(data).unwrap_or_else(|| vec![0]);
+
+    offsets
+        .into_iter()
+        .enumerate()
+        .map(|(index, offset)| {
+            let sfnt = Sfnt::parse_at(data, offset).ok_or(Error::FontLoadError)?;
+            let table = sfnt.table(b"name").unwrap_or_default();
+
+            let find = |ids: &[u16]| {
+                ids.iter()
+                    .find_map(|id| name::find(table, *id))
+                    .unwrap_or_default()
+            };
+
+            let (weight, italic) = weight_and_slant(&sfnt);
+
+            Ok(FaceInfo {
+                index,
+                family: find(&[name::TYPOGRAPHIC_FAMILY, name::FAMILY]),
+                style: find(&[name::TYPOGRAPHIC_SUBFAMILY, name::SUBFAMILY]),
+                full_name: find(&[name::FULL_NAME]),
+                weight,
+                italic,
+            })
+        })
+        .collect()
+}
+
+/// Weight and slant from `OS/2`, or from `head.macStyle` when it is missing
+fn weight_and_slant(sfnt: &Sfnt) -> (Weight, Italic) {
+    let os2 = sfnt.table(b"OS/2").and_then(|os2| {
+        let weight = sfnt::get_u16(os2, 4)?;
+        let fs_selection = sfnt::get_u16(os2, 62)?;
+        // ITALIC or OBLIQUE
+        Some((weight, fs_selection & 0x0201 != 0))
+    });
+
+    let (weight, italic) = os2.unwrap_or_else(|| {
+        let mac_style = sfnt.table(b"head").and_then(|head| sfnt::get_u16(head, 44));
+        let mac_style = mac_style.unwrap_or(0);
+        let weight = if mac_style & 1 != 0 { 700 } else { 400 };
+        (weight, mac_style & 2 != 0)
+    });
+
+    let italic = if italic { Italic::Yes } else { Italic::No };
+    (Weight::from_value(weight), italic)
+}
+
+/// Extract a face of a font collection into a standalone font. Fonts which are not collections
+/// only have the face `0`, and need no extraction (`None`)
+pub(crate) fn extract(data: &[u8], index: usize) -> Result<Option<Vec<u8>>, Error> {
+    let offsets = match sfnt::collection_offsets(data) {
+        Some(offsets) => offsets,
+        None if index == 0 => return Ok(None),
+        None => return Err(Error::FaceNotFound),
+    };
+
+    let offset = *offsets.get(index).ok_or(Error::FaceNotFound)?;
+    let sfnt = Sfnt::parse_at(data, offset).ok_or(Error::FontLoadError)?;
+
+    Ok(Some(SfntBuilder::from_sfnt(&sfnt).build()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tests::read_font;
+
+    #[test]
+    fn test_faces() {
+        let font = read_font(None);
+        let collection = sfnt::build_collection(&[font.clone(), font.clone()]).unwrap();
+
+        let faces = faces(&collection).unwrap();
+        assert_eq!(faces.len(), 2);
+        assert_eq!(faces[1].index, 1);
+        assert_eq!(faces[1].family, "Fira Mono");
+        assert_eq!(faces[1].style, "Medium");
+        assert_eq!(
+            crate::TTFFile::collection_faces_from_slice(&collection).unwrap(),
+            faces
+        );
+
+        // a standalone font is a single face
+        assert_eq!(super::faces(&font).unwrap()[0], faces[0]);
+    }
+
+    #[test]
+    fn test_extract() {
+        let font = read_font(None);
+        let collection = sfnt::build_collection(&[font.clone(), font.clone()]).unwrap();
+
+        let face = extract(&collection, 1).unwrap().unwrap();
+        let (original, extracted) = (Sfnt::parse(&font).unwrap(), Sfnt::parse(&face).unwrap());
+        assert_eq!(original.table(b"glyf"), extracted.table(b"glyf"));
+
+        assert!(matches!(extract(&collection, 2), Err(Error::FaceNotFound)));
+        assert!(matches!(extract(&font, 0), Ok(None)));
+        assert!(matches!(extract(&font, 1), Err(Error::FaceNotFound)));
+    }
+}
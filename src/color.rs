@@ -0,0 +1,92 @@
+//! Color glyphs (`COLR` / `CPAL`): layers of glyph meshes filled with palette colors
+use crate::{
+    sfnt::Sfnt,
+    tables::{colr, cpal},
+    Error, OwnedMesh, Quality, TTFFileRef,
+};
+
+/// A layer of a color glyph, see [`TTFFileRef::color_layers`]
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct ColorLayer {
+    /// Glyph drawn by the layer, see [`TTFFileRef::glyph_by_index`]
+    pub glyph_index: usize,
+    /// 2d mesh of the layer glyph
+    pub mesh: OwnedMesh,
+    /// RGBA color from the palette, `None` for layers drawn in the text color
+    pub color: Option<[u8; 4]>,
+}
+
+impl TTFFileRef<'_> {
+    /// Number of color palettes (`CPAL`), zero for fonts without color glyphs
+    pub fn palette_count(&self) -> usize {
+        Sfnt::parse(self.data())
+            .and_then(|sfnt| sfnt.table(b"CPAL"))
+            .map_or(0, cpal::palette_count)
+    }
+
+    /// Layers of a color glyph (emoji, icons), bottom first, colored with a palette. Palette
+    /// `0` is the default one, see [`TTFFileRef::palette_count`]. Empty for glyphs without color,
+    /// which are drawn from their own mesh as usual
+    ///
+    /// Reads `COLR` version 0 layers, and version 1 glyphs filled with solid colors. Gradient
+    /// fills use the color of their first stop, and transformed or composited paints are
+    /// skipped
+    ///
+    /// Usage:
+    /// ```rust
+    /// # use ttf2mesh::{TTFFile, Quality};
+    /// let mut ttf = TTFFile::from_file("./fonts/FiraMono-Medium.ttf").unwrap();
+    ///
+    /// let index = ttf.glyph_from_char('A').unwrap().index();
+    /// for layer in ttf.color_layers(index, 0, Quality::Medium).unwrap() {
+    ///     let color = layer.color.unwrap_or([0, 0, 0, 255]);
+    ///     // draw layer.mesh with color
+    /// }
+    /// ```
+    pub fn color_layers(
+        &mut self,
+        glyph_index: usize,
+        palette: usize,
+        quality: Quality,
+    ) -> Result<Vec<ColorLayer>, Error> {
+        if glyph_index >= self.glyph_count() {
+            return Err(Error::GlyphNotFound);
+        }
+
+        let (layers, colors) = {
+            let sfnt = Sfnt::parse(self.data());
+            let table = |tag| sfnt.as_ref().and_then(|sfnt| sfnt.table(tag));
+
+            let layers = table(b"COLR")
+                .and_then(|colr| colr::layers(colr, glyph_index as u16))
+                .unwrap_or_default();
+            let colors = match table(b"CPAL") {
+                Some(cpal) => cpal::palette(cpal, palette).ok_or(Error::PaletteNotFound)?,
+                None => Vec::new(),
+            };
+
+            (layers, colors)
+        };
+
+        layers
+            .iter()
+            .map(|layer| {
+                let color = match layer.palette_index {
+                    colr::FOREGROUND => None,
+                    index => colors.get(index as usize).map(|&[r, g, b, a]| {
+                        let alpha = layer.alpha.max(0.0).min(1.0);
+                        [r, g, b, (a as f32 * alpha).round() as u8]
+                    }),
+                };
+
+                let mut glyph = self.glyph_by_index(layer.glyph as usize)?;
+                Ok(ColorLayer {
+                    glyph_index: layer.glyph as usize,
+                    mesh: glyph.to_2d_mesh(quality)?.to_owned_mesh(),
+                    color,
+                })
+            })
+            .collect()
+    }
+}
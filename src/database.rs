@@ -0,0 +1,229 @@
+//! Font discovery from directories and matching by family, weight and slant, with the font
+//! lists of the C library (`ttf_list_fonts`, `ttf_list_match`)
+use std::{
+    ffi::{CStr, CString},
+    os::raw::{c_char, c_int},
+    path::{Path, PathBuf},
+    ptr,
+};
+
+use ttf2mesh_sys as sys;
+
+use crate::{path_to_cstring, Error, Italic, TTFFile, Weight};
+
+/// Masks of the listed font files, separated by `|`
+const FILE_MASK: &str = "*.ttf|*.otf";
+
+/// Requirements of [`FontDatabase::match_font`] for `ttf_list_match`: the family name (`f`)
+/// is mandatory (everything before `!`), then the closest weight (`w`) is preferred, and
+/// italic (`i`) when requested
+const MATCH_REGULAR: &str = "f!w";
+const MATCH_ITALIC: &str = "f!wi";
+
+/// A font face found by [`FontDatabase`]. Only the font headers have been read
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct FontDescriptor {
+    /// Path of the font file
+    pub path: PathBuf,
+    /// Family name, e.g. `Fira Mono`
+    pub family: String,
+    /// Style name, e.g. `Medium Italic`
+    pub style: String,
+    pub weight: Weight,
+    pub italic: Italic,
+}
+
+impl FontDescriptor {
+    /// Load the described font
+    pub fn load(&self) -> Result<TTFFile, Error> {
+        TTFFile::from_file(&self.path)
+    }
+
+    fn from_raw(ttf: &sys::ttf_file) -> Self {
+        let italic = ttf.os2.fsSelection.italic() != 0 || ttf.os2.fsSelection.oblique() != 0;
+
+        FontDescriptor {
+            path: unsafe { cstr_to_path(ttf.filename) },
+            family: unsafe { cstr_to_string(ttf.names.family) },
+            style: unsafe { cstr_to_string(ttf.names.subfamily) },
+            weight: Weight::from_value(ttf.os2.usWeightClass),
+            italic: if italic { Italic::Yes } else { Italic::No },
+        }
+    }
+}
+
+/// A list of fonts discovered from directories. Font collections (`.ttc`) are not listed
+///
+/// Usage:
+/// ```rust,no_run
+/// # use ttf2mesh::{FontDatabase, Italic, Weight};
+/// let db = FontDatabase::system();
+///
+/// let font = db.match_font("Fira Mono", Weight::Medium, Italic::No).unwrap();
+/// println!("{} {} at {:?}", font.family, font.style, font.path);
+///
+/// let mut ttf = font.load().unwrap();
+/// ```
+#[derive(Debug)]
+pub struct FontDatabase {
+    /// Null-terminated list of font headers, or null
+    list: *mut *mut sys::ttf_t,
+    fonts: Vec<FontDescriptor>,
+}
+
+impl Default for FontDatabase {
+    fn default() -> Self {
+        Self {
+            list: ptr::null_mut(),
+            fonts: Vec::new(),
+        }
+    }
+}
+
+impl FontDatabase {
+    /// Scan directories (recursively) for fonts. Files which can not be parsed are skipped
+    pub fn scan<P: AsRef<Path>>(dirs: &[P]) -> Self {
+        let dirs = dirs.iter().map(path_to_cstring).collect::<Vec<_>>();
+        let mut dir_ptrs = dirs.iter().map(|dir| dir.as_ptr()).collect::<Vec<_>>();
+        let mask = CString::new(FILE_MASK).unwrap();
+
+        Self::from_list(unsafe {
+            sys::ttf_list_fonts(
+                dir_ptrs.as_mut_ptr(),
+                dir_ptrs.len() as c_int,
+                mask.as_ptr(),
+            )
+        })
+    }
+
+    /// Scan the font directories of the operating system
+    pub fn system() -> Self {
+        let mask = CString::new(FILE_MASK).unwrap();
+
+        Self::from_list(unsafe { sys::ttf_list_system_fonts(mask.as_ptr()) })
+    }
+
+    /// All discovered font faces
+    pub fn fonts(&self) -> &[FontDescriptor] {
+        &self.fonts
+    }
+
+    /// Find the best face of a family (case-insensitive). Faces of the requested slant and of
+    /// the closest weight are preferred
+    pub fn match_font(
+        &self,
+        family: &str,
+        weight: Weight,
+        italic: Italic,
+    ) -> Option<&FontDescriptor> {
+        if self.list.is_null() {
+            return None;
+        }
+
+        let family = CString::new(family).ok()?;
+        let requirements = CString::new(match italic {
+            Italic::No => MATCH_REGULAR,
+            Italic::Yes => MATCH_ITALIC,
+        })
+        .unwrap();
+
+        let found = unsafe {
+            sys::ttf_list_match(
+                self.list,
+                ptr::null_mut(),
+                requirements.as_ptr(),
+                family.as_ptr(),
+                weight.as_u16() as c_int,
+            )
+        };
+
+        (0..self.fonts.len())
+            .find(|&index| unsafe { *self.list.add(index) } == found)
+            .map(|index| &self.fonts[index])
+    }
+
+    fn from_list(list: *mut *mut sys::ttf_t) -> Self {
+        let mut fonts = Vec::new();
+
+        if !list.is_null() {
+            let mut font = list;
+            unsafe {
+                while !(*font).is_null() {
+                    fonts.push(FontDescriptor::from_raw(&**font));
+                    font = font.add(1);
+                }
+            }
+        }
+
+        Self { list, fonts }
+    }
+}
+
+impl Drop for FontDatabase {
+    fn drop(&mut self) {
+        if !self.list.is_null() {
+            unsafe { sys::ttf_free_list(self.list) }
+        }
+    }
+}
+
+/// # Safety
+///
+/// `ptr` is null or a valid null-terminated string
+unsafe fn cstr_to_string(ptr: *const c_char) -> String {
+    if ptr.is_null() {
+        return String::new();
+    }
+
+    CStr::from_ptr(ptr).to_string_lossy().into_owned()
+}
+
+#[cfg(not(windows))]
+unsafe fn cstr_to_path(ptr: *const c_char) -> PathBuf {
+    use std::{ffi::OsStr, os::unix::ffi::OsStrExt};
+
+    if ptr.is_null() {
+        return PathBuf::new();
+    }
+
+    OsStr::from_bytes(CStr::from_ptr(ptr).to_bytes()).into()
+}
+
+#[cfg(windows)]
+unsafe fn cstr_to_path(ptr: *const c_char) -> PathBuf {
+    cstr_to_string(ptr).into()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_scan() {
+        let dir = std::env::temp_dir().join(format!("ttf2mesh-test-{}", std::process::id()));
+        std::fs::create_dir_all(dir.join("nested")).unwrap();
+
+        let font = Path::new(env!("CARGO_MANIFEST_DIR")).join("fonts/FiraMono-Medium.ttf");
+        std::fs::copy(&font, dir.join("nested/FiraMono-Medium.ttf")).unwrap();
+        std::fs::write(dir.join("broken.ttf"), b"not a font").unwrap();
+        std::fs::write(dir.join("README.md"), b"fonts").unwrap();
+
+        let db = FontDatabase::scan(&[&dir]);
+        std::fs::remove_dir_all(&dir).unwrap();
+
+        assert_eq!(db.fonts().len(), 1);
+
+        let found = db
+            .match_font("fira mono", Weight::Bold, Italic::Yes)
+            .unwrap();
+        assert_eq!(found.path, dir.join("nested/FiraMono-Medium.ttf"));
+        assert_eq!(found.style, "Medium");
+        assert_eq!(found.weight, Weight::Medium);
+        assert_eq!(found.italic, Italic::No);
+
+        assert!(db
+            .match_font("Fira Sans", Weight::Medium, Italic::No)
+            .is_none());
+    }
+}
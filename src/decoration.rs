@@ -0,0 +1,328 @@
+//! Text decorations: underline, strikethrough and overline
+use crate::{
+    cdt, layout,
+    sfnt::{self, Sfnt},
+    OwnedMesh, PositionedGlyph, TTFFileRef,
+};
+
+/// Metrics used when the font does not define them, in em units
+const DEFAULT_THICKNESS: f32 = 0.05;
+const DEFAULT_UNDERLINE_POSITION: f32 = -0.1;
+const DEFAULT_STRIKEOUT_POSITION: f32 = 0.3;
+const DEFAULT_ASCENDER: f32 = 0.8;
+
+/// A line drawn along text, see [`TTFFileRef::decorations`]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum Decoration {
+    /// Below the baseline, from `post.underlinePosition` and `post.underlineThickness`
+    Underline,
+    /// Through lowercase letters, from `OS/2.yStrikeoutPosition` and `OS/2.yStrikeoutSize`
+    Strikethrough,
+    /// At the ascender (`hhea`), as thick as the underline
+    Overline,
+}
+
+/// Placement of a decoration relative to the baseline, in em units. See
+/// [`TTFFileRef::decoration_metrics`]
+#[derive(Debug, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct DecorationMetrics {
+    /// Top of the line
+    pub position: f32,
+    pub thickness: f32,
+}
+
+/// A piece of a decoration line, in em units in the coordinates of [`TTFFileRef::layout`]
+#[derive(Debug, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct DecorationLine {
+    pub decoration: Decoration,
+    /// Horizontal extent, `(left, right)`
+    pub x: (f32, f32),
+    /// Vertical extent, `(bottom, top)`
+    pub y: (f32, f32),
+}
+
+impl DecorationLine {
+    /// The line as a quad of two triangles, with `z = 0`
+    pub fn to_2d_mesh(&self) -> OwnedMesh {
+        let (vertices, faces) = self.quad();
+
+        OwnedMesh {
+            vertices: vertices.iter().map(|&(x, y)| (x, y, 0.0)).collect(),
+            faces,
+            normals: Vec::new(),
+        }
+    }
+
+    /// The line as a box with `depth`, centered on `z = 0` like glyph meshes of
+    /// `Glyph::to_3d_mesh`
+    pub fn to_3d_mesh(&self, depth: f32) -> OwnedMesh {
+        let (vertices, faces, normals) = cdt::extrude(&self.quad(), depth);
+
+        OwnedMesh {
+            vertices,
+            faces,
+            normals,
+        }
+    }
+
+    fn quad(&self) -> cdt::Mesh2d {
+        let ((left, right), (bottom, top)) = (self.x, self.y);
+
+        (
+            vec![(left, bottom), (right, bottom), (right, top), (left, top)],
+            vec![(0, 1, 2), (0, 2, 3)],
+        )
+    }
+}
+
+impl TTFFileRef<'_> {
+    /// Position and thickness of a decoration, from the font tables of the variation instance or
+    /// defaults. With a synthetic style, lines grow by the emboldening offset on both sides, like
+    /// the stems of the glyphs
+    pub fn decoration_metrics(&self, decoration: Decoration) -> DecorationMetrics {
+        metrics(
+            self.instance_data(),
+            decoration,
+            self.synthetic_style().embolden,
+        )
+    }
+
+    /// Decoration lines for laid out text (see [`TTFFileRef::layout`]), spanning each line of the
+    /// text from the left of its first glyph to the advance of its last one. Rotated glyphs of
+    /// vertical text are not decorated
+    ///
+    /// With `skip_ink`, underlines and overlines are cut around glyphs that cross them (e.g.
+    /// descenders of `g` or `y`), leaving a gap of the line thickness. Strikethrough lines are
+    /// never cut
+    ///
+    /// Usage:
+    /// ```rust
+    /// # use ttf2mesh::{TTFFile, Decoration, Quality};
+    /// let mut ttf = TTFFile::from_file("./fonts/FiraMono-Medium.ttf").unwrap();
+    ///
+    /// let glyphs = ttf.layout("Typography");
+    /// let lines = ttf.decorations(&glyphs, &[Decoration::Underline], true);
+    ///
+    /// let meshes = lines.iter().map(|line| line.to_3d_mesh(0.1)).collect::<Vec<_>>();
+    /// ```
+    pub fn decorations(
+        &mut self,
+        glyphs: &[PositionedGlyph],
+        decorations: &[Decoration],
+        skip_ink: bool,
+    ) -> Vec<DecorationLine> {
+        let line_height = layout::line_height(self.instance_data());
+        let metrics = decorations
+            .iter()
+            .map(|&decoration| (decoration, self.decoration_metrics(decoration)))
+            .collect::<Vec<_>>();
+
+        let mut lines = Vec::new();
+        for run in runs(glyphs, line_height) {
+            let baseline = run[0].y;
+            let left = run.iter().map(|g| g.x).fold(f32::INFINITY, f32::min);
+            let right = run
+                .iter()
+                .map(|g| g.x + self.advance(g.glyph_index))
+                .fold(f32::NEG_INFINITY, f32::max);
+
+            for &(decoration, metrics) in &metrics {
+                let y = (
+                    baseline + metrics.position - metrics.thickness,
+                    baseline + metrics.position,
+                );
+
+                let mut pieces = vec![(left, right)];
+                if skip_ink && decoration != Decoration::Strikethrough {
+                    for glyph in &run {
+                        let ink = self.ink_between(
+                            glyph,
+                            y.0 - metrics.thickness,
+                            y.1 + metrics.thickness,
+                        );
+                        if let Some((min, max)) = ink {
+                            cut(
+                                &mut pieces,
+                                min - metrics.thickness,
+                                max + metrics.thickness,
+                            );
+                        }
+                    }
+                }
+
+                lines.extend(
+                    pieces
+                        .into_iter()
+                        .map(|x| DecorationLine { decoration, x, y }),
+                );
+            }
+        }
+
+        lines
+    }
+
+    fn advance(&mut self, glyph_index: usize) -> f32 {
+        self.glyph_by_index(glyph_index)
+            .map(|glyph| glyph.advance())
+            .unwrap_or(0.0)
+    }
+
+    /// Extent of the ink of a placed glyph between two heights, in text coordinates
+    fn ink_between(
+        &mut self,
+        placed: &PositionedGlyph,
+        y_min: f32,
+        y_max: f32,
+    ) -> Option<(f32, f32)> {
+        let glyph = self.glyph_by_index(placed.glyph_index).ok()?;
+        let (min, max) = glyph.ink_between(y_min - placed.y, y_max - placed.y)?;
+
+        Some((placed.x + min, placed.x + max))
+    }
+}
+
+/// Consecutive glyphs on the same line. Glyphs are on a line while their baseline is within
+/// half a line height of the first one, which allows for the offsets of shaped marks
+fn runs(glyphs: &[PositionedGlyph], line_height: f32) -> Vec<Vec<PositionedGlyph>> {
+    let mut runs: Vec<Vec<PositionedGlyph>> = Vec::new();
+
+    for glyph in glyphs.iter().filter(|glyph| !glyph.rotated) {
+        match runs.last_mut() {
+            Some(run) if (glyph.y - run[0].y).abs() <= line_height / 2.0 => run.push(*glyph),
+            _ => runs.push(vec![*glyph]),
+        }
+    }
+
+    runs
+}
+
+/// Remove `[min, max]` from a list of ranges
+fn cut(pieces: &mut Vec<(f32, f32)>, min: f32, max: f32) {
+    let mut result = Vec::with_capacity(pieces.len() + 1);
+
+    for &(left, right) in pieces.iter() {
+        if max <= left || min >= right {
+            result.push((left, right));
+            continue;
+        }
+
+        if min > left {
+            result.push((left, min));
+        }
+        if max < right {
+            result.push((max, right));
+        }
+    }
+
+    *pieces = result;
+}
+
+fn metrics(data: &[u8], decoration: Decoration, embolden: f32) -> DecorationMetrics {
+    let sfnt = Sfnt::parse(data);
+    let units_per_em = sfnt
+        .as_ref()
+        .and_then(|sfnt| sfnt::get_u16(sfnt.table(b"head")?, 18))
+        .filter(|&units| units > 0)
+        .map_or(1000.0, f32::from);
+
+    // a signed value of a table, `None` if missing or zero
+    let get = |tag: &sfnt::Tag, offset: usize| {
+        let value = sfnt::get_u16(sfnt.as_ref()?.table(tag)?, offset)? as i16;
+        if value == 0 {
+            return None;
+        }
+
+        Some(f32::from(value) / units_per_em)
+    };
+
+    let underline_thickness = get(b"post", 10).filter(|t| *t > 0.0);
+    let underline_thickness = underline_thickness.unwrap_or(DEFAULT_THICKNESS);
+
+    let (position, thickness) = match decoration {
+        Decoration::Underline => (
+            get(b"post", 8).unwrap_or(DEFAULT_UNDERLINE_POSITION),
+            underline_thickness,
+        ),
+        Decoration::Strikethrough => (
+            get(b"OS/2", 28).unwrap_or(DEFAULT_STRIKEOUT_POSITION),
+            get(b"OS/2", 26)
+                .filter(|t| *t > 0.0)
+                .unwrap_or(underline_thickness),
+        ),
+        Decoration::Overline => (
+            get(b"hhea", 4).unwrap_or(DEFAULT_ASCENDER),
+            underline_thickness,
+        ),
+    };
+
+    DecorationMetrics {
+        position: position + embolden,
+        thickness: thickness + 2.0 * embolden,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tests::read_font;
+
+    #[test]
+    fn test_metrics() {
+        let font = read_font(None);
+
+        let underline = metrics(&font, Decoration::Underline, 0.0);
+        let strikethrough = metrics(&font, Decoration::Strikethrough, 0.0);
+        let overline = metrics(&font, Decoration::Overline, 0.0);
+
+        assert!(underline.position < 0.0 && underline.thickness > 0.0);
+        assert!(strikethrough.position > 0.0 && strikethrough.position < overline.position);
+        assert_eq!(overline.thickness, underline.thickness);
+
+        // defaults without font tables
+        assert_eq!(
+            metrics(&[], Decoration::Underline, 0.0),
+            DecorationMetrics {
+                position: DEFAULT_UNDERLINE_POSITION,
+                thickness: DEFAULT_THICKNESS,
+            }
+        );
+
+        // emboldened lines grow on both sides
+        let bold = metrics(&font, Decoration::Underline, 0.02);
+        assert!((bold.position - underline.position - 0.02).abs() < 1e-6);
+        assert!((bold.thickness - underline.thickness - 0.04).abs() < 1e-6);
+    }
+
+    #[test]
+    fn test_cut() {
+        let mut pieces = vec![(0.0, 4.0)];
+        cut(&mut pieces, 1.0, 2.0);
+        cut(&mut pieces, 3.5, 5.0);
+        cut(&mut pieces, -1.0, 0.0);
+        assert_eq!(pieces, &[(0.0, 1.0), (2.0, 3.5)]);
+
+        cut(&mut pieces, -1.0, 5.0);
+        assert!(pieces.is_empty());
+    }
+
+    #[test]
+    fn test_to_mesh() {
+        let line = DecorationLine {
+            decoration: Decoration::Underline,
+            x: (0.0, 2.0),
+            y: (-0.2, -0.1),
+        };
+
+        let mesh = line.to_2d_mesh();
+        assert!(mesh.validate().is_valid());
+        assert_eq!(mesh.faces_len(), 2);
+
+        let mesh = line.to_3d_mesh(0.5);
+        assert!(mesh.validate().is_valid());
+        assert_eq!(mesh.faces_len(), 2 * 2 + 4 * 2);
+        assert!(mesh.vertices.iter().all(|v| v.2.abs() == 0.25));
+    }
+}
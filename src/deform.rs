@@ -0,0 +1,177 @@
+//! Deformation of meshes onto curved surfaces
+use crate::{Error, OwnedMesh};
+
+/// Lower bound for the horizontal scale near the poles of a sphere, avoids dividing by zero
+const MIN_SCALE: f32 = 1e-4;
+
+impl OwnedMesh {
+    /// Wrap the mesh around a cylinder with a vertical axis (parallel to y)
+    ///
+    /// The xy plane is rolled onto the cylinder surface so that it touches the plane along the y
+    /// axis: `x` becomes the arc length around the cylinder and `z` the height above its surface
+    /// (the axis is at `x = 0, z = -radius`). Triangles are subdivided so that they deviate at
+    /// most `tolerance` from the curved surface, and normals are transformed with the surface.
+    /// Fails for invalid meshes, as [`OwnedMesh::subdivide`]
+    ///
+    /// Usage:
+    /// ```rust
+    /// # use ttf2mesh::{TTFFile, Quality};
+    /// let mut ttf = TTFFile::from_file("./fonts/FiraMono-Medium.ttf").unwrap();
+    /// let mut glyph = ttf.glyph_from_char('A').unwrap();
+    /// let mesh = glyph.to_3d_mesh(Quality::Medium, 0.1).unwrap().to_owned_mesh();
+    ///
+    /// // embossed onto a bottle with a radius of 2em
+    /// let wrapped = mesh.translate(0.0, 0.0, 0.05).wrap_cylinder(2.0, 0.001).unwrap();
+    /// ```
+    pub fn wrap_cylinder(&self, radius: f32, tolerance: f32) -> Result<OwnedMesh, Error> {
+        if !is_positive(radius) {
+            return Ok(self.clone());
+        }
+
+        let max_arc = max_arc(radius, tolerance);
+
+        Ok(self.subdivide_by(|a, b| (b.0 - a.0).abs() > max_arc)?.map(
+            |v| {
+                let (sin, cos) = (v.0 / radius).sin_cos();
+                let r = radius + v.2;
+
+                (r * sin, v.1, r * cos - radius)
+            },
+            |v, n| {
+                let (sin, cos) = (v.0 / radius).sin_cos();
+                // arc length direction is stretched by the distance from the axis
+                let nx = n.0 / ((radius + v.2) / radius).max(MIN_SCALE);
+
+                (nx * cos + n.2 * sin, n.1, -nx * sin + n.2 * cos)
+            },
+        ))
+    }
+
+    /// Wrap the mesh onto a sphere
+    ///
+    /// The xy plane touches the sphere at the origin: `x` becomes the arc length along the
+    /// equator (longitude), `y` the arc length towards the poles (latitude) and `z` the height
+    /// above the surface (the center is at `z = -radius`). Triangles are subdivided so that they
+    /// deviate at most `tolerance` from the curved surface, and normals are transformed with the
+    /// surface. Fails for invalid meshes, as [`OwnedMesh::subdivide`]
+    pub fn wrap_sphere(&self, radius: f32, tolerance: f32) -> Result<OwnedMesh, Error> {
+        if !is_positive(radius) {
+            return Ok(self.clone());
+        }
+
+        let max_arc = max_arc(radius, tolerance);
+
+        Ok(self
+            .subdivide_by(|a, b| ((b.0 - a.0).powi(2) + (b.1 - a.1).powi(2)).sqrt() > max_arc)?
+            .map(
+                |v| {
+                    let (sin_lon, cos_lon) = (v.0 / radius).sin_cos();
+                    let (sin_lat, cos_lat) = (v.1 / radius).sin_cos();
+                    let r = radius + v.2;
+
+                    (
+                        r * cos_lat * sin_lon,
+                        r * sin_lat,
+                        r * cos_lat * cos_lon - radius,
+                    )
+                },
+                |v, n| {
+                    let (sin_lon, cos_lon) = (v.0 / radius).sin_cos();
+                    let (sin_lat, cos_lat) = (v.1 / radius).sin_cos();
+                    let scale = (radius + v.2) / radius;
+
+                    // components along the stretched longitude and latitude directions
+                    let n_lon = n.0 / (scale * cos_lat).max(MIN_SCALE);
+                    let n_lat = n.1 / scale.max(MIN_SCALE);
+
+                    let east = (cos_lon, 0.0, -sin_lon);
+                    let north = (-sin_lat * sin_lon, cos_lat, -sin_lat * cos_lon);
+                    let up = (cos_lat * sin_lon, sin_lat, cos_lat * cos_lon);
+
+                    (
+                        n_lon * east.0 + n_lat * north.0 + n.2 * up.0,
+                        n_lon * east.1 + n_lat * north.1 + n.2 * up.1,
+                        n_lon * east.2 + n_lat * north.2 + n.2 * up.2,
+                    )
+                },
+            ))
+    }
+}
+
+fn is_positive(value: f32) -> bool {
+    value.is_finite() && value > 0.0
+}
+
+/// Longest arc whose chord deviates at most `tolerance` from a circle (sagitta `l² / 8r`)
+fn max_arc(radius: f32, tolerance: f32) -> f32 {
+    let tolerance = if is_positive(tolerance) {
+        tolerance.min(radius)
+    } else {
+        radius
+    };
+
+    (8.0 * radius * tolerance).sqrt()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// A flat 1x1 quad at height `z`, facing up
+    fn quad(z: f32) -> OwnedMesh {
+        OwnedMesh {
+            vertices: vec![(0.0, 0.0, z), (1.0, 0.0, z), (1.0, 1.0, z), (0.0, 1.0, z)],
+            faces: vec![(0, 1, 2), (0, 2, 3)],
+            normals: vec![(0.0, 0.0, 1.0); 4],
+        }
+    }
+
+    fn length(v: (f32, f32, f32)) -> f32 {
+        (v.0 * v.0 + v.1 * v.1 + v.2 * v.2).sqrt()
+    }
+
+    #[test]
+    fn test_wrap_cylinder() {
+        let radius = 1.5;
+        let mesh = quad(0.1).wrap_cylinder(radius, 0.001).unwrap();
+        assert!(mesh.faces.len() > 2);
+
+        for (v, n) in mesh.vertices.iter().zip(&mesh.normals) {
+            // on the surface, with the normal pointing away from the axis
+            let radial = (v.0, 0.0, v.2 + radius);
+            assert!((length(radial) - (radius + 0.1)).abs() < 1e-4);
+
+            let dot = (radial.0 * n.0 + radial.2 * n.2) / length(radial);
+            assert!((dot - 1.0).abs() < 1e-4);
+        }
+
+        // the vertical edge at x = 0 is not split
+        assert_eq!(mesh.vertices.iter().filter(|v| v.0 == 0.0).count(), 2);
+    }
+
+    #[test]
+    fn test_wrap_sphere() {
+        let radius = 2.0;
+
+        let mesh = quad(0.0).wrap_sphere(radius, 0.001).unwrap();
+        for (v, n) in mesh.vertices.iter().zip(&mesh.normals) {
+            let radial = (v.0, v.1, v.2 + radius);
+            assert!((length(radial) - radius).abs() < 1e-4);
+
+            let dot = (radial.0 * n.0 + radial.1 * n.1 + radial.2 * n.2) / radius;
+            assert!((dot - 1.0).abs() < 1e-4);
+        }
+
+        // a side wall normal stays tangent to the sphere
+        let wall = OwnedMesh {
+            normals: vec![(1.0, 0.0, 0.0); 4],
+            ..quad(0.0)
+        };
+        let mesh = wall.wrap_sphere(radius, 0.001).unwrap();
+        for (v, n) in mesh.vertices.iter().zip(&mesh.normals) {
+            let dot = v.0 * n.0 + v.1 * n.1 + (v.2 + radius) * n.2;
+            assert!(dot.abs() < 1e-4);
+            assert!((length(*n) - 1.0).abs() < 1e-4);
+        }
+    }
+}
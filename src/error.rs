@@ -1,7 +1,10 @@
 /// Represents an error by the library
+///
+/// New variants may be added in minor releases, so matches need a wildcard arm
 #[derive(Debug)]
+#[non_exhaustive]
 pub enum Error {
-    /// Font could not be loaded. The library doesn't support all font types
+    /// Font could not be loaded. The library supports TrueType and CFF-flavored OpenType fonts
     FontLoadError,
 
     /// Font could not be exported to an obj file
@@ -10,6 +13,9 @@ pub enum Error {
     /// Mesh generation failed
     Glyph2MeshError,
 
+    /// Glyph outline could not be linearized or queried
+    OutlineError,
+
     /// Glyph is not found in the font file
     GlyphNotFound,
 
@@ -18,4 +24,20 @@ pub enum Error {
 
     /// File to be opened was not found
     FileNotFound,
+
+    /// Font variation could not be applied (not a variable font, or an unknown axis)
+    VariationError,
+
+    /// Face index is out of range for the font collection
+    FaceNotFound,
+
+    /// Text could not be shaped, the shaper failed to parse the font
+    ShapingError,
+
+    /// Color palette index is out of range for the font
+    PaletteNotFound,
+
+    /// Mesh has a face index out of the vertex range, or a normal count different from the
+    /// vertex count. See `OwnedMesh::validate`
+    InvalidMesh,
 }
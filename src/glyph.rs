@@ -1,12 +1,50 @@
-use std::{convert::TryInto, mem::MaybeUninit};
+use std::{cell::RefCell, convert::TryInto, mem::MaybeUninit, os::raw::c_int, slice};
 
 use ttf2mesh_sys as sys;
 
 use crate::{
+    cdt,
     mesh::{Mesh, Mesh2d, Mesh3d},
-    Error, Quality,
+    quality,
+    sfnt::Sfnt,
+    tables::post,
+    Error, OwnedMesh, Quality,
 };
 
+/// Winding direction of a contour (with y axis pointing up)
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum Orientation {
+    Clockwise,
+    CounterClockwise,
+}
+
+/// Nesting and winding of a glyph contour. See [`Glyph::contours_info`]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct ContourInfo {
+    /// Contour is nested inside an odd number of contours, i.e. it cuts a hole
+    pub hole: bool,
+    /// Index of the innermost contour that contains this contour
+    pub parent: Option<usize>,
+    pub orientation: Orientation,
+}
+
+/// Horizontal metrics and bounds of a glyph in em units. See [`Glyph::metrics`]
+#[derive(Debug, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct GlyphMetrics {
+    pub advance: f32,
+    /// Distance from the origin to the left of the outline
+    pub left_bearing: f32,
+    /// Distance from the right of the outline to the advance
+    pub right_bearing: f32,
+    /// Horizontal extent of the outline, `(min, max)`
+    pub x_bounds: (f32, f32),
+    /// Vertical extent of the outline, `(min, max)`
+    pub y_bounds: (f32, f32),
+}
+
 /// Represents a glyph in truetype font file. Can be converted to a 2d or 3d [`Mesh`]
 ///
 /// Usage:
@@ -20,15 +58,62 @@ use crate::{
 /// ```
 pub struct Glyph<'a> {
     inner: &'a mut sys::ttf_glyph,
+    index: usize,
+    /// Font data, for reading tables on the Rust side
+    data: &'a [u8],
+    /// Outline of [`Glyph::contains`] queries, linearized on first use
+    hit_outline: RefCell<Option<HitOutline>>,
 }
 
 impl<'a> Glyph<'a> {
-    pub(crate) fn from_raw(raw: &'a mut sys::ttf_glyph) -> Self {
-        Self { inner: raw }
+    pub(crate) fn from_raw(raw: &'a mut sys::ttf_glyph, index: usize, data: &'a [u8]) -> Self {
+        Self {
+            inner: raw,
+            index,
+            data,
+            hit_outline: RefCell::new(None),
+        }
+    }
+
+    /// Index of the glyph in the font, see [`crate::TTFFile::glyph_by_index`]
+    pub fn index(&self) -> usize {
+        self.index
+    }
+
+    /// PostScript name of the glyph from the `post` table, e.g. `uni20AC` or `f_i`. `None` for
+    /// fonts without glyph names (e.g. CFF-flavored OpenType fonts)
+    pub fn name(&self) -> Option<String> {
+        let post = Sfnt::parse(self.data)?.table(b"post")?;
+
+        post::glyph_names(post)?
+            .into_iter()
+            .nth(self.index)
+            .filter(|name| !name.is_empty())
     }
 
     /// Generate a 2d mesh from the glyph with desired [`Quality`]
     pub fn to_2d_mesh<'b>(&mut self, quality: Quality) -> Result<Mesh<'b, Mesh2d>, Error> {
+        if cfg!(feature = "pure-rust") {
+            self.mesh_2d_rust(quality)
+        } else {
+            self.mesh_2d_c(quality)
+        }
+    }
+
+    /// Generate a 3d mesh from the glyph with desired [`Quality`] and `depth`
+    pub fn to_3d_mesh<'b>(
+        &mut self,
+        quality: Quality,
+        depth: f32,
+    ) -> Result<Mesh<'b, Mesh3d>, Error> {
+        if cfg!(feature = "pure-rust") {
+            self.mesh_3d_rust(quality, depth)
+        } else {
+            self.mesh_3d_c(quality, depth)
+        }
+    }
+
+    pub(crate) fn mesh_2d_c<'b>(&mut self, quality: Quality) -> Result<Mesh<'b, Mesh2d>, Error> {
         let mut mesh = MaybeUninit::uninit();
 
         let features = sys::TTF_FEATURES_DFLT;
@@ -50,8 +135,7 @@ impl<'a> Glyph<'a> {
         Ok(Mesh::from_raw(mesh)?)
     }
 
-    /// Generate a 3d mesh from the glyph with desired [`Quality`] and `depth`
-    pub fn to_3d_mesh<'b>(
+    pub(crate) fn mesh_3d_c<'b>(
         &mut self,
         quality: Quality,
         depth: f32,
@@ -77,4 +161,457 @@ impl<'a> Glyph<'a> {
         let mesh = unsafe { mesh.assume_init() };
         Ok(Mesh::from_raw(mesh)?)
     }
+
+    pub(crate) fn mesh_2d_rust<'b>(&mut self, quality: Quality) -> Result<Mesh<'b, Mesh2d>, Error> {
+        if self.inner.outline.is_null() {
+            return Err(Error::Glyph2MeshError);
+        }
+
+        let (vertices, faces) = cdt::triangulate(&self.curves(), quality.as_u8())?;
+        Ok(Mesh::<Mesh2d>::from_data(&vertices, &faces))
+    }
+
+    pub(crate) fn mesh_3d_rust<'b>(
+        &mut self,
+        quality: Quality,
+        depth: f32,
+    ) -> Result<Mesh<'b, Mesh3d>, Error> {
+        if self.inner.outline.is_null() {
+            return Err(Error::Glyph2MeshError);
+        }
+
+        let mesh = cdt::triangulate(&self.curves(), quality.as_u8())?;
+        let (vertices, faces, normals) = cdt::extrude(&mesh, depth);
+        Ok(Mesh::<Mesh3d>::from_data(&vertices, &faces, &normals))
+    }
+
+    /// Horizontal advance of the glyph in em units
+    pub fn advance(&self) -> f32 {
+        self.inner.advance
+    }
+
+    /// Advance, side bearings and bounds of the glyph
+    pub fn metrics(&self) -> GlyphMetrics {
+        GlyphMetrics {
+            advance: self.inner.advance,
+            left_bearing: self.inner.lbearing,
+            right_bearing: self.inner.rbearing,
+            x_bounds: (self.inner.xbounds[0], self.inner.xbounds[1]),
+            y_bounds: (self.inner.ybounds[0], self.inner.ybounds[1]),
+        }
+    }
+
+    /// Check whether a point (in em units, relative to the glyph origin - same as mesh
+    /// vertices) lies inside the glyph, using the even-odd rule
+    ///
+    /// Usage:
+    /// ```rust
+    /// # use ttf2mesh::TTFFile;
+    /// # let mut ttf = TTFFile::from_file("./fonts/FiraMono-Medium.ttf").unwrap();
+    /// let glyph = ttf.glyph_from_char('I').unwrap();
+    /// assert!(glyph.contains(0.3, 0.35));
+    /// assert!(!glyph.contains(0.3, -0.2));
+    /// ```
+    pub fn contains(&self, x: f32, y: f32) -> bool {
+        in_bounds(self.inner, x, y)
+            && self
+                .with_hit_outline(|outline| outline.contains(x, y))
+                .unwrap_or(false)
+    }
+
+    /// Like [`Glyph::contains`], also returning the distance from the point to the outline
+    /// along the ray of the even-odd test, e.g. to pick the closest of overlapping glyphs. The
+    /// distance is infinite when the ray does not cross the outline
+    ///
+    /// Usage:
+    /// ```rust
+    /// # use ttf2mesh::TTFFile;
+    /// # let mut ttf = TTFFile::from_file("./fonts/FiraMono-Medium.ttf").unwrap();
+    /// let glyph = ttf.glyph_from_char('I').unwrap();
+    /// let (inside, distance) = glyph.contains_with_distance(0.3, 0.35);
+    /// assert!(inside && distance > 0.0);
+    /// ```
+    pub fn contains_with_distance(&self, x: f32, y: f32) -> (bool, f32) {
+        self.with_hit_outline(|outline| outline.contains_with_distance(x, y))
+            .unwrap_or((false, f32::INFINITY))
+    }
+
+    /// Run a query on the outline of [`Glyph::contains`], `None` for glyphs without an outline
+    fn with_hit_outline<T>(&self, query: impl FnOnce(&HitOutline) -> T) -> Option<T> {
+        let mut outline = self.hit_outline.borrow_mut();
+        if outline.is_none() {
+            *outline = HitOutline::new(self.inner);
+        }
+
+        outline.as_ref().map(query)
+    }
+
+    /// Horizontal extent of the outline between two heights, `None` if the outline does not reach
+    /// into this band
+    pub(crate) fn ink_between(&self, y_min: f32, y_max: f32) -> Option<(f32, f32)> {
+        let outline = LinearOutline::new(self.inner, Quality::Medium)?;
+        let mut extent: Option<(f32, f32)> = None;
+
+        for contour in outline.contours() {
+            let points = points(contour);
+            for (a, b) in points.iter().zip(points.iter().cycle().skip(1)) {
+                // part of the segment within the band, as a range of its parameter
+                let (low, high) = if a.y == b.y {
+                    if a.y < y_min || a.y > y_max {
+                        continue;
+                    }
+                    (0.0, 1.0)
+                } else {
+                    let t0 = (y_min - a.y) / (b.y - a.y);
+                    let t1 = (y_max - a.y) / (b.y - a.y);
+                    (t0.min(t1).max(0.0), t0.max(t1).min(1.0))
+                };
+
+                if low > high {
+                    continue;
+                }
+
+                for &t in &[low, high] {
+                    let x = a.x + (b.x - a.x) * t;
+                    extent = Some(match extent {
+                        Some((min, max)) => (min.min(x), max.max(x)),
+                        None => (x, x),
+                    });
+                }
+            }
+        }
+
+        extent
+    }
+
+    /// Nesting and orientation of each contour of the glyph outline. Empty for glyphs without
+    /// an outline
+    pub fn contours_info(&self) -> Result<Vec<ContourInfo>, Error> {
+        if self.inner.outline.is_null() {
+            return Ok(Vec::new());
+        }
+        let outline = LinearOutline::new(self.inner, Quality::High).ok_or(Error::OutlineError)?;
+
+        let contours = outline.contours();
+        let parents = contours
+            .iter()
+            .enumerate()
+            .map(|(index, contour)| {
+                let mut nested_to: c_int = -1;
+                let result = unsafe {
+                    sys::ttf_outline_contour_info(
+                        outline.raw,
+                        contour.subglyph_order,
+                        index as c_int,
+                        &mut nested_to,
+                    )
+                };
+                if result < 0 {
+                    return Err(Error::OutlineError);
+                }
+
+                Ok(if nested_to >= 0 && (nested_to as usize) < contours.len() {
+                    Some(nested_to as usize)
+                } else {
+                    None
+                })
+            })
+            .collect::<Result<Vec<_>, Error>>()?;
+
+        Ok(contours
+            .iter()
+            .enumerate()
+            .map(|(index, contour)| {
+                let mut depth = 0;
+                let mut parent = parents[index];
+                while let Some(p) = parent {
+                    depth += 1;
+                    parent = if depth < contours.len() {
+                        parents[p]
+                    } else {
+                        None
+                    };
+                }
+
+                ContourInfo {
+                    hole: depth % 2 == 1,
+                    parent: parents[index],
+                    orientation: orientation(points(contour)),
+                }
+            })
+            .collect())
+    }
+
+    /// Largest distance (in em units) between the curves of the glyph outline and their
+    /// approximation by line segments at a [`Quality`], i.e. the geometric error of a mesh
+    ///
+    /// Usage:
+    /// ```rust
+    /// # use ttf2mesh::{TTFFile, Quality};
+    /// # let mut ttf = TTFFile::from_file("./fonts/FiraMono-Medium.ttf").unwrap();
+    /// let glyph = ttf.glyph_from_char('O').unwrap();
+    /// assert!(glyph.flattening_error(Quality::High) < glyph.flattening_error(Quality::Low));
+    /// ```
+    pub fn flattening_error(&self, quality: Quality) -> f32 {
+        flattening_error(&self.curves(), self.inner, quality)
+    }
+
+    /// Lowest quality at which the curves of the glyph deviate at most `tolerance` (in em units)
+    /// from the exact outline, as measured by [`Glyph::flattening_error`]. The highest quality
+    /// (128) when the tolerance can not be met
+    ///
+    /// Usage:
+    /// ```rust
+    /// # use ttf2mesh::TTFFile;
+    /// # let mut ttf = TTFFile::from_file("./fonts/FiraMono-Medium.ttf").unwrap();
+    /// let mut glyph = ttf.glyph_from_char('O').unwrap();
+    ///
+    /// let quality = glyph.quality_for_tolerance(0.002);
+    /// assert!(glyph.flattening_error(quality) <= 0.002);
+    /// let mesh = glyph.to_2d_mesh(quality).unwrap();
+    /// ```
+    pub fn quality_for_tolerance(&self, tolerance: f32) -> Quality {
+        let curves = self.curves();
+
+        quality::lowest_quality(|quality| {
+            flattening_error(&curves, self.inner, quality) <= tolerance
+        })
+    }
+
+    /// Lowest quality for the glyph rendered at a font size of `pixels_per_em` pixels, with
+    /// curves deviating at most a quarter of a pixel. See [`Glyph::quality_for_tolerance`]
+    pub fn quality_for_pixel_size(&self, pixels_per_em: f32) -> Quality {
+        self.quality_for_tolerance(quality::MAX_PIXEL_ERROR / pixels_per_em)
+    }
+
+    /// Generate 2d meshes for a series of [`Quality`] levels, e.g. to pick a level of detail by
+    /// the size of the glyph on screen. The outline curves are read once for all levels
+    ///
+    /// Usage:
+    /// ```rust
+    /// # use ttf2mesh::{TTFFile, Quality};
+    /// # let mut ttf = TTFFile::from_file("./fonts/FiraMono-Medium.ttf").unwrap();
+    /// let mut glyph = ttf.glyph_from_char('€').unwrap();
+    /// let levels = glyph.to_lod_meshes(&[Quality::Low, Quality::High]).unwrap();
+    ///
+    /// assert!(levels[0].mesh.vertices_len() < levels[1].mesh.vertices_len());
+    /// assert!(levels[0].max_error > levels[1].max_error);
+    /// ```
+    pub fn to_lod_meshes(&mut self, qualities: &[Quality]) -> Result<Vec<LodMesh>, Error> {
+        let curves = self.curves();
+
+        qualities
+            .iter()
+            .map(|&quality| {
+                Ok(LodMesh {
+                    quality,
+                    mesh: self.to_2d_mesh(quality)?.to_owned_mesh(),
+                    max_error: flattening_error(&curves, self.inner, quality),
+                })
+            })
+            .collect()
+    }
+
+    /// Generate 3d meshes with `depth` for a series of [`Quality`] levels, see
+    /// [`Glyph::to_lod_meshes`]
+    pub fn to_3d_lod_meshes(
+        &mut self,
+        qualities: &[Quality],
+        depth: f32,
+    ) -> Result<Vec<LodMesh>, Error> {
+        let curves = self.curves();
+
+        qualities
+            .iter()
+            .map(|&quality| {
+                Ok(LodMesh {
+                    quality,
+                    mesh: self.to_3d_mesh(quality, depth)?.to_owned_mesh(),
+                    max_error: flattening_error(&curves, self.inner, quality),
+                })
+            })
+            .collect()
+    }
+
+    /// Points of the outline contours, with `true` for points on the curve
+    fn curves(&self) -> Vec<Vec<(f32, f32, bool)>> {
+        if self.inner.outline.is_null() {
+            return Vec::new();
+        }
+
+        contours(unsafe { &*self.inner.outline })
+            .iter()
+            .map(|contour| {
+                points(contour)
+                    .iter()
+                    .map(|p| (p.x, p.y, p.onc() != 0))
+                    .collect()
+            })
+            .collect()
+    }
+}
+
+/// A mesh of a glyph at one level of detail, see [`Glyph::to_lod_meshes`]
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct LodMesh {
+    pub quality: Quality,
+    pub mesh: OwnedMesh,
+    /// Largest distance (in em units) between the outline curves and the mesh outline
+    pub max_error: f32,
+}
+
+/// Largest distance between outline curves and their approximation at a quality
+fn flattening_error(
+    curves: &[Vec<(f32, f32, bool)>],
+    glyph: &sys::ttf_glyph,
+    quality: Quality,
+) -> f32 {
+    let outline = match LinearOutline::new(glyph, quality) {
+        Some(outline) => outline,
+        None => return 0.0,
+    };
+
+    let polylines = outline
+        .contours()
+        .iter()
+        .map(|contour| points(contour).iter().map(|p| (p.x, p.y)).collect())
+        .collect::<Vec<Vec<_>>>();
+
+    curves
+        .iter()
+        .enumerate()
+        .map(|(index, curve)| {
+            // contours are flattened one by one, match them by index when possible
+            if curves.len() == polylines.len() {
+                quality::flattening_error(curve, &polylines[index..=index])
+            } else {
+                quality::flattening_error(curve, &polylines)
+            }
+        })
+        .fold(0.0, f32::max)
+}
+
+/// Check whether a point lies inside a glyph, see [`Glyph::contains`]. For glyphs which are
+/// only borrowed from the font, the outline is linearized for each query
+pub(crate) fn contains(glyph: &sys::ttf_glyph, x: f32, y: f32) -> bool {
+    in_bounds(glyph, x, y)
+        && matches!(HitOutline::new(glyph), Some(outline) if outline.contains(x, y))
+}
+
+/// The point is within the bounding box of the glyph outline, which all its points are
+fn in_bounds(glyph: &sys::ttf_glyph, x: f32, y: f32) -> bool {
+    (glyph.xbounds[0]..=glyph.xbounds[1]).contains(&x)
+        && (glyph.ybounds[0]..=glyph.ybounds[1]).contains(&y)
+}
+
+/// High quality linear outline for even-odd point queries
+struct HitOutline {
+    outline: LinearOutline,
+    /// Subglyph orders of the contours: components of composite glyphs are filled
+    /// independently
+    orders: Vec<c_int>,
+}
+
+impl HitOutline {
+    fn new(glyph: &sys::ttf_glyph) -> Option<Self> {
+        let outline = LinearOutline::new(glyph, Quality::High)?;
+        let mut orders = outline
+            .contours()
+            .iter()
+            .map(|c| c.subglyph_order)
+            .collect::<Vec<_>>();
+        orders.dedup();
+
+        Some(Self { outline, orders })
+    }
+
+    fn contains(&self, x: f32, y: f32) -> bool {
+        let point = [x, y];
+
+        self.orders.iter().any(|&order| unsafe {
+            sys::ttf_outline_evenodd(self.outline.raw, point.as_ptr(), order)
+        })
+    }
+
+    fn contains_with_distance(&self, x: f32, y: f32) -> (bool, f32) {
+        let point = [x, y];
+
+        self.orders
+            .iter()
+            .fold((false, f32::INFINITY), |(inside, distance), &order| {
+                let mut dist = f32::INFINITY;
+                let crossings = unsafe {
+                    sys::ttf_outline_evenodd_base(
+                        self.outline.raw,
+                        point.as_ptr(),
+                        order,
+                        &mut dist,
+                    )
+                };
+
+                (inside || crossings % 2 == 1, distance.min(dist))
+            })
+    }
+}
+
+/// Glyph outline with curves approximated by line segments, freed on drop
+struct LinearOutline {
+    raw: *mut sys::ttf_outline,
+}
+
+impl LinearOutline {
+    fn new(glyph: &sys::ttf_glyph, quality: Quality) -> Option<Self> {
+        if glyph.outline.is_null() {
+            return None;
+        }
+
+        let raw = unsafe { sys::ttf_linear_outline(glyph, quality.as_u8()) };
+        if raw.is_null() {
+            return None;
+        }
+
+        Some(Self { raw })
+    }
+
+    fn contours(&self) -> &[sys::ttf_outline__bindgen_ty_1] {
+        contours(unsafe { &*self.raw })
+    }
+}
+
+impl Drop for LinearOutline {
+    fn drop(&mut self) {
+        unsafe { sys::ttf_free_outline(self.raw) }
+    }
+}
+
+fn contours(outline: &sys::ttf_outline) -> &[sys::ttf_outline__bindgen_ty_1] {
+    if outline.ncontours <= 0 {
+        return &[];
+    }
+
+    unsafe { slice::from_raw_parts(outline.cont.as_ptr(), outline.ncontours as usize) }
+}
+
+fn points(contour: &sys::ttf_outline__bindgen_ty_1) -> &[sys::ttf_point] {
+    if contour.pt.is_null() || contour.length <= 0 {
+        return &[];
+    }
+
+    unsafe { slice::from_raw_parts(contour.pt, contour.length as usize) }
+}
+
+/// Orientation by the sign of the contour area (shoelace formula)
+fn orientation(points: &[sys::ttf_point]) -> Orientation {
+    let area: f32 = points
+        .iter()
+        .zip(points.iter().cycle().skip(1))
+        .map(|(a, b)| a.x * b.y - b.x * a.y)
+        .sum();
+
+    if area < 0.0 {
+        Orientation::Clockwise
+    } else {
+        Orientation::CounterClockwise
+    }
 }
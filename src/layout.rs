@@ -0,0 +1,261 @@
+//! Text layout: placing the glyphs of a string
+use crate::{
+    bidi, glyph,
+    sfnt::{self, Sfnt},
+    vertical::{self, VerticalMetrics},
+    Coords2, TTFFileRef,
+};
+
+/// Line height used when the font has no usable `hhea` table, in em units
+const DEFAULT_LINE_HEIGHT: f32 = 1.2;
+
+/// A glyph placed by [`TTFFileRef::layout`]
+#[derive(Debug, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct PositionedGlyph {
+    /// Index of the character (not byte) within the laid out text
+    pub char_index: usize,
+    /// Glyph index, see [`TTFFileRef::glyph_by_index`]
+    pub glyph_index: usize,
+    /// Horizontal position of the glyph origin, in em units
+    pub x: f32,
+    /// Vertical position of the glyph origin (baseline), in em units
+    pub y: f32,
+    /// The glyph is rotated 90° clockwise (Latin text in [`TTFFileRef::layout_vertical`]): a mesh
+    /// vertex `(vx, vy)` is placed at `(x + vy, y - vx)`
+    pub rotated: bool,
+}
+
+impl PositionedGlyph {
+    /// Position of the glyph origin as `(x, y)` or a math library type, see [`Coords2`]
+    pub fn origin<P: Coords2>(&self) -> P {
+        P::from_xy(self.x, self.y)
+    }
+}
+
+impl TTFFileRef<'_> {
+    /// Place the glyphs of a text. The first baseline starts at the origin, and each `\n` starts
+    /// a new line below. Characters which the font does not map use the `.notdef` glyph
+    ///
+    /// With the `bidi` feature, mixed-direction lines are reordered by the Unicode bidirectional
+    /// algorithm: right-to-left runs (Hebrew, Arabic) are drawn in reverse, with mirrored
+    /// brackets. Glyphs are returned in visual order, left to right
+    ///
+    /// With the `shaping` feature, each run is shaped with [`TTFFileRef::shape`], so a glyph may
+    /// cover several characters (ligatures) and `char_index` is the first character of its
+    /// cluster. Otherwise characters map one-to-one to glyphs
+    ///
+    /// Positions are in em units, same as mesh vertices: a glyph mesh is translated by
+    /// `(x, y)` to its place in the text
+    pub fn layout(&self, text: &str) -> Vec<PositionedGlyph> {
+        let line_height = line_height(self.instance_data());
+        let mut glyphs = Vec::with_capacity(text.len());
+        let mut first_char = 0;
+        let mut y = 0.0;
+
+        for line in text.split('\n') {
+            self.layout_line(line, first_char, y, &mut glyphs);
+
+            first_char += line.chars().count() + 1;
+            y -= line_height;
+        }
+
+        glyphs
+    }
+
+    /// Place the glyphs of a text in vertical columns, top to bottom. The first column is
+    /// centered on `x = 0` with its top at the origin, and each `\n` starts a new column to the
+    /// left
+    ///
+    /// CJK characters stay upright and advance by the vertical metrics of the font (`vhea` /
+    /// `vmtx`), which are synthesized from the ascender and descender for fonts without them.
+    /// They follow the variation instance, and grow by `2 * embolden` of the synthetic style
+    /// like horizontal advances. Other characters (e.g. Latin) are rotated 90° clockwise, see
+    /// [`PositionedGlyph::rotated`], and advance by their width. Characters map one-to-one to
+    /// glyphs
+    ///
+    /// Usage:
+    /// ```rust
+    /// # use ttf2mesh::TTFFile;
+    /// let ttf = TTFFile::from_file("./fonts/FiraMono-Medium.ttf").unwrap();
+    ///
+    /// let glyphs = ttf.layout_vertical("AB");
+    /// assert!(glyphs.iter().all(|glyph| glyph.rotated));
+    /// assert!(glyphs[1].y < glyphs[0].y);
+    /// ```
+    pub fn layout_vertical(&self, text: &str) -> Vec<PositionedGlyph> {
+        let chars = text
+            .chars()
+            .map(|char| {
+                let glyph_index = self.glyph_index(char).unwrap_or(0);
+                let width = self
+                    .raw_glyph(glyph_index)
+                    .map_or(0.0, |glyph| glyph.advance);
+
+                (char, glyph_index, width)
+            })
+            .collect::<Vec<_>>();
+
+        let metrics = VerticalMetrics::parse(self.instance_data(), self.synthetic_style());
+        let column_width = metrics
+            .as_ref()
+            .and_then(|metrics| metrics.column_width())
+            .unwrap_or_else(|| line_height(self.instance_data()));
+        let center = metrics.as_ref().map_or(0.0, |metrics| metrics.center());
+
+        let mut glyphs = Vec::with_capacity(chars.len());
+        let (mut x, mut y) = (0.0, 0.0);
+
+        for (char_index, (char, glyph_index, width)) in chars.into_iter().enumerate() {
+            if char == '\n' {
+                x -= column_width;
+                y = 0.0;
+                continue;
+            }
+
+            if vertical::is_upright(char) {
+                let (advance, origin_y) = match &metrics {
+                    Some(metrics) => (metrics.advance(glyph_index), metrics.origin_y(glyph_index)),
+                    None => (DEFAULT_LINE_HEIGHT, 1.0),
+                };
+
+                glyphs.push(PositionedGlyph {
+                    char_index,
+                    glyph_index,
+                    x: x - width / 2.0,
+                    y: y - origin_y,
+                    rotated: false,
+                });
+                y -= advance;
+            } else {
+                glyphs.push(PositionedGlyph {
+                    char_index,
+                    glyph_index,
+                    x: x - center,
+                    y,
+                    rotated: true,
+                });
+                y -= width;
+            }
+        }
+
+        glyphs
+    }
+
+    /// Find the character of a text whose glyph contains a point (in em units, in the
+    /// coordinates of [`TTFFileRef::layout`]). Returns the character (not byte) index
+    ///
+    /// Usage:
+    /// ```rust
+    /// # use ttf2mesh::TTFFile;
+    /// let ttf = TTFFile::from_file("./fonts/FiraMono-Medium.ttf").unwrap();
+    ///
+    /// // FiraMono glyphs are 0.6em wide
+    /// assert_eq!(ttf.hit_test("II", 0.9, 0.35), Some(1));
+    /// assert_eq!(ttf.hit_test("II", 0.9, -0.5), None);
+    /// ```
+    pub fn hit_test(&self, text: &str, x: f32, y: f32) -> Option<usize> {
+        let glyphs = self.layout(text);
+
+        // later glyphs are drawn on top
+        for placed in glyphs.iter().rev() {
+            let glyph = match self.raw_glyph(placed.glyph_index) {
+                Some(glyph) => glyph,
+                None => continue,
+            };
+
+            if glyph::contains(glyph, x - placed.x, y - placed.y) {
+                return Some(placed.char_index);
+            }
+        }
+
+        None
+    }
+
+    fn layout_line(
+        &self,
+        line: &str,
+        first_char: usize,
+        y: f32,
+        glyphs: &mut Vec<PositionedGlyph>,
+    ) {
+        // byte offsets of characters, for mapping runs and clusters to character indices
+        let offsets = line.char_indices().map(|(i, _)| i).collect::<Vec<_>>();
+        let char_index = |byte: usize| match offsets.binary_search(&byte) {
+            Ok(index) | Err(index) => first_char + index,
+        };
+        let mut x = 0.0;
+
+        for (range, rtl) in bidi::visual_runs(line) {
+            let run = &line[range.clone()];
+
+            #[cfg(feature = "shaping")]
+            {
+                if let Ok(shaped) = self.shape_run(run, rtl) {
+                    for glyph in shaped {
+                        glyphs.push(PositionedGlyph {
+                            char_index: char_index(range.start + glyph.cluster),
+                            glyph_index: glyph.glyph_index,
+                            x: x + glyph.x_offset,
+                            y: y + glyph.y_offset,
+                            rotated: false,
+                        });
+                        x += glyph.x_advance;
+                    }
+
+                    continue;
+                }
+            }
+
+            let mut chars = run.char_indices().collect::<Vec<_>>();
+            if rtl == Some(true) {
+                chars.reverse();
+            }
+
+            for (offset, char) in chars {
+                let char = if rtl == Some(true) {
+                    bidi::mirror(char)
+                } else {
+                    char
+                };
+
+                let glyph_index = self.glyph_index(char).unwrap_or(0);
+                let advance = self
+                    .raw_glyph(glyph_index)
+                    .map_or(0.0, |glyph| glyph.advance);
+
+                glyphs.push(PositionedGlyph {
+                    char_index: char_index(range.start + offset),
+                    glyph_index,
+                    x,
+                    y,
+                    rotated: false,
+                });
+                x += advance;
+            }
+        }
+    }
+}
+
+/// Distance between baselines from `hhea` (ascender - descender + line gap), in em units
+pub(crate) fn line_height(data: &[u8]) -> f32 {
+    let metrics = Sfnt::parse(data).and_then(|sfnt| {
+        let hhea = sfnt.table(b"hhea")?;
+        let units_per_em = sfnt::get_u16(sfnt.table(b"head")?, 18)?;
+
+        let ascender = sfnt::get_u16(hhea, 4)? as i16 as f32;
+        let descender = sfnt::get_u16(hhea, 6)? as i16 as f32;
+        let line_gap = sfnt::get_u16(hhea, 8)? as i16 as f32;
+
+        if units_per_em == 0 {
+            return None;
+        }
+
+        Some((ascender - descender + line_gap) / units_per_em as f32)
+    });
+
+    match metrics {
+        Some(height) if height > 0.0 => height,
+        _ => DEFAULT_LINE_HEIGHT,
+    }
+}
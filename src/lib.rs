@@ -18,36 +18,83 @@
 //! let mesh_2d: Mesh<Mesh2d> = glyph.to_2d_mesh(Quality::Medium).unwrap();
 //!
 //! // work with Mesh vertices, faces (indices). See Mesh documentation for more
+//! # if cfg!(not(feature = "pure-rust")) {
 //! assert_eq!(mesh_2d.vertices_len(), 56);
 //! assert_eq!(mesh_2d.iter_vertices().next().unwrap().val(), (0.555, 0.656));
 //!
 //! assert_eq!(mesh_2d.faces_len(), 54);
 //! assert_eq!(mesh_2d.iter_faces().next().unwrap().val(), (53, 52, 5));
+//! # } else {
+//! #     // the pure-rust mesher triangulates differently
+//! #     assert!(mesh_2d.vertices_len() > 0);
+//! #     let (a, b, c) = mesh_2d.iter_faces().next().unwrap().val();
+//! #     assert!((a.max(b).max(c) as usize) < mesh_2d.vertices_len());
+//! # }
 //!
 //! // 3d mesh with depth of 0.5
 //! let mesh_3d: Mesh<Mesh3d> = glyph.to_3d_mesh(Quality::Medium, 0.5).unwrap();
 //! ```
 #![cfg_attr(feature = "unstable", feature(test))]
 
+#[cfg(not(target_arch = "wasm32"))]
 use std::{ffi::CString, path::Path};
 
+mod bidi;
+mod cdt;
+mod collection;
+mod color;
+#[cfg(not(target_arch = "wasm32"))]
+mod database;
+mod decoration;
+mod deform;
 mod error;
 mod glyph;
+mod layout;
+mod loader;
+mod math;
 mod mesh;
 mod output;
+mod owned_mesh;
+mod path;
 mod quality;
+mod sfnt;
+#[cfg(feature = "shaping")]
+mod shaping;
+mod substitution;
+mod synthetic;
+mod tables;
 mod ttf;
-
+mod validate;
+mod variation;
+mod vertical;
+#[cfg(feature = "woff")]
+mod woff;
+
+pub use collection::{FaceInfo, Italic, Weight};
+pub use color::ColorLayer;
+#[cfg(not(target_arch = "wasm32"))]
+pub use database::{FontDatabase, FontDescriptor};
+pub use decoration::{Decoration, DecorationLine, DecorationMetrics};
 pub use error::Error;
-pub use glyph::Glyph;
+pub use glyph::{ContourInfo, Glyph, GlyphMetrics, LodMesh, Orientation};
+pub use layout::PositionedGlyph;
+pub use math::{Coords2, Coords3, FromValue};
 pub use mesh::{Mesh, Mesh2d, Mesh3d};
 pub use output::{DataIterator, Value};
+pub use owned_mesh::OwnedMesh;
+pub use path::{PathGlyph, TextPath};
 pub use quality::Quality;
-pub use ttf::TTFFile;
+#[cfg(feature = "shaping")]
+pub use shaping::ShapedGlyph;
+pub use substitution::SubstitutedGlyph;
+pub use synthetic::SyntheticStyle;
+pub use ttf::{TTFFile, TTFFileRef};
+pub use validate::MeshReport;
+pub use variation::VariationAxis;
 
 // TODO: support TTF_FEATURE_IGN_ERR as bitflag
 
-#[cfg(not(windows))]
+#[cfg(all(not(windows), not(target_arch = "wasm32")))]
 fn path_to_cstring<P: AsRef<Path>>(path: P) -> CString {
     use std::os::unix::ffi::OsStrExt;
     CString::new(path.as_ref().as_os_str().as_bytes()).unwrap()
@@ -89,6 +136,15 @@ mod tests {
         let _ = TTFFile::from_file(get_font(None)).unwrap();
     }
 
+    #[test]
+    fn test_from_slice() {
+        let data = read_font(None);
+        let mut font = TTFFileRef::from_slice(&data).unwrap();
+
+        assert_eq!(font.glyph_count(), 1485);
+        let _ = font.glyph_from_char('A').unwrap();
+    }
+
     #[test]
     fn test_get_glyph_from_char() {
         let mut font = TTFFile::from_buffer_vec(read_font(None)).unwrap();
@@ -96,35 +152,401 @@ mod tests {
     }
 
     #[test]
-    fn test_to_3d_mesh() {
+    fn test_cff_fonts() {
+        let mut ttf = TTFFile::from_buffer_vec(read_font(None)).unwrap();
+
+        for (file, chars) in &[
+            ("FiraMono-Medium-CFF.otf", "AEOog\u{c9}"),
+            ("FiraMono-Medium-CFF2.otf", "AEOog"),
+        ] {
+            let mut font = TTFFile::from_buffer_vec(read_font(Some(file))).unwrap();
+
+            for c in chars.chars() {
+                let mut glyph = font.glyph_from_char(c).unwrap();
+                let expected = ttf.glyph_from_char(c).unwrap().contours_info().unwrap();
+                assert_eq!(glyph.contours_info().unwrap().len(), expected.len());
+
+                let mesh = glyph.to_2d_mesh(Quality::Medium).unwrap();
+                assert!(mesh.vertices_len() > 0);
+                assert!(mesh
+                    .to_owned_mesh()
+                    .validate()
+                    .out_of_bounds_faces
+                    .is_empty());
+            }
+        }
+
+        // CFF2 variable fonts are static fonts once converted
+        let mut font =
+            TTFFile::from_buffer_vec(read_font(Some("FiraMono-Medium-CFF2.otf"))).unwrap();
+        assert!(font.axes().is_empty());
+        assert!(font.set_variation(&[("wght", 700.0)]).is_err());
+    }
+
+    #[test]
+    fn test_contours_info() {
+        let mut font = TTFFile::from_buffer_vec(read_font(None)).unwrap();
+        let glyph = font.glyph_from_char('O').unwrap();
+
+        let info = glyph.contours_info().unwrap();
+        assert_eq!(info.len(), 2);
+
+        let (outer, hole) = if info[0].hole {
+            (info[1], info[0])
+        } else {
+            (info[0], info[1])
+        };
+        assert!(!outer.hole);
+        assert_eq!(outer.parent, None);
+        assert!(hole.parent.is_some());
+        assert_ne!(outer.orientation, hole.orientation);
+
+        // center of the counter is outside, the ring is inside
+        assert!(!glyph.contains(0.3, 0.345));
+        assert!(glyph.contains(0.08, 0.345));
+
+        let (inside, distance) = glyph.contains_with_distance(0.3, 0.345);
+        assert!(!inside && distance.is_finite());
+        assert!(glyph.contains_with_distance(0.08, 0.345).0);
+    }
+
+    #[cfg(feature = "shaping")]
+    #[test]
+    fn test_shape() {
+        let font = TTFFile::from_buffer_vec(read_font(None)).unwrap();
+        let shaped = font.shape("A€").unwrap();
+
+        assert_eq!(shaped.len(), 2);
+        assert_eq!(shaped[0].glyph_index, font.glyph_index('A').unwrap());
+        assert_eq!(shaped[1].glyph_index, font.glyph_index('€').unwrap());
+        assert_eq!(shaped[1].cluster, 1);
+        assert!((shaped[0].x_advance - 0.6).abs() < 1e-6);
+    }
+
+    #[cfg(feature = "bidi")]
+    #[test]
+    fn test_layout_bidi() {
+        let font = TTFFile::from_buffer_vec(read_font(None)).unwrap();
+
+        // the Hebrew word is drawn right-to-left between the Latin words
+        let glyphs = font.layout("ab \u{5d0}\u{5d1} (c)");
+        let order = glyphs.iter().map(|g| g.char_index).collect::<Vec<_>>();
+        assert_eq!(order, &[0, 1, 2, 4, 3, 5, 6, 7, 8]);
+
+        assert!(glyphs.windows(2).all(|pair| pair[0].x < pair[1].x));
+    }
+
+    #[test]
+    fn test_quality_for_tolerance() {
+        let mut font = TTFFile::from_buffer_vec(read_font(None)).unwrap();
+
+        for char in &['O', 'S'] {
+            let glyph = font.glyph_from_char(*char).unwrap();
+
+            let coarse = glyph.quality_for_tolerance(0.01);
+            let fine = glyph.quality_for_tolerance(0.002);
+            assert!(glyph.flattening_error(coarse) <= 0.01);
+            assert!(glyph.flattening_error(fine) <= 0.002);
+            assert!(coarse.as_u8() <= fine.as_u8());
+
+            let at_48px = glyph.quality_for_pixel_size(48.0);
+            assert!(glyph.flattening_error(at_48px) <= 0.25 / 48.0);
+        }
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn test_serde() {
+        let mut font = TTFFile::from_buffer_vec(read_font(None)).unwrap();
+        let mut glyph = font.glyph_from_char('A').unwrap();
+
+        let metrics = glyph.metrics();
+        assert_eq!(metrics.advance, glyph.advance());
+        let data = bincode::serialize(&metrics).unwrap();
+        assert_eq!(
+            bincode::deserialize::<GlyphMetrics>(&data).unwrap(),
+            metrics
+        );
+
+        let levels = glyph.to_lod_meshes(&[Quality::Low]).unwrap();
+        let data = bincode::serialize(&levels).unwrap();
+        assert_eq!(bincode::deserialize::<Vec<LodMesh>>(&data).unwrap(), levels);
+
+        let faces = TTFFile::collection_faces_from_slice(&read_font(None)).unwrap();
+        let data = bincode::serialize(&faces).unwrap();
+        assert_eq!(bincode::deserialize::<Vec<FaceInfo>>(&data).unwrap(), faces);
+    }
+
+    #[test]
+    fn test_to_lod_meshes() {
         let mut font = TTFFile::from_buffer_vec(read_font(None)).unwrap();
         let mut glyph = font.glyph_from_char('€').unwrap();
-        let mesh = glyph.to_3d_mesh(Quality::Low, 0.5).unwrap();
 
-        let mut sizes = Vec::new();
-        sizes.extend_from_slice(&[
-            mesh.iter_vertices().collect::<Vec<_>>().len(),
-            mesh.iter_normals().unwrap().collect::<Vec<_>>().len(),
-            mesh.iter_faces().collect::<Vec<_>>().len(),
-        ]);
+        let qualities = [Quality::Low, Quality::High, Quality::Custom(255)];
+        let levels = glyph.to_lod_meshes(&qualities).unwrap();
+
+        let sizes = levels
+            .iter()
+            .flat_map(|level| vec![level.mesh.vertices_len(), level.mesh.faces_len()])
+            .collect::<Vec<_>>();
+        assert!(sizes.iter().all(|&size| size > 0));
+        assert!(levels
+            .windows(2)
+            .all(|l| l[0].mesh.vertices_len() < l[1].mesh.vertices_len()));
+        // vertex counts of the C mesher
+        if cfg!(not(feature = "pure-rust")) {
+            assert_eq!(sizes, &[41, 39, 92, 90, 194, 192]);
+        }
+
+        assert!(levels.windows(2).all(|l| l[0].max_error > l[1].max_error));
+        assert_eq!(levels[1].max_error, glyph.flattening_error(Quality::High));
+
+        let levels = glyph.to_3d_lod_meshes(&qualities[..1], 0.5).unwrap();
+        assert!(levels[0].mesh.is_3d());
+        assert_eq!(levels[0].mesh.normals.len(), levels[0].mesh.vertices_len());
+        if cfg!(not(feature = "pure-rust")) {
+            assert_eq!(levels[0].mesh.vertices_len(), 246);
+        }
+    }
+
+    #[test]
+    fn test_pure_rust_mesher() {
+        let mut font = TTFFile::from_buffer_vec(read_font(None)).unwrap();
+
+        let area = |mesh: &OwnedMesh| {
+            mesh.faces
+                .iter()
+                .map(|&(a, b, c)| {
+                    let p = |i: i32| mesh.vertices[i as usize];
+                    let (a, b, c) = (p(a), p(b), p(c));
+                    ((b.0 - a.0) * (c.1 - a.1) - (b.1 - a.1) * (c.0 - a.0)).abs() / 2.0
+                })
+                .sum::<f32>()
+        };
+
+        // both meshers cover the same area
+        for char in "A€O%&@g8".chars() {
+            let mut glyph = font.glyph_from_char(char).unwrap();
+
+            let c = glyph.mesh_2d_c(Quality::High).unwrap().to_owned_mesh();
+            let rust = glyph.mesh_2d_rust(Quality::High).unwrap().to_owned_mesh();
+            assert!(rust.validate().is_valid());
+
+            let (c, rust) = (area(&c), area(&rust));
+            assert!((c - rust).abs() / c < 0.01, "{}: {} != {}", char, c, rust);
+
+            let mesh = glyph.mesh_3d_rust(Quality::High, 0.5).unwrap();
+            assert_eq!(mesh.normals_len(), mesh.vertices_len());
+        }
+    }
+
+    #[test]
+    fn test_synthetic_style() {
+        let mut font = TTFFile::from_buffer_vec(read_font(None)).unwrap();
+        let regular = font.glyph_from_char('I').unwrap().metrics();
+        let underline = font.decoration_metrics(Decoration::Underline);
+
+        let style = SyntheticStyle {
+            embolden: 0.02,
+            skew: 0.2,
+        };
+        font.set_synthetic_style(style).unwrap();
+        let mut glyph = font.glyph_from_char('I').unwrap();
+        let bold = glyph.metrics();
+
+        assert!((bold.advance - regular.advance - 0.04).abs() < 1e-6);
+        assert!(bold.x_bounds.1 - regular.x_bounds.1 > 0.2 * regular.y_bounds.1);
+        assert!(glyph
+            .to_2d_mesh(Quality::Medium)
+            .unwrap()
+            .validate()
+            .is_valid());
+
+        // upright glyphs of vertical text advance by the emboldened height too
+        let column = font.layout_vertical("\u{3000}\u{3000}");
+        assert!((column[0].y - column[1].y - 1.24).abs() < 1e-6);
+
+        // decorations are as thick as the emboldened stems
+        let bold_underline = font.decoration_metrics(Decoration::Underline);
+        assert!((bold_underline.thickness - underline.thickness - 0.04).abs() < 1e-6);
+
+        font.set_synthetic_style(SyntheticStyle::default()).unwrap();
+        assert_eq!(font.glyph_from_char('I').unwrap().metrics(), regular);
+
+        // the static font has no variations, the default instance reloads it as it is
+        font.set_variation(&[]).unwrap();
+        assert_eq!(font.glyph_from_char('I').unwrap().metrics(), regular);
+        assert!(font.set_variation(&[("wght", 700.0)]).is_err());
+    }
+
+    #[test]
+    fn test_glyph_names() {
+        let mut font = TTFFile::from_buffer_vec(read_font(None)).unwrap();
 
-        let mesh = glyph.to_3d_mesh(Quality::High, 1.5).unwrap();
+        assert_eq!(font.glyph_from_char('A').unwrap().name().unwrap(), "A");
+        assert_eq!(font.glyph_from_char('€').unwrap().name().unwrap(), "Euro");
 
-        sizes.extend_from_slice(&[
-            mesh.iter_vertices().collect::<Vec<_>>().len(),
-            mesh.iter_normals().unwrap().collect::<Vec<_>>().len(),
-            mesh.iter_faces().collect::<Vec<_>>().len(),
-        ]);
+        let glyph = font.glyph_by_name("zero.zero").unwrap();
+        assert_eq!(glyph.index(), 918);
+        assert_eq!(glyph.name().unwrap(), "zero.zero");
 
-        let mesh = glyph.to_3d_mesh(Quality::Custom(255), 0.5).unwrap();
+        assert!(matches!(
+            font.glyph_by_name("missing"),
+            Err(Error::GlyphNotFound)
+        ));
+    }
 
-        sizes.extend_from_slice(&[
-            mesh.iter_vertices().collect::<Vec<_>>().len(),
-            mesh.iter_normals().unwrap().collect::<Vec<_>>().len(),
-            mesh.iter_faces().collect::<Vec<_>>().len(),
-        ]);
+    #[test]
+    fn test_substitute() {
+        let font = TTFFile::from_buffer_vec(read_font(None)).unwrap();
+        assert!(font.gsub_features().contains(&"dlig".to_string()));
+
+        let glyphs = font.substitute("fi0", &["dlig", "zero", "invalid"]);
+        let indices = glyphs
+            .iter()
+            .map(|glyph| (glyph.glyph_index, glyph.char_index))
+            .collect::<Vec<_>>();
+        assert_eq!(indices, &[(273, 0), (918, 2)]);
+
+        let glyphs = font.substitute("fi", &[]);
+        assert_eq!(glyphs.len(), 2);
+        assert_eq!(glyphs[1].char_index, 1);
+    }
 
-        assert_eq!(sizes, &[246, 246, 160, 552, 552, 364, 1164, 1164, 772]);
+    #[test]
+    fn test_color_layers() {
+        use crate::{
+            sfnt::{Sfnt, SfntBuilder},
+            tables::{colr, cpal},
+        };
+
+        // 'A' (glyph 4) as a blue or translucent red zero under an 'A' in the text color
+        let font = read_font(None);
+        let mut builder = SfntBuilder::from_sfnt(&Sfnt::parse(&font).unwrap());
+        builder.insert(
+            *b"COLR",
+            colr::tests::build(&[(4, &[(918, 0), (4, colr::FOREGROUND)])]),
+        );
+        builder.insert(
+            *b"CPAL",
+            cpal::tests::build(&[&[[255, 0, 0, 255]], &[[0, 0, 255, 128]]]),
+        );
+        let mut font = TTFFile::from_buffer_vec(builder.build()).unwrap();
+        assert_eq!(font.palette_count(), 2);
+
+        let layers = font.color_layers(4, 1, Quality::Medium).unwrap();
+        assert_eq!(layers.len(), 2);
+        assert_eq!(layers[0].glyph_index, 918);
+        assert_eq!(layers[0].color, Some([255, 0, 0, 128]));
+        assert_eq!(layers[1].color, None);
+        assert!(layers.iter().all(|layer| layer.mesh.faces_len() > 0));
+
+        let layers = font.color_layers(4, 0, Quality::Medium).unwrap();
+        assert_eq!(layers[0].color, Some([0, 0, 255, 255]));
+
+        assert!(font.color_layers(5, 0, Quality::Medium).unwrap().is_empty());
+        assert!(matches!(
+            font.color_layers(4, 2, Quality::Medium),
+            Err(Error::PaletteNotFound)
+        ));
+    }
+
+    #[test]
+    fn test_non_bmp_chars() {
+        use crate::{
+            sfnt::{Sfnt, SfntBuilder},
+            tables::cmap,
+        };
+
+        // '😀' as the glyph of 'A' (4), in a full Unicode subtable next to the BMP one
+        let font = read_font(None);
+        let sfnt = Sfnt::parse(&font).unwrap();
+        let cmap = cmap::tests::with_segmented_coverage(
+            sfnt.table(b"cmap").unwrap(),
+            &[(0x1F600, 0x1F600, 4)],
+        );
+        let mut builder = SfntBuilder::from_sfnt(&sfnt);
+        builder.insert(*b"cmap", cmap);
+        let mut font = TTFFile::from_buffer_vec(builder.build()).unwrap();
+
+        assert_eq!(font.glyph_from_char('😀').unwrap().index(), 4);
+        assert_eq!(font.glyph_from_char('A').unwrap().index(), 4);
+        assert!(matches!(
+            font.glyph_from_char('😁'),
+            Err(Error::GlyphNotFound)
+        ));
+        assert_eq!(font.substitute("A😀", &[])[1].glyph_index, 4);
+    }
+
+    #[test]
+    fn test_decorations() {
+        let mut font = TTFFile::from_buffer_vec(read_font(None)).unwrap();
+        let glyphs = font.layout("xx\nxgx");
+        let decorations = [Decoration::Underline, Decoration::Strikethrough];
+
+        let lines = font.decorations(&glyphs, &decorations, false);
+        assert_eq!(lines.len(), 4);
+        assert_eq!(lines[0].x, (0.0, 1.2));
+        assert!(lines[2].y.1 < lines[0].y.0 - 1.0);
+
+        // the descender of `g` cuts the underline of the second line only
+        let lines = font.decorations(&glyphs, &decorations, true);
+        let underlines = lines
+            .iter()
+            .filter(|line| line.decoration == Decoration::Underline)
+            .map(|line| line.x)
+            .collect::<Vec<_>>();
+        assert_eq!(underlines.len(), 3);
+        assert_eq!(underlines[0], (0.0, 1.2));
+        assert!(underlines[1].0 == 0.0 && underlines[1].1 > 0.5 && underlines[1].1 < 0.9);
+        assert!(underlines[2].0 > 0.9 && underlines[2].0 < 1.3);
+        assert!((underlines[2].1 - 1.8).abs() < 1e-6);
+    }
+
+    #[test]
+    fn test_variation_static_font() {
+        let mut font = TTFFile::from_buffer_vec(read_font(None)).unwrap();
+
+        assert!(font.axes().is_empty());
+        assert!(font.set_variation(&[("wght", 700.0)]).is_err());
+
+        // the default instance is a no-op
+        font.set_variation(&[]).unwrap();
+        assert!(font.variation().is_empty());
+    }
+
+    #[test]
+    fn test_to_3d_mesh() {
+        let mut font = TTFFile::from_buffer_vec(read_font(None)).unwrap();
+        let mut glyph = font.glyph_from_char('€').unwrap();
+
+        let mut sizes = Vec::new();
+        for &(quality, depth) in &[
+            (Quality::Low, 0.5),
+            (Quality::High, 1.5),
+            (Quality::Custom(255), 0.5),
+        ] {
+            let mesh = glyph.to_3d_mesh(quality, depth).unwrap();
+            assert!(mesh
+                .to_owned_mesh()
+                .validate()
+                .out_of_bounds_faces
+                .is_empty());
+
+            sizes.extend_from_slice(&[
+                mesh.iter_vertices().collect::<Vec<_>>().len(),
+                mesh.iter_normals().unwrap().collect::<Vec<_>>().len(),
+                mesh.iter_faces().collect::<Vec<_>>().len(),
+            ]);
+        }
+
+        // a normal for each vertex, more of both at higher qualities
+        assert!(sizes.chunks(3).all(|s| s[0] == s[1] && s[2] > 0));
+        assert!(sizes[0] < sizes[3] && sizes[3] < sizes[6]);
+        // vertex counts of the C mesher
+        if cfg!(not(feature = "pure-rust")) {
+            assert_eq!(sizes, &[246, 246, 160, 552, 552, 364, 1164, 1164, 772]);
+        }
     }
 
     #[test]
@@ -133,26 +555,28 @@ mod tests {
         let mut glyph = font.glyph_from_char('€').unwrap();
 
         let mut sizes = Vec::new();
-        let mesh = glyph.to_2d_mesh(Quality::Low).unwrap();
-        assert!(mesh.iter_normals().is_none());
-        sizes.extend_from_slice(&[
-            mesh.iter_vertices().collect::<Vec<_>>().len(),
-            mesh.iter_faces().collect::<Vec<_>>().len(),
-        ]);
-
-        let mesh = glyph.to_2d_mesh(Quality::High).unwrap();
-        sizes.extend_from_slice(&[
-            mesh.iter_vertices().collect::<Vec<_>>().len(),
-            mesh.iter_faces().collect::<Vec<_>>().len(),
-        ]);
-
-        let mesh = glyph.to_2d_mesh(Quality::Custom(255)).unwrap();
-        sizes.extend_from_slice(&[
-            mesh.iter_vertices().collect::<Vec<_>>().len(),
-            mesh.iter_faces().collect::<Vec<_>>().len(),
-        ]);
-
-        assert_eq!(sizes, &[41, 39, 92, 90, 194, 192]);
+        for &quality in &[Quality::Low, Quality::High, Quality::Custom(255)] {
+            let mesh = glyph.to_2d_mesh(quality).unwrap();
+            assert!(mesh.iter_normals().is_none());
+            assert!(mesh
+                .to_owned_mesh()
+                .validate()
+                .out_of_bounds_faces
+                .is_empty());
+
+            sizes.extend_from_slice(&[
+                mesh.iter_vertices().collect::<Vec<_>>().len(),
+                mesh.iter_faces().collect::<Vec<_>>().len(),
+            ]);
+        }
+
+        // more vertices and faces at higher qualities
+        assert!(sizes[0] < sizes[2] && sizes[2] < sizes[4]);
+        assert!(sizes[1] < sizes[3] && sizes[3] < sizes[5]);
+        // vertex counts of the C mesher
+        if cfg!(not(feature = "pure-rust")) {
+            assert_eq!(sizes, &[41, 39, 92, 90, 194, 192]);
+        }
     }
 }
 
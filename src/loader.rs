@@ -0,0 +1,187 @@
+//! Conversion of font data into a TrueType font that the C library can load
+use std::ops::Deref;
+#[cfg(not(target_arch = "wasm32"))]
+use std::path::Path;
+
+use crate::{
+    collection,
+    sfnt::{self, Sfnt, SfntBuilder, WriteBe, VERSION_TRUETYPE},
+    tables::{cff::Cff, glyf},
+    Error,
+};
+
+/// Tables which only apply to CFF outlines, dropped after conversion
+const CFF_TABLES: [&sfnt::Tag; 3] = [b"CFF ", b"CFF2", b"VORG"];
+
+/// Variation tables of CFF2 fonts, dropped after conversion: the converted outlines are those of
+/// the default instance and have no `gvar` variations, so the font becomes a static font
+const CFF2_VARIATION_TABLES: [&sfnt::Tag; 5] = [b"fvar", b"avar", b"HVAR", b"VVAR", b"MVAR"];
+
+/// Font data retained by a [`crate::TTFFile`]
+///
+/// The C library copies everything it needs while loading, so the data is only kept for the
+/// tables which are read on the Rust side
+pub(crate) enum FontData<'a> {
+    Owned(Vec<u8>),
+    Borrowed(&'a [u8]),
+    #[cfg(feature = "mmap")]
+    Mapped(memmap2::Mmap),
+}
+
+impl Deref for FontData<'_> {
+    type Target = [u8];
+
+    fn deref(&self) -> &[u8] {
+        match self {
+            FontData::Owned(data) => data,
+            FontData::Borrowed(data) => data,
+            #[cfg(feature = "mmap")]
+            FontData::Mapped(map) => map,
+        }
+    }
+}
+
+/// Read a font file, or memory-map it with the `mmap` feature
+#[cfg(not(target_arch = "wasm32"))]
+pub(crate) fn read_file<P: AsRef<Path>>(path: P) -> Result<FontData<'static>, Error> {
+    if !Path::new(path.as_ref().as_os_str()).exists() {
+        return Err(Error::FileNotFound);
+    }
+
+    #[cfg(feature = "mmap")]
+    let data = {
+        let file = std::fs::File::open(path).map_err(|_| Error::FileNotFound)?;
+        // mapping is read-only, and the file is documented to stay unmodified
+        let map = unsafe { memmap2::Mmap::map(&file) }.map_err(|_| Error::FileNotFound)?;
+        FontData::Mapped(map)
+    };
+
+    #[cfg(not(feature = "mmap"))]
+    let data = FontData::Owned(std::fs::read(path).map_err(|_| Error::FileNotFound)?);
+
+    Ok(data)
+}
+
+/// Decode web fonts (WOFF / WOFF2) into sfnt data, when the `woff` feature is enabled
+pub(crate) fn decode(data: FontData) -> Result<FontData, Error> {
+    #[cfg(feature = "woff")]
+    {
+        if let Some(decoded) = crate::woff::decode(&data)? {
+            return Ok(FontData::Owned(decoded));
+        }
+    }
+
+    Ok(data)
+}
+
+/// Prepare a face of the font data for loading. TrueType fonts are used as-is (without copying),
+/// web fonts are decoded, faces of font collections are extracted and fonts with CFF outlines are
+/// converted to TrueType outlines
+pub(crate) fn prepare(data: FontData, face: usize) -> Result<FontData, Error> {
+    let data = decode(data)?;
+
+    let data = match collection::extract(&data, face)? {
+        Some(extracted) => FontData::Owned(extracted),
+        None => data,
+    };
+
+    let converted = match Sfnt::parse(&data) {
+        Some(sfnt) if sfnt.table(b"glyf").is_none() => {
+            if sfnt.table(b"CFF ").is_some() || sfnt.table(b"CFF2").is_some() {
+                Some(cff_to_truetype(&sfnt).ok_or(Error::FontLoadError)?)
+            } else {
+                None
+            }
+        }
+        _ => None,
+    };
+
+    Ok(match converted {
+        Some(converted) => FontData::Owned(converted),
+        None => data,
+    })
+}
+
+/// Replace `CFF `/`CFF2` outlines with quadratic `glyf` outlines. Glyph indices are preserved
+fn cff_to_truetype(sfnt: &Sfnt) -> Option<Vec<u8>> {
+    let head = sfnt.table(b"head")?;
+    let units_per_em = sfnt::get_u16(head, 18)?;
+
+    let (cff, cff2) = match sfnt.table(b"CFF ") {
+        Some(table) => (Cff::parse(table, false, units_per_em)?, false),
+        None => (Cff::parse(sfnt.table(b"CFF2")?, true, units_per_em)?, true),
+    };
+
+    let num_glyphs = glyf::num_glyphs(sfnt.table(b"maxp")?)?;
+
+    let mut encoded = Vec::with_capacity(num_glyphs as usize);
+    let (mut max_points, mut max_contours) = (0, 0);
+
+    for index in 0..num_glyphs {
+        let contours = cff.outline(index)?;
+
+        max_points = max_points.max(contours.iter().map(|c| c.len()).sum::<usize>());
+        max_contours = max_contours.max(contours.len());
+
+        encoded.push(glyf::encode_simple(&contours, &[]));
+    }
+
+    let (glyf_table, loca_table) = glyf::build_tables(&encoded);
+
+    let mut head = head.to_vec();
+    sfnt::set_u16(&mut head, 50, 1); // indexToLocFormat: long offsets
+
+    // CFF fonts carry the short (version 0.5) maxp, TrueType requires version 1.0
+    let mut maxp = Vec::with_capacity(32);
+    maxp.put_u32(0x0001_0000);
+    maxp.put_u16(num_glyphs);
+    maxp.put_u16(max_points.min(u16::MAX as usize) as u16);
+    maxp.put_u16(max_contours.min(u16::MAX as usize) as u16);
+    maxp.put_u16(0); // maxCompositePoints
+    maxp.put_u16(0); // maxCompositeContours
+    maxp.put_u16(2); // maxZones
+    maxp.resize(32, 0);
+
+    let mut builder = SfntBuilder::from_sfnt(sfnt);
+    builder.set_version(VERSION_TRUETYPE);
+    for tag in CFF_TABLES.iter() {
+        builder.remove(tag);
+    }
+    if cff2 {
+        for tag in CFF2_VARIATION_TABLES.iter() {
+            builder.remove(tag);
+        }
+    }
+    builder.insert(*b"head", head);
+    builder.insert(*b"maxp", maxp);
+    builder.insert(*b"glyf", glyf_table);
+    builder.insert(*b"loca", loca_table);
+
+    Some(builder.build())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_prepare_cff() {
+        for (file, cff) in &[
+            ("FiraMono-Medium-CFF.otf", b"CFF "),
+            ("FiraMono-Medium-CFF2.otf", b"CFF2"),
+        ] {
+            let data = FontData::Owned(crate::tests::read_font(Some(file)));
+            let variable = Sfnt::parse(&data).unwrap().table(b"fvar").is_some();
+            assert_eq!(variable, *cff == b"CFF2");
+
+            let prepared = prepare(data, 0).unwrap();
+            let sfnt = Sfnt::parse(&prepared).unwrap();
+
+            assert_eq!(sfnt.version(), VERSION_TRUETYPE);
+            assert!(sfnt.table(b"glyf").is_some() && sfnt.table(b"loca").is_some());
+            assert!(sfnt.table(cff).is_none());
+            // CFF2 variations are dropped with the outlines
+            assert!(sfnt.table(b"fvar").is_none());
+        }
+    }
+}
@@ -0,0 +1,207 @@
+//! Interop with math libraries: mesh values and layout positions as vector and point types of
+//! `mint`, `glam` and `nalgebra` (with the features of the same name)
+
+#[allow(unused_imports)]
+use crate::{OwnedMesh, TextPath, Value};
+
+/// A 2d vector or point: `(f32, f32)`, or with the math library features `mint::Vector2`,
+/// `mint::Point2`, `glam::Vec2`, `nalgebra::Vector2` and `nalgebra::Point2` (of `f32`)
+///
+/// Accepted by [`TextPath`] constructors and produced from 2d mesh vertices by [`Value::val_as`]
+pub trait Coords2: Copy {
+    fn from_xy(x: f32, y: f32) -> Self;
+    fn xy(self) -> (f32, f32);
+}
+
+/// A 3d vector or point: `(f32, f32, f32)`, or with the math library features `mint::Vector3`,
+/// `mint::Point3`, `glam::Vec3`, `glam::Vec3A`, `nalgebra::Vector3` and `nalgebra::Point3` (of
+/// `f32`)
+///
+/// Accepted by [`OwnedMesh::translate_by`] and produced from 3d mesh vertices and normals by
+/// [`Value::val_as`]
+pub trait Coords3: Copy {
+    fn from_xyz(x: f32, y: f32, z: f32) -> Self;
+    fn xyz(self) -> (f32, f32, f32);
+}
+
+/// Conversion from a `.val()` output, see [`Value::val_as`]
+pub trait FromValue<T> {
+    fn from_value(value: T) -> Self;
+}
+
+impl<T: Coords2> FromValue<(f32, f32)> for T {
+    fn from_value((x, y): (f32, f32)) -> Self {
+        T::from_xy(x, y)
+    }
+}
+
+impl<T: Coords3> FromValue<(f32, f32, f32)> for T {
+    fn from_value((x, y, z): (f32, f32, f32)) -> Self {
+        T::from_xyz(x, y, z)
+    }
+}
+
+impl Coords2 for (f32, f32) {
+    fn from_xy(x: f32, y: f32) -> Self {
+        (x, y)
+    }
+
+    fn xy(self) -> (f32, f32) {
+        self
+    }
+}
+
+impl Coords3 for (f32, f32, f32) {
+    fn from_xyz(x: f32, y: f32, z: f32) -> Self {
+        (x, y, z)
+    }
+
+    fn xyz(self) -> (f32, f32, f32) {
+        self
+    }
+}
+
+/// Implement `Coords2` for a type with public `x` and `y` fields
+#[allow(unused_macros)]
+macro_rules! coords2 {
+    ($($ty:ty),* $(,)?) => {
+        $(
+            impl Coords2 for $ty {
+                fn from_xy(x: f32, y: f32) -> Self {
+                    <$ty>::from([x, y])
+                }
+
+                fn xy(self) -> (f32, f32) {
+                    (self.x, self.y)
+                }
+            }
+        )*
+    };
+}
+
+/// Implement `Coords3` for a type with public `x`, `y` and `z` fields
+#[allow(unused_macros)]
+macro_rules! coords3 {
+    ($($ty:ty),* $(,)?) => {
+        $(
+            impl Coords3 for $ty {
+                fn from_xyz(x: f32, y: f32, z: f32) -> Self {
+                    <$ty>::from([x, y, z])
+                }
+
+                fn xyz(self) -> (f32, f32, f32) {
+                    (self.x, self.y, self.z)
+                }
+            }
+        )*
+    };
+}
+
+#[cfg(feature = "mint")]
+coords2! {
+    mint::Vector2<f32>,
+    mint::Point2<f32>,
+}
+
+#[cfg(feature = "mint")]
+coords3! {
+    mint::Vector3<f32>,
+    mint::Point3<f32>,
+}
+
+#[cfg(feature = "glam")]
+coords2! {
+    glam::Vec2,
+}
+
+#[cfg(feature = "glam")]
+coords3! {
+    glam::Vec3,
+    glam::Vec3A,
+}
+
+#[cfg(feature = "nalgebra")]
+coords2! {
+    nalgebra::Vector2<f32>,
+    nalgebra::Point2<f32>,
+}
+
+#[cfg(feature = "nalgebra")]
+coords3! {
+    nalgebra::Vector3<f32>,
+    nalgebra::Point3<f32>,
+}
+
+impl OwnedMesh {
+    /// Vertices as math library types, e.g. `glam::Vec3`
+    pub fn vertices_as<T: Coords3>(&self) -> Vec<T> {
+        self.vertices
+            .iter()
+            .map(|&(x, y, z)| T::from_xyz(x, y, z))
+            .collect()
+    }
+
+    /// Normals as math library types, e.g. `nalgebra::Vector3<f32>`. Empty for 2d meshes
+    pub fn normals_as<T: Coords3>(&self) -> Vec<T> {
+        self.normals
+            .iter()
+            .map(|&(x, y, z)| T::from_xyz(x, y, z))
+            .collect()
+    }
+
+    /// Move the mesh by an offset, see [`OwnedMesh::translate`]
+    pub fn translate_by<T: Coords3>(&self, offset: T) -> OwnedMesh {
+        let (x, y, z) = offset.xyz();
+        self.translate(x, y, z)
+    }
+}
+
+#[cfg(all(test, any(feature = "mint", feature = "glam", feature = "nalgebra")))]
+mod tests {
+    use super::*;
+
+    #[cfg(feature = "glam")]
+    #[test]
+    fn test_glam() {
+        let path = TextPath::polyline(&[glam::Vec2::ZERO, glam::Vec2::new(2.0, 0.0)]).unwrap();
+        assert_eq!(path.length(), 2.0);
+
+        let mesh = OwnedMesh {
+            vertices: vec![(1.0, 2.0, 3.0)],
+            faces: Vec::new(),
+            normals: vec![(0.0, 0.0, 1.0)],
+        };
+        let mesh = mesh.translate_by(glam::Vec3::X);
+        assert_eq!(
+            mesh.vertices_as::<glam::Vec3>(),
+            &[glam::Vec3::new(2.0, 2.0, 3.0)]
+        );
+        assert_eq!(mesh.normals_as::<glam::Vec3A>(), &[glam::Vec3A::Z]);
+    }
+
+    #[cfg(feature = "nalgebra")]
+    #[test]
+    fn test_nalgebra() {
+        let point: nalgebra::Point2<f32> = FromValue::from_value((1.0, 2.0));
+        assert_eq!(point, nalgebra::Point2::new(1.0, 2.0));
+        assert_eq!(
+            Coords3::xyz(nalgebra::Vector3::new(1.0, 2.0, 3.0)),
+            (1.0, 2.0, 3.0)
+        );
+    }
+
+    #[cfg(feature = "mint")]
+    #[test]
+    fn test_mint() {
+        let point = mint::Point3::from_xyz(1.0, 2.0, 3.0);
+        assert_eq!(
+            point,
+            mint::Point3 {
+                x: 1.0,
+                y: 2.0,
+                z: 3.0
+            }
+        );
+        assert_eq!(point.xyz(), (1.0, 2.0, 3.0));
+    }
+}
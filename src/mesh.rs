@@ -1,12 +1,12 @@
 //! Mesh -related structures
 use core::slice;
-use std::{convert::TryInto, marker::PhantomData};
+use std::{convert::TryInto, marker::PhantomData, ptr};
 
 use ttf2mesh_sys as sys;
 
 use crate::{
     output::{DataIterator, Value},
-    Error,
+    Error, MeshReport, OwnedMesh,
 };
 
 /// Type for 3d mesh data
@@ -47,6 +47,8 @@ use crate::Glyph;
 /// ```
 pub struct Mesh<'a, T: InnerMesh<'a>> {
     inner: *mut T,
+    /// Data was allocated in Rust (by the `pure-rust` mesher) instead of the C library
+    rust_owned: bool,
     _phantom: &'a PhantomData<T>,
 }
 
@@ -146,10 +148,19 @@ impl<'a, T: InnerMesh<'a>> Mesh<'a, T> {
     pub(crate) fn from_raw(mesh: *mut T) -> Result<Self, Error> {
         Ok(Mesh {
             inner: mesh,
+            rust_owned: false,
             _phantom: &PhantomData,
         })
     }
 
+    fn from_box(mesh: Box<T>) -> Self {
+        Mesh {
+            inner: Box::into_raw(mesh),
+            rust_owned: true,
+            _phantom: &PhantomData,
+        }
+    }
+
     /// Get an iterator of mesh vertices
     ///
     /// Produces `(x: f32, y: f32, z: f32)` tuples for 3d mesh and `(x: f32, y: f32)` tuples for 2d mesh
@@ -199,8 +210,120 @@ impl<'a, T: InnerMesh<'a>> Mesh<'a, T> {
     }
 }
 
+impl<'a> Mesh<'a, Mesh2d> {
+    /// Copy the mesh data for transforming it, see [`OwnedMesh`]
+    pub fn to_owned_mesh(&'a self) -> OwnedMesh {
+        OwnedMesh {
+            vertices: self
+                .iter_vertices()
+                .map(|v| {
+                    let (x, y) = v.val();
+                    (x, y, 0.0)
+                })
+                .collect(),
+            faces: self.iter_faces().map(|f| f.val()).collect(),
+            normals: Vec::new(),
+        }
+    }
+
+    /// Check the mesh for problems, see [`OwnedMesh::validate`]. To fix them, repair a copy
+    /// made with `to_owned_mesh`
+    pub fn validate(&'a self) -> MeshReport {
+        self.to_owned_mesh().validate()
+    }
+}
+
+impl<'a> Mesh<'a, Mesh3d> {
+    /// Copy the mesh data for transforming it, see [`OwnedMesh`]
+    pub fn to_owned_mesh(&'a self) -> OwnedMesh {
+        OwnedMesh {
+            vertices: self.iter_vertices().map(|v| v.val()).collect(),
+            faces: self.iter_faces().map(|f| f.val()).collect(),
+            normals: self
+                .iter_normals()
+                .map(|normals| normals.map(|n| n.val()).collect())
+                .unwrap_or_default(),
+        }
+    }
+
+    /// Check the mesh for problems, see [`OwnedMesh::validate`]. To fix them, repair a copy
+    /// made with `to_owned_mesh`
+    pub fn validate(&'a self) -> MeshReport {
+        self.to_owned_mesh().validate()
+    }
+}
+
+impl<'a> Mesh<'a, Mesh2d> {
+    /// Mesh with data generated in Rust
+    pub(crate) fn from_data(vertices: &[(f32, f32)], faces: &[(i32, i32, i32)]) -> Self {
+        let vert = vertices.iter().map(|&(x, y)| Vert2d { x, y }).collect();
+        let faces = faces
+            .iter()
+            .map(|&(v1, v2, v3)| Face2d { v1, v2, v3 })
+            .collect::<Vec<_>>();
+
+        Self::from_box(Box::new(Mesh2d {
+            nvert: vertices.len().try_into().unwrap(),
+            nfaces: faces.len().try_into().unwrap(),
+            vert: into_raw(vert),
+            faces: into_raw(faces),
+            outline: ptr::null_mut(),
+        }))
+    }
+}
+
+impl<'a> Mesh<'a, Mesh3d> {
+    /// Mesh with data generated in Rust
+    pub(crate) fn from_data(
+        vertices: &[(f32, f32, f32)],
+        faces: &[(i32, i32, i32)],
+        normals: &[(f32, f32, f32)],
+    ) -> Self {
+        let vert = vertices
+            .iter()
+            .map(|&(x, y, z)| Vert3d { x, y, z })
+            .collect();
+        let normals = normals
+            .iter()
+            .map(|&(x, y, z)| Normal { x, y, z })
+            .collect();
+        let faces = faces
+            .iter()
+            .map(|&(v1, v2, v3)| Face3d { v1, v2, v3 })
+            .collect::<Vec<_>>();
+
+        Self::from_box(Box::new(Mesh3d {
+            nvert: vertices.len().try_into().unwrap(),
+            nfaces: faces.len().try_into().unwrap(),
+            vert: into_raw(vert),
+            faces: into_raw(faces),
+            normals: into_raw(normals),
+            outline: ptr::null_mut(),
+        }))
+    }
+}
+
+fn into_raw<T>(data: Vec<T>) -> *mut T {
+    Box::into_raw(data.into_boxed_slice()) as *mut T
+}
+
+unsafe fn free_raw<T>(data: *mut T, len: usize) {
+    drop(Box::from_raw(ptr::slice_from_raw_parts_mut(data, len)));
+}
+
 impl<'a, T: InnerMesh<'a>> Drop for Mesh<'a, T> {
     fn drop(&mut self) {
-        unsafe { (&mut *self.inner).free() }
+        if !self.rust_owned {
+            return unsafe { (&mut *self.inner).free() };
+        }
+
+        unsafe {
+            let mesh = Box::from_raw(self.inner);
+            free_raw(mesh.vert_ptr(), mesh.vertices_len());
+            free_raw(mesh.face_ptr(), mesh.faces_len());
+            if let Some(normals) = mesh.normals_ptr() {
+                free_raw(normals, mesh.normals_len());
+            }
+        }
     }
 }
@@ -3,6 +3,8 @@ use std::fmt;
 
 use ttf2mesh_sys as sys;
 
+use crate::FromValue;
+
 /// Value produced by a mesh data iterator. Access with `.val()`
 ///
 /// Values produced by `.val()` depend on the mesh & data type:
@@ -14,6 +16,12 @@ pub trait Value<'a> {
     type Output: fmt::Debug;
 
     fn val(&self) -> Self::Output;
+
+    /// Value as a math library type, e.g. `glam::Vec3` or `nalgebra::Point2<f32>` for vertices
+    /// and normals. See [`crate::Coords3`]
+    fn val_as<T: FromValue<Self::Output>>(&self) -> T {
+        T::from_value(self.val())
+    }
 }
 
 impl<'a> Value<'a> for sys::ttf_mesh__bindgen_ty_1 {
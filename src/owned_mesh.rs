@@ -0,0 +1,326 @@
+//! Meshes owned on the Rust side, for transforming glyph meshes
+use std::collections::HashMap;
+
+use crate::Error;
+#[allow(unused_imports)]
+use crate::Mesh;
+
+/// Limit of subdivision passes, each pass halves the longest edges
+const MAX_SUBDIVISION_PASSES: usize = 8;
+
+/// A 2d or 3d mesh with its data copied from a [`Mesh`], which can be transformed. Created with
+/// `Mesh::to_owned_mesh`
+///
+/// Values have the same layout as the ones produced by [`Mesh`] iterators, with `z = 0` for
+/// 2d meshes
+///
+/// With the `serde` feature, vertices, faces and normals are serialized as flat arrays of
+/// numbers (`[x0, y0, z0, x1, ...]`)
+#[derive(Debug, Clone, PartialEq, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct OwnedMesh {
+    /// `(x, y, z)`
+    #[cfg_attr(feature = "serde", serde(with = "flat"))]
+    pub vertices: Vec<(f32, f32, f32)>,
+    /// Vertex indices of triangles
+    #[cfg_attr(feature = "serde", serde(with = "flat"))]
+    pub faces: Vec<(i32, i32, i32)>,
+    /// A normal for each vertex of a 3d mesh. Empty for 2d meshes
+    #[cfg_attr(feature = "serde", serde(with = "flat"))]
+    pub normals: Vec<(f32, f32, f32)>,
+}
+
+impl OwnedMesh {
+    /// Whether the mesh has normals, i.e. it was generated with `Glyph::to_3d_mesh`
+    pub fn is_3d(&self) -> bool {
+        !self.normals.is_empty()
+    }
+
+    /// Split triangles until no edge is longer than `max_edge` in the xy plane (edges are halved
+    /// at most 8 times). Shared edges are split the same way in both triangles, so the mesh stays
+    /// free of cracks when deformed
+    ///
+    /// Fails with [`Error::InvalidMesh`] for meshes with out of range face indices or a normal
+    /// count different from the vertex count, see [`OwnedMesh::validate`]
+    pub fn subdivide(&self, max_edge: f32) -> Result<OwnedMesh, Error> {
+        if max_edge.is_nan() || max_edge <= 0.0 {
+            return Ok(self.clone());
+        }
+
+        self.subdivide_by(|a, b| ((b.0 - a.0).powi(2) + (b.1 - a.1).powi(2)).sqrt() > max_edge)
+    }
+
+    /// Get the count of vertices
+    pub fn vertices_len(&self) -> usize {
+        self.vertices.len()
+    }
+
+    /// Get the count of faces (indices)
+    pub fn faces_len(&self) -> usize {
+        self.faces.len()
+    }
+
+    /// Move the mesh
+    pub fn translate(&self, x: f32, y: f32, z: f32) -> OwnedMesh {
+        self.map(|v| (v.0 + x, v.1 + y, v.2 + z), |_, n| n)
+    }
+
+    /// Split triangles until `split` returns `false` for all edges (given the edge vertices), at
+    /// most 8 passes. Fails for meshes whose faces or normals do not match the vertices
+    pub(crate) fn subdivide_by<F>(&self, split: F) -> Result<OwnedMesh, Error>
+    where
+        F: Fn((f32, f32, f32), (f32, f32, f32)) -> bool,
+    {
+        let indexable = (self.normals.is_empty() || self.normals.len() == self.vertices.len())
+            && self.faces.iter().all(|face| self.in_bounds(*face));
+        if !indexable {
+            return Err(Error::InvalidMesh);
+        }
+
+        let mut mesh = self.clone();
+
+        for _ in 0..MAX_SUBDIVISION_PASSES {
+            if !mesh.subdivide_pass(&split) {
+                break;
+            }
+        }
+
+        Ok(mesh)
+    }
+
+    /// Map each vertex `(x, y, z)` to a new position and each normal with the vertex it belongs to.
+    /// Normals are normalized afterwards
+    pub(crate) fn map<F, N>(&self, mut vertex: F, mut normal: N) -> OwnedMesh
+    where
+        F: FnMut((f32, f32, f32)) -> (f32, f32, f32),
+        N: FnMut((f32, f32, f32), (f32, f32, f32)) -> (f32, f32, f32),
+    {
+        let normals = self
+            .normals
+            .iter()
+            .zip(&self.vertices)
+            .map(|(n, v)| normalize(normal(*v, *n)))
+            .collect();
+
+        OwnedMesh {
+            vertices: self.vertices.iter().map(|v| vertex(*v)).collect(),
+            faces: self.faces.clone(),
+            normals,
+        }
+    }
+
+    /// Split edges at their midpoint. Returns `false` when there were none to split
+    fn subdivide_pass<F>(&mut self, split: &F) -> bool
+    where
+        F: Fn((f32, f32, f32), (f32, f32, f32)) -> bool,
+    {
+        let mut midpoints = HashMap::new();
+        let mut faces = Vec::with_capacity(self.faces.len());
+
+        for (a, b, c) in std::mem::take(&mut self.faces) {
+            let verts = [a, b, c];
+            let mut mids = [None; 3];
+
+            for i in 0..3 {
+                let (from, to) = (verts[i], verts[(i + 1) % 3]);
+                if split(self.vertices[from as usize], self.vertices[to as usize]) {
+                    mids[i] = Some(self.midpoint(&mut midpoints, from, to));
+                }
+            }
+
+            split_face(verts, mids, &mut faces);
+        }
+
+        self.faces = faces;
+        !midpoints.is_empty()
+    }
+
+    /// Vertex in the middle of an edge, shared by both faces of the edge
+    fn midpoint(&mut self, midpoints: &mut HashMap<(i32, i32), i32>, a: i32, b: i32) -> i32 {
+        let key = (a.min(b), a.max(b));
+        if let Some(index) = midpoints.get(&key) {
+            return *index;
+        }
+
+        let index = self.vertices.len() as i32;
+        let (va, vb) = (self.vertices[a as usize], self.vertices[b as usize]);
+        self.vertices.push(lerp(va, vb));
+
+        if self.is_3d() {
+            let (na, nb) = (self.normals[a as usize], self.normals[b as usize]);
+            self.normals.push(normalize(lerp(na, nb)));
+        }
+
+        midpoints.insert(key, index);
+        index
+    }
+}
+
+/// Triangles of a face whose edges `i` (from vertex `i` to `i + 1`) are split at `mids[i]`.
+/// Winding is preserved
+fn split_face(verts: [i32; 3], mids: [Option<i32>; 3], faces: &mut Vec<(i32, i32, i32)>) {
+    let split = mids.iter().filter(|mid| mid.is_some()).count();
+
+    // rotate so that the split edges come first
+    let rotation = match split {
+        1 => mids.iter().position(|mid| mid.is_some()).unwrap(),
+        2 => (mids.iter().position(|mid| mid.is_none()).unwrap() + 1) % 3,
+        _ => 0,
+    };
+    let v = |i: usize| verts[(i + rotation) % 3];
+    let m = |i: usize| mids[(i + rotation) % 3].unwrap();
+
+    match split {
+        0 => faces.push((v(0), v(1), v(2))),
+        1 => {
+            faces.push((v(0), m(0), v(2)));
+            faces.push((m(0), v(1), v(2)));
+        }
+        2 => {
+            faces.push((m(0), v(1), m(1)));
+            faces.push((v(0), m(0), m(1)));
+            faces.push((v(0), m(1), v(2)));
+        }
+        _ => {
+            faces.push((v(0), m(0), m(2)));
+            faces.push((m(0), v(1), m(1)));
+            faces.push((m(2), m(1), v(2)));
+            faces.push((m(0), m(1), m(2)));
+        }
+    }
+}
+
+fn lerp(a: (f32, f32, f32), b: (f32, f32, f32)) -> (f32, f32, f32) {
+    ((a.0 + b.0) / 2.0, (a.1 + b.1) / 2.0, (a.2 + b.2) / 2.0)
+}
+
+pub(crate) fn normalize(v: (f32, f32, f32)) -> (f32, f32, f32) {
+    let length = (v.0 * v.0 + v.1 * v.1 + v.2 * v.2).sqrt();
+    if length > 0.0 {
+        (v.0 / length, v.1 / length, v.2 / length)
+    } else {
+        v
+    }
+}
+
+/// (De)serialization of `(a, b, c)` tuples as a flat sequence, which needs no per-tuple framing
+#[cfg(feature = "serde")]
+mod flat {
+    use serde::{de::Error, ser::SerializeSeq, Deserialize, Deserializer, Serialize, Serializer};
+
+    pub fn serialize<T, S>(values: &[(T, T, T)], serializer: S) -> Result<S::Ok, S::Error>
+    where
+        T: Serialize,
+        S: Serializer,
+    {
+        let mut seq = serializer.serialize_seq(Some(values.len() * 3))?;
+        for (a, b, c) in values {
+            seq.serialize_element(a)?;
+            seq.serialize_element(b)?;
+            seq.serialize_element(c)?;
+        }
+        seq.end()
+    }
+
+    pub fn deserialize<'de, T, D>(deserializer: D) -> Result<Vec<(T, T, T)>, D::Error>
+    where
+        T: Deserialize<'de> + Copy,
+        D: Deserializer<'de>,
+    {
+        let values = Vec::<T>::deserialize(deserializer)?;
+        if values.len() % 3 != 0 {
+            return Err(D::Error::invalid_length(values.len(), &"a multiple of 3"));
+        }
+
+        Ok(values.chunks(3).map(|v| (v[0], v[1], v[2])).collect())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Signed doubled area of a face in the xy plane
+    fn area(mesh: &OwnedMesh, face: (i32, i32, i32)) -> f32 {
+        let p = |i: i32| mesh.vertices[i as usize];
+        let (a, b, c) = (p(face.0), p(face.1), p(face.2));
+        (b.0 - a.0) * (c.1 - a.1) - (c.0 - a.0) * (b.1 - a.1)
+    }
+
+    fn edge_length(mesh: &OwnedMesh, a: i32, b: i32) -> f32 {
+        let (a, b) = (mesh.vertices[a as usize], mesh.vertices[b as usize]);
+        ((b.0 - a.0).powi(2) + (b.1 - a.1).powi(2)).sqrt()
+    }
+
+    #[test]
+    fn test_subdivide() {
+        // a long thin quad of two triangles
+        let mesh = OwnedMesh {
+            vertices: vec![
+                (0.0, 0.0, 0.0),
+                (1.0, 0.0, 0.0),
+                (1.0, 0.1, 0.0),
+                (0.0, 0.1, 0.0),
+            ],
+            faces: vec![(0, 1, 2), (0, 2, 3)],
+            normals: Vec::new(),
+        };
+
+        let subdivided = mesh.subdivide(0.2).unwrap();
+
+        let total = |mesh: &OwnedMesh| mesh.faces.iter().map(|f| area(mesh, *f)).sum::<f32>();
+        assert!((total(&subdivided) - total(&mesh)).abs() < 1e-5);
+        assert!(subdivided.faces.iter().all(|f| area(&subdivided, *f) > 0.0));
+
+        for &(a, b, c) in &subdivided.faces {
+            for &(from, to) in &[(a, b), (b, c), (c, a)] {
+                assert!(edge_length(&subdivided, from, to) <= 0.2);
+            }
+        }
+
+        // every edge is shared by two faces or lies on the boundary of the quad
+        let mut edges = HashMap::new();
+        for &(a, b, c) in &subdivided.faces {
+            for &(from, to) in &[(a, b), (b, c), (c, a)] {
+                *edges.entry((from.min(to), from.max(to))).or_insert(0) += 1;
+            }
+        }
+        for ((from, to), count) in edges {
+            let (p, q) = (
+                subdivided.vertices[from as usize],
+                subdivided.vertices[to as usize],
+            );
+            let boundary = (p.0 == q.0 && (p.0 == 0.0 || p.0 == 1.0))
+                || (p.1 == q.1 && (p.1 == 0.0 || p.1 == 0.1));
+            assert_eq!(count, if boundary { 1 } else { 2 });
+        }
+
+        let mut broken = mesh.clone();
+        broken.faces.push((2, 3, 4));
+        assert!(matches!(broken.subdivide(0.2), Err(Error::InvalidMesh)));
+
+        let mut broken = mesh;
+        broken.normals = vec![(0.0, 0.0, 1.0)];
+        assert!(matches!(broken.subdivide(0.2), Err(Error::InvalidMesh)));
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn test_serde() {
+        let mesh = OwnedMesh {
+            vertices: vec![(0.0, 0.0, 0.0), (1.0, 0.0, 0.0), (0.0, 1.0, 0.0)],
+            faces: vec![(0, 1, 2)],
+            normals: Vec::new(),
+        };
+
+        let data = bincode::serialize(&mesh).unwrap();
+        // three lengths and the values
+        assert_eq!(data.len(), 3 * 8 + (9 + 3) * 4);
+        assert_eq!(&data[..8], &9u64.to_le_bytes());
+        assert_eq!(bincode::deserialize::<OwnedMesh>(&data).unwrap(), mesh);
+
+        // a vertex cut short
+        let data = bincode::serialize(&(vec![0.0f32; 4], vec![0i32; 0], vec![0.0f32; 0])).unwrap();
+        assert!(bincode::deserialize::<OwnedMesh>(&data).is_err());
+    }
+}
@@ -0,0 +1,315 @@
+//! Text along a path: polylines and cubic Bézier curves
+use std::cmp::Ordering;
+
+use crate::{Coords2, Error, OwnedMesh, PositionedGlyph, TTFFileRef};
+
+/// Line segments a cubic Bézier segment is flattened to
+const CUBIC_SEGMENTS: usize = 64;
+
+/// Longest mesh edge (in em units) when bending glyph meshes, so that triangles follow the curve
+const BEND_MAX_EDGE: f32 = 0.05;
+
+type Point = (f32, f32);
+
+/// A path to lay out text along, in em units (same as [`TTFFileRef::layout`] positions)
+///
+/// Usage:
+/// ```rust
+/// # use ttf2mesh::{TTFFile, TextPath, Quality};
+/// let mut ttf = TTFFile::from_file("./fonts/FiraMono-Medium.ttf").unwrap();
+///
+/// // an arch over the text
+/// let path = TextPath::cubic_bezier((0.0, 0.0), &[((1.0, 2.0), (3.0, 2.0), (4.0, 0.0))]).unwrap();
+///
+/// for placed in ttf.layout_on_path("Hello", &path) {
+///     let mut glyph = ttf.glyph_by_index(placed.layout.glyph_index).unwrap();
+///     let mesh = glyph.to_3d_mesh(Quality::Medium, 0.2).unwrap().to_owned_mesh();
+///
+///     // rotated and translated onto the path
+///     let rigid = placed.transform_mesh(&mesh);
+///     // or with the glyph vertices following the curve
+///     let bent = path.bend_mesh(&placed, &mesh).unwrap();
+/// }
+/// ```
+#[derive(Debug, Clone, PartialEq)]
+pub struct TextPath {
+    points: Vec<Point>,
+    /// Arc length from the start of the path to each point
+    distances: Vec<f32>,
+}
+
+impl TextPath {
+    /// Path through a list of points, as `(x, y)` or math library types (see [`Coords2`]).
+    /// `None` if there are less than two distinct points
+    pub fn polyline<P: Coords2>(points: &[P]) -> Option<TextPath> {
+        let mut path = TextPath {
+            points: Vec::with_capacity(points.len()),
+            distances: Vec::with_capacity(points.len()),
+        };
+
+        for point in points {
+            path.push(point.xy());
+        }
+
+        if path.points.len() < 2 {
+            return None;
+        }
+
+        Some(path)
+    }
+
+    /// Path of cubic Bézier segments. Each segment is given as `(control1, control2, end)`, and
+    /// starts where the previous one ended. `None` if the path has no length
+    pub fn cubic_bezier<P: Coords2>(start: P, segments: &[(P, P, P)]) -> Option<TextPath> {
+        let mut p0 = start.xy();
+        let mut points = vec![p0];
+
+        for &(p1, p2, p3) in segments {
+            let (p1, p2, p3) = (p1.xy(), p2.xy(), p3.xy());
+            for step in 1..=CUBIC_SEGMENTS {
+                let t = step as f32 / CUBIC_SEGMENTS as f32;
+                let u = 1.0 - t;
+
+                let (a, b, c, d) = (u * u * u, 3.0 * u * u * t, 3.0 * u * t * t, t * t * t);
+                points.push((
+                    a * p0.0 + b * p1.0 + c * p2.0 + d * p3.0,
+                    a * p0.1 + b * p1.1 + c * p2.1 + d * p3.1,
+                ));
+            }
+            p0 = p3;
+        }
+
+        Self::polyline(&points)
+    }
+
+    /// Arc length of the path
+    pub fn length(&self) -> f32 {
+        self.distances[self.distances.len() - 1]
+    }
+
+    /// Point and unit tangent at a distance along the path. Beyond its ends, the path continues
+    /// straight in the direction of the end segments
+    pub fn sample(&self, distance: f32) -> (Point, Point) {
+        let segment = self.segment(distance);
+        let (from, to) = (self.points[segment], self.points[segment + 1]);
+        let length = self.distances[segment + 1] - self.distances[segment];
+
+        let tangent = ((to.0 - from.0) / length, (to.1 - from.1) / length);
+        let along = distance - self.distances[segment];
+
+        (
+            (from.0 + tangent.0 * along, from.1 + tangent.1 * along),
+            tangent,
+        )
+    }
+
+    /// Map a point of straight laid out text onto the path: `x` is the distance along the path,
+    /// `y` the distance from it (to the left of the path direction)
+    pub fn map_point(&self, x: f32, y: f32) -> Point {
+        let (point, tangent) = self.sample(x);
+
+        (point.0 - tangent.1 * y, point.1 + tangent.0 * y)
+    }
+
+    /// Bend a glyph mesh (2d or 3d) along the path. Unlike [`PathGlyph::transform_mesh`], each
+    /// vertex follows the curve; the mesh is subdivided first so that long triangles bend
+    /// smoothly. Normals are rotated with the path direction, `z` is kept as is. Fails for
+    /// invalid meshes, as [`OwnedMesh::subdivide`]
+    pub fn bend_mesh(&self, glyph: &PathGlyph, mesh: &OwnedMesh) -> Result<OwnedMesh, Error> {
+        let (x, y) = (glyph.layout.x, glyph.layout.y);
+
+        Ok(mesh.subdivide(BEND_MAX_EDGE)?.map(
+            |v| {
+                let (px, py) = self.map_point(x + v.0, y + v.1);
+                (px, py, v.2)
+            },
+            |v, n| {
+                let (_, (cos, sin)) = self.sample(x + v.0);
+                (n.0 * cos - n.1 * sin, n.0 * sin + n.1 * cos, n.2)
+            },
+        ))
+    }
+
+    fn push(&mut self, point: Point) {
+        match self.points.last() {
+            Some(last) => {
+                let length = ((point.0 - last.0).powi(2) + (point.1 - last.1).powi(2)).sqrt();
+                if length.is_nan() || length <= f32::EPSILON {
+                    return;
+                }
+
+                let distance = self.length() + length;
+                self.distances.push(distance);
+            }
+            None => self.distances.push(0.0),
+        }
+
+        self.points.push(point);
+    }
+
+    /// Index of the segment containing a distance, the first or last one beyond the ends
+    fn segment(&self, distance: f32) -> usize {
+        let index = self
+            .distances
+            .binary_search_by(|d| d.partial_cmp(&distance).unwrap_or(Ordering::Less))
+            .unwrap_or_else(|index| index.saturating_sub(1));
+
+        index.min(self.points.len() - 2)
+    }
+}
+
+/// A glyph placed along a [`TextPath`] by [`TTFFileRef::layout_on_path`]
+#[derive(Debug, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct PathGlyph {
+    /// Placement of the glyph in straight text: `layout.x` is the distance along the path and
+    /// `layout.y` the distance from it
+    pub layout: PositionedGlyph,
+    /// Position of the glyph origin, in em units
+    pub x: f32,
+    pub y: f32,
+    /// Counter-clockwise rotation of the glyph around its origin, in radians
+    pub angle: f32,
+}
+
+impl PathGlyph {
+    /// Position of the glyph origin as `(x, y)` or a math library type, see [`Coords2`]
+    pub fn origin<P: Coords2>(&self) -> P {
+        P::from_xy(self.x, self.y)
+    }
+
+    /// Rotate and translate a glyph mesh (2d or 3d) to its place on the path. Normals are rotated
+    /// with the mesh
+    pub fn transform_mesh(&self, mesh: &OwnedMesh) -> OwnedMesh {
+        let (sin, cos) = self.angle.sin_cos();
+
+        mesh.map(
+            |v| {
+                (
+                    self.x + v.0 * cos - v.1 * sin,
+                    self.y + v.0 * sin + v.1 * cos,
+                    v.2,
+                )
+            },
+            |_, n| (n.0 * cos - n.1 * sin, n.0 * sin + n.1 * cos, n.2),
+        )
+    }
+}
+
+impl TTFFileRef<'_> {
+    /// Place the glyphs of a text along a path. The text is laid out with [`TTFFileRef::layout`]
+    /// first, then each glyph is rotated to the path direction at its horizontal center. Further
+    /// lines run parallel to the path
+    pub fn layout_on_path(&mut self, text: &str, path: &TextPath) -> Vec<PathGlyph> {
+        self.layout(text)
+            .into_iter()
+            .map(|layout| {
+                let advance = self
+                    .glyph_by_index(layout.glyph_index)
+                    .map(|glyph| glyph.advance())
+                    .unwrap_or(0.0);
+                let half = advance / 2.0;
+
+                let center = path.map_point(layout.x + half, layout.y);
+                let (_, (cos, sin)) = path.sample(layout.x + half);
+
+                PathGlyph {
+                    layout,
+                    x: center.0 - cos * half,
+                    y: center.1 - sin * half,
+                    angle: sin.atan2(cos),
+                }
+            })
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn assert_near(a: Point, b: Point) {
+        assert!(
+            (a.0 - b.0).abs() < 1e-4 && (a.1 - b.1).abs() < 1e-4,
+            "{:?} != {:?}",
+            a,
+            b
+        );
+    }
+
+    #[test]
+    fn test_polyline() {
+        // right, then up
+        let path = TextPath::polyline(&[(0.0, 0.0), (2.0, 0.0), (2.0, 0.0), (2.0, 1.0)]).unwrap();
+        assert_eq!(path.length(), 3.0);
+
+        assert_near(path.sample(1.0).0, (1.0, 0.0));
+        assert_near(path.sample(2.5).0, (2.0, 0.5));
+        assert_near(path.sample(2.5).1, (0.0, 1.0));
+
+        // extended past the ends
+        assert_near(path.sample(-1.0).0, (-1.0, 0.0));
+        assert_near(path.sample(4.0).0, (2.0, 2.0));
+
+        // left of the path direction
+        assert_near(path.map_point(1.0, 0.5), (1.0, 0.5));
+        assert_near(path.map_point(2.5, 0.5), (1.5, 0.5));
+
+        assert!(TextPath::polyline(&[(1.0, 1.0), (1.0, 1.0)]).is_none());
+    }
+
+    #[test]
+    fn test_cubic_bezier() {
+        // a straight line with evenly spaced control points
+        let line =
+            TextPath::cubic_bezier((0.0, 0.0), &[((1.0, 0.0), (2.0, 0.0), (3.0, 0.0))]).unwrap();
+        assert!((line.length() - 3.0).abs() < 1e-4);
+
+        // quarter circle approximation of radius 1
+        let k = 0.552_284_8;
+        let arc = TextPath::cubic_bezier((1.0, 0.0), &[((1.0, k), (k, 1.0), (0.0, 1.0))]).unwrap();
+        assert!((arc.length() - std::f32::consts::FRAC_PI_2).abs() < 1e-3);
+        assert_near(arc.sample(arc.length()).1, (-1.0, 0.0));
+    }
+
+    #[test]
+    fn test_transform_and_bend() {
+        let path = TextPath::polyline(&[(0.0, 0.0), (0.0, 2.0)]).unwrap();
+        let mesh = OwnedMesh {
+            vertices: vec![(0.0, 0.0, 0.0), (1.0, 0.0, 0.5), (0.0, 0.2, 0.0)],
+            faces: vec![(0, 1, 2)],
+            normals: vec![(1.0, 0.0, 0.0), (0.0, 1.0, 0.0), (0.0, 0.0, 1.0)],
+        };
+
+        let glyph = PathGlyph {
+            layout: PositionedGlyph {
+                char_index: 0,
+                glyph_index: 0,
+                x: 0.5,
+                y: 0.0,
+                rotated: false,
+            },
+            x: 0.0,
+            y: 0.5,
+            angle: std::f32::consts::FRAC_PI_2,
+        };
+
+        // path goes up, glyphs are rotated a quarter turn
+        let rigid = glyph.transform_mesh(&mesh);
+        assert_near((rigid.vertices[1].0, rigid.vertices[1].1), (0.0, 1.5));
+        assert_eq!(rigid.vertices[1].2, 0.5);
+        assert_near((rigid.normals[0].0, rigid.normals[0].1), (0.0, 1.0));
+
+        // a straight path bends like the rigid transform. Subdivision appends vertices
+        let bent = path.bend_mesh(&glyph, &mesh).unwrap();
+        assert!(bent.faces.len() > mesh.faces.len());
+        for i in 0..3 {
+            let (v, w) = (bent.vertices[i], rigid.vertices[i]);
+            assert_near((v.0, v.1), (w.0, w.1));
+            assert_near(
+                (bent.normals[i].0, bent.normals[i].1),
+                (rigid.normals[i].0, rigid.normals[i].1),
+            );
+        }
+    }
+}
@@ -1,7 +1,30 @@
+use std::f32::consts::PI;
+
 use crate::Error;
 
+#[allow(unused_imports)]
+use crate::Glyph;
+
+/// Radius (in em units) of the curves assumed by [`Quality::estimate_for_tolerance`]: a circle
+/// filling the em square, which bounds the curves of typical glyphs
+const MODEL_RADIUS: f32 = 0.5;
+
+/// Largest deviation of curves in pixels for [`Quality::estimate_for_pixel_size`] and
+/// [`Glyph::quality_for_pixel_size`]
+pub(crate) const MAX_PIXEL_ERROR: f32 = 0.25;
+
+/// Quality values accepted by ttf2mesh
+const MIN_QUALITY: f32 = 8.0;
+const MAX_QUALITY: f32 = 128.0;
+
+/// Points sampled on each curve when measuring the flattening error
+const ERROR_SAMPLES: usize = 16;
+
+type Point = (f32, f32);
+
 /// Quality of the output mesh. Higher quality produces more vertices and takes longer
 #[derive(Debug, PartialEq, Copy, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum Quality {
     /// 10
     Low,
@@ -33,12 +56,48 @@ impl Quality {
             }
         };
 
-        Ok(match val {
+        Ok(Self::from_u8(val))
+    }
+
+    /// Estimate the lowest quality at which curves deviate at most `tolerance` (in em units)
+    /// from the exact glyph outline, without a glyph at hand
+    ///
+    /// ttf2mesh splits curves into about `quality` line segments per full turn. The deviation
+    /// of such segments from a circular arc of radius `r` is `r * (1 - cos(PI / quality))`, and
+    /// curves are assumed to be no flatter than a circle filling the em square. The error is not
+    /// measured: use [`Glyph::quality_for_tolerance`] for the quality measured on a glyph
+    ///
+    /// Usage:
+    /// ```rust
+    /// # use ttf2mesh::Quality;
+    /// assert_eq!(Quality::estimate_for_tolerance(0.001), Quality::High);
+    /// ```
+    pub fn estimate_for_tolerance(tolerance: f32) -> Self {
+        let steps = if tolerance >= MODEL_RADIUS {
+            MIN_QUALITY
+        } else if tolerance > 0.0 {
+            PI / (1.0 - tolerance / MODEL_RADIUS).acos()
+        } else {
+            MAX_QUALITY
+        };
+
+        Self::from_u8(steps.ceil().max(MIN_QUALITY).min(MAX_QUALITY) as u8)
+    }
+
+    /// Estimate the quality for text rendered at a font size of `pixels_per_em` pixels, with
+    /// curves deviating at most a quarter of a pixel. See [`Quality::estimate_for_tolerance`] and
+    /// [`Glyph::quality_for_pixel_size`]
+    pub fn estimate_for_pixel_size(pixels_per_em: f32) -> Self {
+        Self::estimate_for_tolerance(MAX_PIXEL_ERROR / pixels_per_em)
+    }
+
+    fn from_u8(value: u8) -> Self {
+        match value {
             10 => Quality::Low,
             20 => Quality::Medium,
             50 => Quality::High,
-            _ => Quality::Custom(val),
-        })
+            _ => Quality::Custom(value),
+        }
     }
 
     pub(crate) fn as_u8(&self) -> u8 {
@@ -51,6 +110,130 @@ impl Quality {
     }
 }
 
+/// Lowest quality which meets a condition, bisecting the qualities accepted by ttf2mesh. The
+/// condition must hold for all qualities above the ones it holds for (as the flattening error
+/// shrinks with the quality); the highest quality when it never holds
+pub(crate) fn lowest_quality<F: Fn(Quality) -> bool>(meets: F) -> Quality {
+    let (mut low, mut high) = (MIN_QUALITY as u8, MAX_QUALITY as u8);
+
+    while low < high {
+        let middle = low + (high - low) / 2;
+        if meets(Quality::from_u8(middle)) {
+            high = middle;
+        } else {
+            low = middle + 1;
+        }
+    }
+
+    Quality::from_u8(low)
+}
+
+/// Largest distance from the quadratic curves of a contour to the nearest of the polylines.
+/// Contour points are `(x, y, on_curve)`, polylines are closed
+pub(crate) fn flattening_error(contour: &[(f32, f32, bool)], polylines: &[Vec<Point>]) -> f32 {
+    let mut error: f32 = 0.0;
+
+    for (p0, p1, p2) in quadratic_curves(contour) {
+        for step in 1..ERROR_SAMPLES {
+            let t = step as f32 / ERROR_SAMPLES as f32;
+            let u = 1.0 - t;
+            let point = (
+                u * u * p0.0 + 2.0 * u * t * p1.0 + t * t * p2.0,
+                u * u * p0.1 + 2.0 * u * t * p1.1 + t * t * p2.1,
+            );
+
+            let distance = polylines
+                .iter()
+                .map(|polyline| distance_to_polyline(point, polyline))
+                .fold(f32::INFINITY, f32::min);
+
+            if distance.is_finite() {
+                error = error.max(distance);
+            }
+        }
+    }
+
+    error
+}
+
+/// Quadratic curves `(start, control, end)` of a closed TrueType contour
+fn quadratic_curves(contour: &[(f32, f32, bool)]) -> Vec<(Point, Point, Point)> {
+    contour_segments(contour)
+        .into_iter()
+        .filter_map(|(start, control, end)| control.map(|control| (start, control, end)))
+        .collect()
+}
+
+/// Segments `(start, control, end)` of a closed TrueType contour: quadratic curves, and lines
+/// without a control point. Consecutive off-curve points have an implied on-curve point between
+/// them
+pub(crate) fn contour_segments(contour: &[(f32, f32, bool)]) -> Vec<(Point, Option<Point>, Point)> {
+    let mid = |a: Point, b: Point| ((a.0 + b.0) / 2.0, (a.1 + b.1) / 2.0);
+    let point = |i: usize| {
+        let (x, y, _) = contour[i % contour.len()];
+        (x, y)
+    };
+
+    if contour.len() < 2 {
+        return Vec::new();
+    }
+
+    // start from an on-curve point, or an implied one if there are none
+    let (start, first) = match contour.iter().position(|p| p.2) {
+        Some(index) => (point(index), index + 1),
+        None => (mid(point(contour.len() - 1), point(0)), 0),
+    };
+
+    let mut segments = Vec::new();
+    let mut current = start;
+    let mut control = None;
+
+    for i in first..first + contour.len() {
+        let p = point(i);
+
+        if contour[i % contour.len()].2 {
+            segments.push((current, control.take(), p));
+            current = p;
+        } else {
+            if let Some(c) = control {
+                let implied = mid(c, p);
+                segments.push((current, Some(c), implied));
+                current = implied;
+            }
+            control = Some(p);
+        }
+    }
+
+    if let Some(c) = control {
+        segments.push((current, Some(c), start));
+    }
+
+    segments
+}
+
+fn distance_to_polyline(point: Point, polyline: &[Point]) -> f32 {
+    polyline
+        .iter()
+        .zip(polyline.iter().cycle().skip(1))
+        .map(|(a, b)| distance_to_segment(point, *a, *b))
+        .fold(f32::INFINITY, f32::min)
+}
+
+fn distance_to_segment(p: Point, a: Point, b: Point) -> f32 {
+    let (dx, dy) = (b.0 - a.0, b.1 - a.1);
+    let length = dx * dx + dy * dy;
+
+    let t = if length > 0.0 {
+        (((p.0 - a.0) * dx + (p.1 - a.1) * dy) / length)
+            .max(0.0)
+            .min(1.0)
+    } else {
+        0.0
+    };
+
+    ((a.0 + t * dx - p.0).powi(2) + (a.1 + t * dy - p.1).powi(2)).sqrt()
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -63,4 +246,46 @@ mod tests {
         assert!(Quality::from_str("1000").is_err());
         assert!(Quality::from_str("Medium").is_err());
     }
+
+    #[test]
+    fn test_estimate_for_tolerance() {
+        assert_eq!(Quality::estimate_for_tolerance(0.001), Quality::High);
+        assert_eq!(Quality::estimate_for_tolerance(1.0), Quality::Custom(8));
+        assert_eq!(Quality::estimate_for_tolerance(0.0), Quality::Custom(128));
+
+        let at_48px = Quality::estimate_for_pixel_size(48.0).as_u8();
+        assert!(at_48px > Quality::Medium.as_u8() && at_48px < Quality::High.as_u8());
+        assert!(Quality::estimate_for_pixel_size(12.0).as_u8() < at_48px);
+    }
+
+    #[test]
+    fn test_lowest_quality() {
+        assert_eq!(lowest_quality(|q| q.as_u8() >= 50), Quality::High);
+        assert_eq!(lowest_quality(|q| q.as_u8() >= 33), Quality::Custom(33));
+        assert_eq!(lowest_quality(|_| true), Quality::Custom(8));
+        assert_eq!(lowest_quality(|_| false), Quality::Custom(128));
+    }
+
+    #[test]
+    fn test_flattening_error() {
+        // y = 4t(1 - t) from (0, 0) to (2, 0), peaking at (1, 1)
+        let contour = [(0.0, 0.0, true), (1.0, 2.0, false), (2.0, 0.0, true)];
+
+        let chord = vec![(0.0, 0.0), (2.0, 0.0)];
+        assert!((flattening_error(&contour, &[chord]) - 1.0).abs() < 1e-5);
+
+        // two segments, farthest at t = 0.25 (and 0.75)
+        let halves = vec![(0.0, 0.0), (1.0, 1.0), (2.0, 0.0)];
+        let expected = 0.25 / 2f32.sqrt();
+        assert!((flattening_error(&contour, &[halves]) - expected).abs() < 1e-5);
+
+        // off-curve points only: four curves through implied midpoints
+        let square = [
+            (0.0, 0.0, false),
+            (1.0, 0.0, false),
+            (1.0, 1.0, false),
+            (0.0, 1.0, false),
+        ];
+        assert_eq!(quadratic_curves(&square).len(), 4);
+    }
 }
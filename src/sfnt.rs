@@ -0,0 +1,398 @@
+//! Minimal reader and writer for the sfnt (TrueType / OpenType) font container
+//!
+//! The C library parses only the tables it needs for meshing. Everything else (variations,
+//! collections, ...) is handled on the Rust side by reading tables with [`Reader`] and, when the
+//! C library has to see the result, by re-assembling a font with [`SfntBuilder`].
+use std::{collections::BTreeMap, convert::TryInto};
+
+/// Four-byte table tag, e.g. `*b"glyf"`
+pub(crate) type Tag = [u8; 4];
+
+/// sfnt version for fonts with TrueType outlines
+pub(crate) const VERSION_TRUETYPE: u32 = 0x0001_0000;
+
+/// sfnt version for fonts with CFF outlines
+pub(crate) const VERSION_OPENTYPE: u32 = 0x4F54_544F; // 'OTTO'
+
+/// Legacy Apple sfnt version for TrueType outlines
+const VERSION_APPLE: u32 = 0x7472_7565; // 'true'
+
+/// Tag which starts a font collection
+const TAG_COLLECTION: Tag = *b"ttcf";
+
+/// Bounds-checked big-endian cursor over a byte slice
+#[derive(Clone)]
+pub(crate) struct Reader<'a> {
+    data: &'a [u8],
+    pos: usize,
+}
+
+impl<'a> Reader<'a> {
+    pub(crate) fn new(data: &'a [u8]) -> Self {
+        Self { data, pos: 0 }
+    }
+
+    /// Create a reader positioned at `offset`. Returns `None` if offset is out of bounds
+    pub(crate) fn at(data: &'a [u8], offset: usize) -> Option<Self> {
+        if offset > data.len() {
+            return None;
+        }
+
+        Some(Self { data, pos: offset })
+    }
+
+    pub(crate) fn pos(&self) -> usize {
+        self.pos
+    }
+
+    pub(crate) fn skip(&mut self, len: usize) -> Option<()> {
+        self.read_bytes(len).map(|_| ())
+    }
+
+    pub(crate) fn read_bytes(&mut self, len: usize) -> Option<&'a [u8]> {
+        let end = self.pos.checked_add(len)?;
+        let bytes = self.data.get(self.pos..end)?;
+        self.pos = end;
+
+        Some(bytes)
+    }
+
+    pub(crate) fn read_u8(&mut self) -> Option<u8> {
+        self.read_bytes(1).map(|b| b[0])
+    }
+
+    pub(crate) fn read_i8(&mut self) -> Option<i8> {
+        self.read_u8().map(|v| v as i8)
+    }
+
+    pub(crate) fn read_u16(&mut self) -> Option<u16> {
+        self.read_bytes(2)
+            .map(|b| u16::from_be_bytes(b.try_into().unwrap()))
+    }
+
+    pub(crate) fn read_i16(&mut self) -> Option<i16> {
+        self.read_u16().map(|v| v as i16)
+    }
+
+    /// 24-bit offset of `COLR` version 1 paint tables
+    pub(crate) fn read_u24(&mut self) -> Option<u32> {
+        self.read_bytes(3)
+            .map(|b| u32::from(b[0]) << 16 | u32::from(b[1]) << 8 | u32::from(b[2]))
+    }
+
+    pub(crate) fn read_u32(&mut self) -> Option<u32> {
+        self.read_bytes(4)
+            .map(|b| u32::from_be_bytes(b.try_into().unwrap()))
+    }
+
+    pub(crate) fn read_i32(&mut self) -> Option<i32> {
+        self.read_u32().map(|v| v as i32)
+    }
+
+    pub(crate) fn read_tag(&mut self) -> Option<Tag> {
+        self.read_bytes(4).map(|b| b.try_into().unwrap())
+    }
+
+    /// 16.16 fixed point number
+    pub(crate) fn read_fixed(&mut self) -> Option<f32> {
+        self.read_i32().map(|v| v as f32 / 65536.0)
+    }
+
+    /// 2.14 fixed point number
+    pub(crate) fn read_f2dot14(&mut self) -> Option<f32> {
+        self.read_i16().map(|v| v as f32 / 16384.0)
+    }
+}
+
+#[derive(Debug, Clone, Copy)]
+struct TableRecord {
+    tag: Tag,
+    offset: usize,
+    length: usize,
+}
+
+/// Parsed table directory of a single sfnt font
+pub(crate) struct Sfnt<'a> {
+    data: &'a [u8],
+    version: u32,
+    tables: Vec<TableRecord>,
+}
+
+impl<'a> Sfnt<'a> {
+    /// Parse a font which starts at the beginning of `data`
+    pub(crate) fn parse(data: &'a [u8]) -> Option<Self> {
+        Self::parse_at(data, 0)
+    }
+
+    /// Parse a font whose table directory starts at `offset`. Table offsets are always relative
+    /// to the beginning of `data` (as is the case within font collections)
+    pub(crate) fn parse_at(data: &'a [u8], offset: usize) -> Option<Self> {
+        let mut r = Reader::at(data, offset)?;
+
+        let version = r.read_u32()?;
+        if version != VERSION_TRUETYPE && version != VERSION_OPENTYPE && version != VERSION_APPLE {
+            return None;
+        }
+
+        let num_tables = r.read_u16()?;
+        r.skip(6)?;
+
+        let mut tables = Vec::with_capacity(num_tables as usize);
+        for _ in 0..num_tables {
+            let tag = r.read_tag()?;
+            let _checksum = r.read_u32()?;
+            let offset = r.read_u32()? as usize;
+            let length = r.read_u32()? as usize;
+
+            if offset.checked_add(length)? > data.len() {
+                return None;
+            }
+
+            tables.push(TableRecord {
+                tag,
+                offset,
+                length,
+            });
+        }
+
+        Some(Self {
+            data,
+            version,
+            tables,
+        })
+    }
+
+    pub(crate) fn version(&self) -> u32 {
+        self.version
+    }
+
+    /// Get table contents by tag
+    pub(crate) fn table(&self, tag: &Tag) -> Option<&'a [u8]> {
+        self.tables
+            .iter()
+            .find(|t| &t.tag == tag)
+            .map(|t| &self.data[t.offset..t.offset + t.length])
+    }
+
+    /// Iterate over `(tag, contents)` of all tables
+    pub(crate) fn tables(&self) -> impl Iterator<Item = (Tag, &'a [u8])> + '_ {
+        self.tables
+            .iter()
+            .map(move |t| (t.tag, &self.data[t.offset..t.offset + t.length]))
+    }
+}
+
+/// Offsets of the table directories of each face if `data` is a font collection (`.ttc`)
+pub(crate) fn collection_offsets(data: &[u8]) -> Option<Vec<usize>> {
+    let mut r = Reader::new(data);
+    if r.read_tag()? != TAG_COLLECTION {
+        return None;
+    }
+
+    let _major_version = r.read_u16()?;
+    let _minor_version = r.read_u16()?;
+    let num_fonts = r.read_u32()?;
+
+    (0..num_fonts)
+        .map(|_| r.read_u32().map(|offset| offset as usize))
+        .collect()
+}
+
+/// Join standalone fonts into a font collection. Tables are not shared between faces
+#[cfg(any(feature = "woff", test))]
+pub(crate) fn build_collection(fonts: &[Vec<u8>]) -> Option<Vec<u8>> {
+    let mut out = Vec::new();
+    out.extend_from_slice(&TAG_COLLECTION);
+    out.put_u32(0x0001_0000);
+    out.put_u32(fonts.len() as u32);
+
+    let mut offset = 12 + 4 * fonts.len();
+    for font in fonts {
+        out.put_u32(offset as u32);
+        offset += padded_len(font.len());
+    }
+
+    for font in fonts {
+        let base = out.len();
+        let num_tables = Sfnt::parse(font)?.tables.len();
+
+        out.extend_from_slice(font);
+        out.resize(padded_len(out.len()), 0);
+
+        // table offsets are relative to the beginning of the collection
+        for record in 0..num_tables {
+            let at = base + 12 + 16 * record + 8;
+            let table_offset = Reader::at(&out, at)?.read_u32()? as usize + base;
+            out[at..at + 4].copy_from_slice(&(table_offset as u32).to_be_bytes());
+        }
+    }
+
+    Some(out)
+}
+
+/// Assembles a standalone sfnt font from a set of tables
+///
+/// Table directory, padding and checksums (including `head.checkSumAdjustment`) are generated
+pub(crate) struct SfntBuilder {
+    version: u32,
+    tables: BTreeMap<Tag, Vec<u8>>,
+}
+
+impl SfntBuilder {
+    pub(crate) fn new(version: u32) -> Self {
+        Self {
+            version,
+            tables: BTreeMap::new(),
+        }
+    }
+
+    /// Start from all tables of an existing font
+    pub(crate) fn from_sfnt(sfnt: &Sfnt) -> Self {
+        let mut builder = Self::new(sfnt.version());
+        for (tag, data) in sfnt.tables() {
+            builder.insert(tag, data.to_vec());
+        }
+
+        builder
+    }
+
+    pub(crate) fn set_version(&mut self, version: u32) {
+        self.version = version;
+    }
+
+    pub(crate) fn insert(&mut self, tag: Tag, data: Vec<u8>) {
+        self.tables.insert(tag, data);
+    }
+
+    pub(crate) fn remove(&mut self, tag: &Tag) {
+        self.tables.remove(tag);
+    }
+
+    pub(crate) fn build(mut self) -> Vec<u8> {
+        if let Some(head) = self.tables.get_mut(b"head") {
+            if head.len() >= 12 {
+                head[8..12].copy_from_slice(&[0; 4]);
+            }
+        }
+
+        let num_tables = self.tables.len();
+        let mut entry_selector = 0u16;
+        while (1usize << (entry_selector + 1)) <= num_tables {
+            entry_selector += 1;
+        }
+        let search_range = (1u16 << entry_selector) * 16;
+        let range_shift = (num_tables as u16 * 16).saturating_sub(search_range);
+
+        let mut out = Vec::new();
+        out.extend_from_slice(&self.version.to_be_bytes());
+        out.extend_from_slice(&(num_tables as u16).to_be_bytes());
+        out.extend_from_slice(&search_range.to_be_bytes());
+        out.extend_from_slice(&entry_selector.to_be_bytes());
+        out.extend_from_slice(&range_shift.to_be_bytes());
+
+        let mut offset = 12 + 16 * num_tables;
+        let mut head_offset = None;
+        for (tag, data) in self.tables.iter() {
+            if tag == b"head" {
+                head_offset = Some(offset);
+            }
+
+            out.extend_from_slice(tag);
+            out.extend_from_slice(&checksum(data).to_be_bytes());
+            out.extend_from_slice(&(offset as u32).to_be_bytes());
+            out.extend_from_slice(&(data.len() as u32).to_be_bytes());
+            offset += padded_len(data.len());
+        }
+
+        for data in self.tables.values() {
+            out.extend_from_slice(data);
+            out.resize(padded_len(out.len()), 0);
+        }
+
+        if let Some(head_offset) = head_offset {
+            let adjustment = 0xB1B0_AFBAu32.wrapping_sub(checksum(&out));
+            out[head_offset + 8..head_offset + 12].copy_from_slice(&adjustment.to_be_bytes());
+        }
+
+        out
+    }
+}
+
+fn padded_len(len: usize) -> usize {
+    (len + 3) & !3
+}
+
+/// Table checksum: sum of big-endian u32 words, with the tail zero-padded
+pub(crate) fn checksum(data: &[u8]) -> u32 {
+    data.chunks(4).fold(0u32, |sum, chunk| {
+        let mut word = [0u8; 4];
+        word[..chunk.len()].copy_from_slice(chunk);
+        sum.wrapping_add(u32::from_be_bytes(word))
+    })
+}
+
+/// Big-endian writer helpers for building tables
+pub(crate) trait WriteBe {
+    fn put_u16(&mut self, value: u16);
+    fn put_i16(&mut self, value: i16);
+    fn put_u32(&mut self, value: u32);
+}
+
+impl WriteBe for Vec<u8> {
+    fn put_u16(&mut self, value: u16) {
+        self.extend_from_slice(&value.to_be_bytes());
+    }
+
+    fn put_i16(&mut self, value: i16) {
+        self.extend_from_slice(&value.to_be_bytes());
+    }
+
+    fn put_u32(&mut self, value: u32) {
+        self.extend_from_slice(&value.to_be_bytes());
+    }
+}
+
+/// Overwrite a big-endian u16 at `offset` (table must be long enough)
+pub(crate) fn set_u16(data: &mut [u8], offset: usize, value: u16) {
+    data[offset..offset + 2].copy_from_slice(&value.to_be_bytes());
+}
+
+/// Read a big-endian u16 at `offset`
+pub(crate) fn get_u16(data: &[u8], offset: usize) -> Option<u16> {
+    Reader::at(data, offset)?.read_u16()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tests::read_font;
+
+    #[test]
+    fn test_parse_tables() {
+        let data = read_font(None);
+        let sfnt = Sfnt::parse(&data).unwrap();
+
+        assert_eq!(sfnt.version(), VERSION_TRUETYPE);
+        assert_eq!(sfnt.table(b"head").unwrap().len(), 54);
+        assert!(sfnt.table(b"glyf").is_some());
+        assert!(sfnt.table(b"fvar").is_none());
+    }
+
+    #[test]
+    fn test_builder_roundtrip() {
+        let data = read_font(None);
+        let sfnt = Sfnt::parse(&data).unwrap();
+
+        let rebuilt = SfntBuilder::from_sfnt(&sfnt).build();
+        let parsed = Sfnt::parse(&rebuilt).unwrap();
+
+        for (tag, table) in sfnt.tables() {
+            if &tag != b"head" {
+                assert_eq!(parsed.table(&tag).unwrap(), table);
+            }
+        }
+
+        // whole font checksums to the magic number once head.checkSumAdjustment is set
+        assert_eq!(checksum(&rebuilt), 0xB1B0_AFBA);
+    }
+}
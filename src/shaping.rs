@@ -0,0 +1,102 @@
+//! Complex script shaping with rustybuzz (`shaping` feature)
+use rustybuzz::{ttf_parser::Tag, Direction, UnicodeBuffer, Variation};
+
+use crate::{Error, TTFFileRef};
+
+/// A glyph produced by [`TTFFileRef::shape`]
+#[derive(Debug, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct ShapedGlyph {
+    /// Glyph index, see [`TTFFileRef::glyph_by_index`]
+    pub glyph_index: usize,
+    /// Byte offset of the first character of the cluster this glyph belongs to. A ligature
+    /// covers several characters, and a character may produce several glyphs
+    pub cluster: usize,
+    /// Horizontal advance after drawing the glyph, in em units
+    pub x_advance: f32,
+    /// Vertical advance after drawing the glyph, in em units. Zero for horizontal text
+    pub y_advance: f32,
+    /// Horizontal offset of the glyph from the pen position, in em units
+    pub x_offset: f32,
+    /// Vertical offset of the glyph from the pen position, in em units
+    pub y_offset: f32,
+}
+
+impl TTFFileRef<'_> {
+    /// Shape a single run of text, applying the `GSUB` and `GPOS` features of the font
+    /// (ligatures, contextual forms, mark positioning, kerning)
+    ///
+    /// Script, direction and language are guessed from the text. Glyphs of right-to-left runs
+    /// are returned in visual order, so the pen always moves by `x_advance` to the right. Text
+    /// mixing directions has to be split into runs first, as [`TTFFileRef::layout`] does with the
+    /// `bidi` feature. The variation selected with [`TTFFileRef::set_variation`] is applied
+    ///
+    /// Usage:
+    /// ```rust
+    /// # use ttf2mesh::{TTFFile, Quality};
+    /// let mut ttf = TTFFile::from_file("./fonts/FiraMono-Medium.ttf").unwrap();
+    ///
+    /// let mut x = 0.0;
+    /// for shaped in ttf.shape("fi").unwrap() {
+    ///     let mut glyph = ttf.glyph_by_index(shaped.glyph_index).unwrap();
+    ///     let mesh = glyph.to_2d_mesh(Quality::Medium).unwrap();
+    ///
+    ///     // translate the mesh by (x + shaped.x_offset, shaped.y_offset)
+    ///     x += shaped.x_advance;
+    /// }
+    /// ```
+    pub fn shape(&self, text: &str) -> Result<Vec<ShapedGlyph>, Error> {
+        self.shape_run(text, None)
+    }
+
+    /// Shape a run of text in a known direction (`Some(true)` for right-to-left), or in the
+    /// direction guessed from its script
+    pub(crate) fn shape_run(
+        &self,
+        text: &str,
+        rtl: Option<bool>,
+    ) -> Result<Vec<ShapedGlyph>, Error> {
+        let mut face = rustybuzz::Face::from_slice(self.data(), 0).ok_or(Error::ShapingError)?;
+
+        let variations = self
+            .variation()
+            .iter()
+            .map(|(tag, value)| Variation {
+                tag: Tag::from_bytes_lossy(tag.as_bytes()),
+                value: *value,
+            })
+            .collect::<Vec<_>>();
+        face.set_variations(&variations);
+
+        let units_per_em = face.units_per_em() as f32;
+        if units_per_em <= 0.0 {
+            return Err(Error::ShapingError);
+        }
+
+        let mut buffer = UnicodeBuffer::new();
+        buffer.push_str(text);
+        match rtl {
+            Some(true) => buffer.set_direction(Direction::RightToLeft),
+            Some(false) => buffer.set_direction(Direction::LeftToRight),
+            None => (),
+        }
+        buffer.guess_segment_properties();
+
+        let output = rustybuzz::shape(&face, &[], buffer);
+        let scale = |value: i32| value as f32 / units_per_em;
+
+        Ok(output
+            .glyph_infos()
+            .iter()
+            .zip(output.glyph_positions())
+            .map(|(info, position)| ShapedGlyph {
+                glyph_index: info.glyph_id as usize,
+                cluster: info.cluster as usize,
+                x_advance: scale(position.x_advance),
+                y_advance: scale(position.y_advance),
+                x_offset: scale(position.x_offset),
+                y_offset: scale(position.y_offset),
+            })
+            .collect())
+    }
+}
@@ -0,0 +1,87 @@
+//! OpenType feature substitutions (`GSUB`) without shaping: ligatures, alternates and stylistic
+//! forms
+use std::convert::TryInto;
+
+use crate::{
+    sfnt::{Sfnt, Tag},
+    tables::gsub::Gsub,
+    TTFFileRef,
+};
+
+/// A glyph produced by [`TTFFileRef::substitute`]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct SubstitutedGlyph {
+    /// Glyph index, see [`TTFFileRef::glyph_by_index`]
+    pub glyph_index: usize,
+    /// Index of the character (not byte) within the text. A ligature has the index of its first
+    /// character
+    pub char_index: usize,
+}
+
+impl TTFFileRef<'_> {
+    /// Tags of the `GSUB` features of the font which [`TTFFileRef::substitute`] can enable, e.g.
+    /// `liga`, `smcp` or `ss01`
+    pub fn gsub_features(&self) -> Vec<String> {
+        match self.gsub() {
+            Some(gsub) => gsub
+                .features()
+                .iter()
+                .map(|tag| String::from_utf8_lossy(tag).into_owned())
+                .collect(),
+            None => Vec::new(),
+        }
+    }
+
+    /// Map the characters of a text to glyphs, applying the single, alternate and ligature
+    /// substitutions of the enabled `GSUB` features, e.g. `liga`, `dlig`, `smcp`, `onum`,
+    /// `ss01` to `ss20` or `zero`. Alternate substitutions pick the first alternate
+    ///
+    /// Unlike [`TTFFileRef::shape`] this needs no `shaping` feature, and only features of the
+    /// default language (of the `DFLT` or `latn` script) are used. Contextual substitutions (e.g.
+    /// `calt`) are not applied. Characters which the font does not map use the `.notdef` glyph,
+    /// and unknown features are ignored
+    ///
+    /// Usage:
+    /// ```rust
+    /// # use ttf2mesh::{TTFFile, Quality};
+    /// let mut ttf = TTFFile::from_file("./fonts/FiraMono-Medium.ttf").unwrap();
+    ///
+    /// for substituted in ttf.substitute("0", &["zero"]) {
+    ///     let mut glyph = ttf.glyph_by_index(substituted.glyph_index).unwrap();
+    ///     assert_eq!(glyph.name().unwrap(), "zero.zero");
+    ///
+    ///     let mesh = glyph.to_2d_mesh(Quality::Medium).unwrap();
+    /// }
+    /// ```
+    pub fn substitute(&self, text: &str, features: &[&str]) -> Vec<SubstitutedGlyph> {
+        let mut glyphs = text
+            .chars()
+            .enumerate()
+            .map(|(char_index, char)| (self.glyph_index(char).unwrap_or(0) as u16, char_index))
+            .collect::<Vec<_>>();
+
+        if let Some(gsub) = self.gsub() {
+            let features = features
+                .iter()
+                .filter_map(|tag| tag.as_bytes().try_into().ok())
+                .collect::<Vec<Tag>>();
+
+            for lookup in gsub.lookups(&features) {
+                gsub.apply(lookup, &mut glyphs);
+            }
+        }
+
+        glyphs
+            .into_iter()
+            .map(|(glyph_index, char_index)| SubstitutedGlyph {
+                glyph_index: glyph_index as usize,
+                char_index,
+            })
+            .collect()
+    }
+
+    fn gsub(&self) -> Option<Gsub> {
+        Gsub::parse(Sfnt::parse(self.data())?.table(b"GSUB")?)
+    }
+}
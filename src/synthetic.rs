@@ -0,0 +1,226 @@
+//! Synthetic bold and oblique: emboldening and skewing glyph outlines
+use std::slice;
+
+use ttf2mesh_sys as sys;
+
+#[allow(unused_imports)]
+use crate::TTFFile;
+
+/// Longest shift of a corner, relative to the emboldening offset. Sharper corners are cut short
+/// instead of growing long spikes
+const MITER_LIMIT: f32 = 4.0;
+
+type Point = (f32, f32);
+
+/// Synthetic bold and italic for fonts without such faces, see [`TTFFile::set_synthetic_style`]
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct SyntheticStyle {
+    /// Offset of the contours outward, in em units, e.g. `0.02`. Stems get twice as thick, and
+    /// the advance of each glyph grows by `2 * embolden`
+    pub embolden: f32,
+    /// Horizontal shift per em of height, e.g. `0.2` for a slant of about 11°
+    pub skew: f32,
+}
+
+impl SyntheticStyle {
+    /// Style which keeps the outlines as they are
+    pub fn is_none(&self) -> bool {
+        self.embolden == 0.0 && self.skew == 0.0
+    }
+}
+
+/// Apply a style to the outline and metrics of a glyph loaded by the C library
+pub(crate) fn apply(glyph: &mut sys::ttf_glyph, style: &SyntheticStyle) {
+    glyph.advance += 2.0 * style.embolden;
+    if glyph.outline.is_null() {
+        return;
+    }
+
+    let outline = unsafe { &mut *glyph.outline };
+    let mut contours = contours_mut(outline)
+        .iter_mut()
+        .map(|contour| points_mut(contour).iter().map(|p| (p.x, p.y)).collect())
+        .collect::<Vec<Vec<Point>>>();
+
+    transform(&mut contours, style);
+
+    for (contour, transformed) in contours_mut(outline).iter_mut().zip(&contours) {
+        for (point, &(x, y)) in points_mut(contour).iter_mut().zip(transformed) {
+            point.x = x;
+            point.y = y;
+        }
+    }
+
+    let (old_x, old_advance) = (glyph.xbounds, glyph.advance - 2.0 * style.embolden);
+    if let Some((xbounds, ybounds)) = bounds(&contours) {
+        glyph.xbounds = xbounds;
+        glyph.ybounds = ybounds;
+        glyph.lbearing += xbounds[0] - old_x[0];
+        glyph.rbearing += (glyph.advance - xbounds[1]) - (old_advance - old_x[1]);
+    }
+}
+
+/// Embolden, then skew contours. Emboldened glyphs are moved right by the offset, so that they
+/// keep their left side bearing
+fn transform(contours: &mut [Vec<Point>], style: &SyntheticStyle) {
+    if style.embolden != 0.0 {
+        // TrueType outlines have the filled area on the right of the contour direction, and
+        // CFF ones on the left; the outer contours decide
+        let area = contours.iter().map(|c| signed_area(c)).sum::<f32>();
+        let offset = if area < 0.0 {
+            style.embolden
+        } else {
+            -style.embolden
+        };
+
+        for contour in contours.iter_mut() {
+            embolden(contour, offset);
+        }
+    }
+
+    for contour in contours.iter_mut() {
+        for point in contour.iter_mut() {
+            point.0 += style.embolden + style.skew * point.1;
+        }
+    }
+}
+
+/// Move the edges of a closed contour by `offset` to their left (to their right for a negative
+/// offset). Corners move along their bisector, so that edges stay parallel to the original ones
+fn embolden(contour: &mut [Point], offset: f32) {
+    let original = contour.to_vec();
+
+    for (index, point) in contour.iter_mut().enumerate() {
+        let p = original[index];
+        let distinct = |&&q: &&Point| q != p;
+
+        // neighbours at the same position are skipped
+        let mut previous = original[..index]
+            .iter()
+            .rev()
+            .chain(original[index..].iter().rev());
+        let mut next = original[index..].iter().chain(original[..index].iter());
+        let (previous, next) = match (previous.find(distinct), next.find(distinct)) {
+            (Some(previous), Some(next)) => (*previous, *next),
+            _ => continue,
+        };
+
+        let incoming = left_normal(p.0 - previous.0, p.1 - previous.1);
+        let outgoing = left_normal(next.0 - p.0, next.1 - p.1);
+
+        let dot = incoming.0 * outgoing.0 + incoming.1 * outgoing.1;
+        let scale = offset / (1.0 + dot).max(2.0 / (MITER_LIMIT * MITER_LIMIT));
+
+        point.0 += (incoming.0 + outgoing.0) * scale;
+        point.1 += (incoming.1 + outgoing.1) * scale;
+    }
+}
+
+/// Unit normal to the left of a direction
+fn left_normal(dx: f32, dy: f32) -> Point {
+    let length = (dx * dx + dy * dy).sqrt();
+    (-dy / length, dx / length)
+}
+
+/// Doubled signed area of a contour, positive for counter-clockwise ones
+fn signed_area(contour: &[Point]) -> f32 {
+    contour
+        .iter()
+        .zip(contour.iter().cycle().skip(1))
+        .map(|(a, b)| a.0 * b.1 - b.0 * a.1)
+        .sum()
+}
+
+/// `([min x, max x], [min y, max y])` of the contours, `None` without points
+fn bounds(contours: &[Vec<Point>]) -> Option<([f32; 2], [f32; 2])> {
+    let mut points = contours.iter().flatten();
+    let first = points.next()?;
+
+    Some(
+        points.fold(([first.0, first.0], [first.1, first.1]), |(x, y), p| {
+            (
+                [x[0].min(p.0), x[1].max(p.0)],
+                [y[0].min(p.1), y[1].max(p.1)],
+            )
+        }),
+    )
+}
+
+fn contours_mut(outline: &mut sys::ttf_outline) -> &mut [sys::ttf_outline__bindgen_ty_1] {
+    if outline.ncontours <= 0 {
+        return &mut [];
+    }
+
+    unsafe { slice::from_raw_parts_mut(outline.cont.as_mut_ptr(), outline.ncontours as usize) }
+}
+
+fn points_mut(contour: &mut sys::ttf_outline__bindgen_ty_1) -> &mut [sys::ttf_point] {
+    if contour.pt.is_null() || contour.length <= 0 {
+        return &mut [];
+    }
+
+    unsafe { slice::from_raw_parts_mut(contour.pt, contour.length as usize) }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Outer contour of a TrueType glyph (clockwise) with a hole (counter-clockwise)
+    fn square_with_hole() -> Vec<Vec<Point>> {
+        vec![
+            vec![(0.0, 0.0), (0.0, 1.0), (1.0, 1.0), (1.0, 0.0)],
+            vec![(0.25, 0.25), (0.75, 0.25), (0.75, 0.75), (0.25, 0.75)],
+        ]
+    }
+
+    #[test]
+    fn test_embolden() {
+        let mut contours = square_with_hole();
+        let style = SyntheticStyle {
+            embolden: 0.1,
+            skew: 0.0,
+        };
+        transform(&mut contours, &style);
+
+        // the outer contour grows, the hole shrinks, and both move right by the offset
+        let expected = vec![
+            vec![(0.0, -0.1), (0.0, 1.1), (1.2, 1.1), (1.2, -0.1)],
+            vec![(0.45, 0.35), (0.75, 0.35), (0.75, 0.65), (0.45, 0.65)],
+        ];
+        for (contour, expected) in contours.iter().zip(&expected) {
+            for (p, e) in contour.iter().zip(expected) {
+                assert!((p.0 - e.0).abs() < 1e-6 && (p.1 - e.1).abs() < 1e-6);
+            }
+        }
+
+        // the same with counter-clockwise outer contours (CFF)
+        let mut reversed = square_with_hole();
+        reversed.iter_mut().for_each(|contour| contour.reverse());
+        transform(&mut reversed, &style);
+        assert_eq!(bounds(&reversed), bounds(&contours));
+
+        // a spike is cut at the miter limit, and repeated points are moved like their neighbours
+        let mut spike = vec![(0.0, 0.0), (0.0, 0.0), (10.0, 0.5), (0.0, 1.0)];
+        embolden(&mut spike, -0.1);
+        assert_eq!(spike[0], spike[1]);
+        assert!(spike[2].0 - 10.0 <= 0.1 * MITER_LIMIT);
+    }
+
+    #[test]
+    fn test_skew() {
+        let mut contours = square_with_hole();
+        transform(
+            &mut contours,
+            &SyntheticStyle {
+                embolden: 0.0,
+                skew: 0.2,
+            },
+        );
+
+        assert_eq!(contours[0][0], (0.0, 0.0));
+        assert_eq!(contours[0][2], (1.2, 1.0));
+        assert_eq!(bounds(&contours), Some(([0.0, 1.2], [0.0, 1.0])));
+    }
+}
@@ -0,0 +1,57 @@
+//! `avar` - axis variations table (non-linear mapping of normalized coordinates)
+use crate::sfnt::Reader;
+
+pub(crate) struct Avar {
+    /// `(from, to)` maps for each axis, in `fvar` axis order
+    segments: Vec<Vec<(f32, f32)>>,
+}
+
+impl Avar {
+    pub fn parse(data: &[u8]) -> Option<Self> {
+        let mut r = Reader::new(data);
+
+        let major_version = r.read_u16()?;
+        if major_version != 1 {
+            return None;
+        }
+
+        let _minor_version = r.read_u16()?;
+        let _reserved = r.read_u16()?;
+        let axis_count = r.read_u16()?;
+
+        let mut segments = Vec::with_capacity(axis_count as usize);
+        for _ in 0..axis_count {
+            let count = r.read_u16()?;
+            let mut map = Vec::with_capacity(count as usize);
+            for _ in 0..count {
+                map.push((r.read_f2dot14()?, r.read_f2dot14()?));
+            }
+            segments.push(map);
+        }
+
+        Some(Self { segments })
+    }
+
+    /// Apply the segment map of `axis` to a normalized coordinate
+    pub fn map(&self, axis: usize, value: f32) -> f32 {
+        let map = match self.segments.get(axis) {
+            Some(map) if map.len() >= 2 => map,
+            _ => return value,
+        };
+
+        for pair in map.windows(2) {
+            let (from0, to0) = pair[0];
+            let (from1, to1) = pair[1];
+
+            if value >= from0 && value <= from1 {
+                if from1 == from0 {
+                    return to0;
+                }
+
+                return to0 + (to1 - to0) * (value - from0) / (from1 - from0);
+            }
+        }
+
+        value
+    }
+}
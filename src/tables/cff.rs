@@ -0,0 +1,1229 @@
+//! `CFF ` / `CFF2` - Compact Font Format outlines (Type 2 charstrings with cubic curves)
+//!
+//! Charstrings are interpreted into TrueType-style quadratic contours, so that CFF-flavored
+//! OpenType fonts can be meshed like any TrueType font.
+use std::convert::TryInto;
+
+use crate::{
+    sfnt::Reader,
+    tables::glyf::{Contours, Point},
+};
+
+const OP_CHARSET: u16 = 15;
+const OP_CHAR_STRINGS: u16 = 17;
+const OP_PRIVATE: u16 = 18;
+const OP_SUBRS: u16 = 19;
+const OP_VSINDEX: u16 = 22;
+const OP_VSTORE: u16 = 24;
+const OP_FONT_MATRIX: u16 = 1207;
+const OP_ROS: u16 = 1230;
+const OP_FD_ARRAY: u16 = 1236;
+const OP_FD_SELECT: u16 = 1237;
+
+/// Limit of nested subroutine calls
+const MAX_CALL_DEPTH: usize = 10;
+
+/// Limit of the argument stack (CFF2 allows 513 entries, CFF 48)
+const MAX_STACK: usize = 513;
+
+/// FontMatrix of fonts which do not set one: 1000 units per em
+const DEFAULT_FONT_MATRIX: [f64; 6] = [0.001, 0.0, 0.0, 0.001, 0.0, 0.0];
+
+/// Number of glyph names of the predefined ISOAdobe charset
+const ISO_ADOBE_CHARSET_LEN: usize = 229;
+
+/// String ids of the glyph names of the standard encoding codes, used by `seac` accents
+const STANDARD_ENCODING: [u8; 256] = [
+    0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0,
+    1, 2, 3, 4, 5, 6, 7, 8, 9, 10, 11, 12, 13, 14, 15, 16, 17, 18, 19, 20, 21, 22, 23, 24, 25, 26,
+    27, 28, 29, 30, 31, 32, 33, 34, 35, 36, 37, 38, 39, 40, 41, 42, 43, 44, 45, 46, 47, 48, 49, 50,
+    51, 52, 53, 54, 55, 56, 57, 58, 59, 60, 61, 62, 63, 64, 65, 66, 67, 68, 69, 70, 71, 72, 73, 74,
+    75, 76, 77, 78, 79, 80, 81, 82, 83, 84, 85, 86, 87, 88, 89, 90, 91, 92, 93, 94, 95, 0, 0, 0, 0,
+    0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 96,
+    97, 98, 99, 100, 101, 102, 103, 104, 105, 106, 107, 108, 109, 110, 0, 111, 112, 113, 114, 0,
+    115, 116, 117, 118, 119, 120, 121, 122, 0, 123, 0, 124, 125, 126, 127, 128, 129, 130, 131, 0,
+    132, 133, 0, 134, 135, 136, 137, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 138, 0, 139,
+    0, 0, 0, 0, 140, 141, 142, 143, 0, 0, 0, 0, 0, 144, 0, 0, 0, 145, 0, 0, 146, 147, 148, 149, 0,
+    0, 0, 0,
+];
+
+/// Maximum distance (in font units) between a cubic curve and its quadratic approximation
+const CUBIC_TOLERANCE: f32 = 0.5;
+
+/// An INDEX structure: an array of variable-sized objects
+struct Index<'a> {
+    data: &'a [u8],
+    offsets: Vec<usize>,
+}
+
+impl<'a> Index<'a> {
+    fn empty() -> Self {
+        Self {
+            data: &[],
+            offsets: vec![0],
+        }
+    }
+
+    fn parse(r: &mut Reader<'a>, cff2: bool) -> Option<Self> {
+        let count = if cff2 {
+            r.read_u32()? as usize
+        } else {
+            r.read_u16()? as usize
+        };
+
+        if count == 0 {
+            return Some(Self::empty());
+        }
+
+        let off_size = r.read_u8()? as usize;
+        if !(1..=4).contains(&off_size) {
+            return None;
+        }
+
+        let mut offsets = Vec::with_capacity(count + 1);
+        for _ in 0..=count {
+            // offsets are 1-based
+            offsets.push(read_offset(r, off_size)?.checked_sub(1)?);
+        }
+
+        let data = r.read_bytes(*offsets.last()?)?;
+        if offsets.windows(2).any(|w| w[0] > w[1]) {
+            return None;
+        }
+
+        Some(Self { data, offsets })
+    }
+
+    fn len(&self) -> usize {
+        self.offsets.len() - 1
+    }
+
+    fn get(&self, index: usize) -> Option<&'a [u8]> {
+        let start = *self.offsets.get(index)?;
+        let end = *self.offsets.get(index + 1)?;
+
+        self.data.get(start..end)
+    }
+}
+
+fn read_offset(r: &mut Reader, size: usize) -> Option<usize> {
+    let bytes = r.read_bytes(size)?;
+    Some(bytes.iter().fold(0usize, |acc, &b| acc << 8 | b as usize))
+}
+
+/// Parse a DICT into `(operator, operands)` entries. Two-byte operators are `1200 + op`
+fn parse_dict(data: &[u8]) -> Option<Vec<(u16, Vec<f64>)>> {
+    let mut r = Reader::new(data);
+    let mut entries = Vec::new();
+    let mut operands = Vec::new();
+
+    while let Some(b0) = r.read_u8() {
+        match b0 {
+            0..=11 | 13..=21 | 22..=27 => {
+                entries.push((b0 as u16, std::mem::take(&mut operands)));
+            }
+            12 => {
+                let b1 = r.read_u8()?;
+                entries.push((1200 + b1 as u16, std::mem::take(&mut operands)));
+            }
+            28 => operands.push(r.read_i16()? as f64),
+            29 => operands.push(r.read_i32()? as f64),
+            30 => operands.push(parse_real(&mut r)?),
+            32..=246 => operands.push(b0 as f64 - 139.0),
+            247..=250 => {
+                let b1 = r.read_u8()? as f64;
+                operands.push((b0 as f64 - 247.0) * 256.0 + b1 + 108.0);
+            }
+            251..=254 => {
+                let b1 = r.read_u8()? as f64;
+                operands.push(-(b0 as f64 - 251.0) * 256.0 - b1 - 108.0);
+            }
+            _ => return None,
+        }
+    }
+
+    Some(entries)
+}
+
+fn parse_real(r: &mut Reader) -> Option<f64> {
+    let mut s = String::new();
+
+    loop {
+        let byte = r.read_u8()?;
+        for nibble in [byte >> 4, byte & 0x0F].iter() {
+            match nibble {
+                0..=9 => s.push((b'0' + nibble) as char),
+                0xA => s.push('.'),
+                0xB => s.push('E'),
+                0xC => s.push_str("E-"),
+                0xE => s.push('-'),
+                0xF => return Some(s.parse().unwrap_or(0.0)),
+                _ => (),
+            }
+        }
+    }
+}
+
+fn dict_get(dict: &[(u16, Vec<f64>)], operator: u16) -> Option<&[f64]> {
+    dict.iter()
+        .find(|(op, _)| *op == operator)
+        .map(|(_, operands)| operands.as_slice())
+}
+
+fn dict_offset(dict: &[(u16, Vec<f64>)], operator: u16) -> Option<usize> {
+    let value = *dict_get(dict, operator)?.first()?;
+    if value < 0.0 {
+        return None;
+    }
+
+    Some(value as usize)
+}
+
+/// Local subroutines and blend defaults of a font DICT
+struct PrivateDict<'a> {
+    subrs: Index<'a>,
+    vsindex: usize,
+}
+
+fn parse_private<'a>(cff: &'a [u8], font_dict: &[(u16, Vec<f64>)], cff2: bool) -> PrivateDict<'a> {
+    let empty = PrivateDict {
+        subrs: Index::empty(),
+        vsindex: 0,
+    };
+
+    let (size, offset) = match dict_get(font_dict, OP_PRIVATE) {
+        Some(&[size, offset]) if size >= 0.0 && offset >= 0.0 => (size as usize, offset as usize),
+        _ => return empty,
+    };
+
+    let dict = match cff.get(offset..offset + size).and_then(parse_dict) {
+        Some(dict) => dict,
+        None => return empty,
+    };
+
+    let subrs = dict_offset(&dict, OP_SUBRS)
+        .and_then(|subrs| Reader::at(cff, offset + subrs))
+        .and_then(|mut r| Index::parse(&mut r, cff2))
+        .unwrap_or_else(Index::empty);
+
+    PrivateDict {
+        subrs,
+        vsindex: dict_offset(&dict, OP_VSINDEX).unwrap_or(0),
+    }
+}
+
+pub(crate) struct Cff<'a> {
+    cff2: bool,
+    global_subrs: Index<'a>,
+    char_strings: Index<'a>,
+    privates: Vec<PrivateDict<'a>>,
+    /// Font DICT index for each glyph (CID-keyed fonts and CFF2)
+    fd_select: Vec<u8>,
+    /// Region count for each item variation data (CFF2 blend operands)
+    region_counts: Vec<usize>,
+    /// Glyph name string ids by glyph index (non-CID fonts), to resolve `seac` accents
+    charset: Vec<u16>,
+    /// Transform from charstring to font units for each font DICT, `None` for the identity
+    transforms: Vec<Option<[f32; 6]>>,
+}
+
+impl<'a> Cff<'a> {
+    /// `units_per_em` of the `head` table scales the FontMatrix into font units
+    pub fn parse(data: &'a [u8], cff2: bool, units_per_em: u16) -> Option<Self> {
+        let mut r = Reader::new(data);
+
+        let major = r.read_u8()?;
+        let _minor = r.read_u8()?;
+        let header_size = r.read_u8()? as usize;
+
+        let (top_dict, global_subrs) = if cff2 {
+            if major != 2 {
+                return None;
+            }
+
+            let top_dict_length = r.read_u16()? as usize;
+            let top_dict = parse_dict(data.get(header_size..header_size + top_dict_length)?)?;
+
+            let mut r = Reader::at(data, header_size + top_dict_length)?;
+            (top_dict, Index::parse(&mut r, true)?)
+        } else {
+            if major != 1 {
+                return None;
+            }
+
+            let mut r = Reader::at(data, header_size)?;
+            let _names = Index::parse(&mut r, false)?;
+            let top_dicts = Index::parse(&mut r, false)?;
+            let _strings = Index::parse(&mut r, false)?;
+            let global_subrs = Index::parse(&mut r, false)?;
+
+            (parse_dict(top_dicts.get(0)?)?, global_subrs)
+        };
+
+        let char_strings = {
+            let mut r = Reader::at(data, dict_offset(&top_dict, OP_CHAR_STRINGS)?)?;
+            Index::parse(&mut r, cff2)?
+        };
+
+        let num_glyphs = char_strings.len();
+
+        let top_matrix = font_matrix(&top_dict);
+        let cid_keyed = cff2 || dict_get(&top_dict, OP_ROS).is_some();
+
+        let (privates, transforms, fd_select) = if cid_keyed {
+            let mut r = Reader::at(data, dict_offset(&top_dict, OP_FD_ARRAY)?)?;
+            let fd_array = Index::parse(&mut r, cff2)?;
+
+            let (privates, transforms) = (0..fd_array.len())
+                .map(|i| {
+                    let font_dict = fd_array.get(i).and_then(parse_dict).unwrap_or_default();
+                    let matrix = match (font_matrix(&font_dict), top_matrix) {
+                        (Some(fd), Some(top)) => concat_matrices(&fd, &top),
+                        (fd, top) => fd.or(top).unwrap_or(DEFAULT_FONT_MATRIX),
+                    };
+
+                    (
+                        parse_private(data, &font_dict, cff2),
+                        font_units_transform(&matrix, units_per_em),
+                    )
+                })
+                .unzip();
+
+            let fd_select = match dict_offset(&top_dict, OP_FD_SELECT) {
+                Some(offset) => parse_fd_select(data, offset, num_glyphs)?,
+                None => vec![0; num_glyphs],
+            };
+
+            (privates, transforms, fd_select)
+        } else {
+            let matrix = top_matrix.unwrap_or(DEFAULT_FONT_MATRIX);
+
+            (
+                vec![parse_private(data, &top_dict, cff2)],
+                vec![font_units_transform(&matrix, units_per_em)],
+                vec![0; num_glyphs],
+            )
+        };
+
+        let charset = if cid_keyed {
+            Vec::new()
+        } else {
+            let offset = dict_offset(&top_dict, OP_CHARSET).unwrap_or(0);
+            parse_charset(data, offset, num_glyphs).unwrap_or_default()
+        };
+
+        let region_counts = match dict_offset(&top_dict, OP_VSTORE) {
+            Some(offset) if cff2 => parse_region_counts(data, offset).unwrap_or_default(),
+            _ => Vec::new(),
+        };
+
+        Some(Self {
+            cff2,
+            global_subrs,
+            char_strings,
+            privates,
+            fd_select,
+            region_counts,
+            charset,
+            transforms,
+        })
+    }
+
+    /// Interpret the charstring of a glyph into quadratic contours (TrueType orientation)
+    pub fn outline(&self, glyph: u16) -> Option<Contours> {
+        let mut contours = self.charstring_outline(glyph, true)?;
+
+        let fd = *self.fd_select.get(glyph as usize)? as usize;
+        if let Some(Some(transform)) = self.transforms.get(fd) {
+            transform_contours(&mut contours, transform);
+        }
+
+        Some(contours)
+    }
+
+    /// Contours in charstring units. A `seac` accented glyph is composed of its base glyph and
+    /// its translated accent glyph, which may not be accented glyphs themselves
+    fn charstring_outline(&self, glyph: u16, allow_seac: bool) -> Option<Contours> {
+        let charstring = self.char_strings.get(glyph as usize)?;
+        let fd = *self.fd_select.get(glyph as usize)? as usize;
+        let private = self.privates.get(fd)?;
+
+        let mut interpreter = Interpreter {
+            cff: self,
+            local_subrs: &private.subrs,
+            stack: Vec::new(),
+            builder: ContourBuilder::default(),
+            stems: 0,
+            width_parsed: self.cff2,
+            vsindex: private.vsindex,
+            seac: None,
+        };
+
+        interpreter.run(charstring, 0)?;
+
+        let seac = interpreter.seac;
+        let mut contours = interpreter.builder.finish();
+
+        if let Some([adx, ady, base, accent]) = seac {
+            if !allow_seac {
+                return None;
+            }
+
+            contours.extend(self.charstring_outline(self.standard_glyph(base)?, false)?);
+
+            let mut accent = self.charstring_outline(self.standard_glyph(accent)?, false)?;
+            for point in accent.iter_mut().flatten() {
+                point.x += adx;
+                point.y += ady;
+            }
+            contours.extend(accent);
+        }
+
+        Some(contours)
+    }
+
+    /// Glyph named by a standard encoding code
+    fn standard_glyph(&self, code: f32) -> Option<u16> {
+        if !(0.0..256.0).contains(&code) {
+            return None;
+        }
+
+        let sid = STANDARD_ENCODING[code as usize] as u16;
+        if sid == 0 {
+            return None;
+        }
+
+        let glyph = self.charset.iter().position(|&s| s == sid)?;
+        glyph.try_into().ok()
+    }
+}
+
+fn font_matrix(dict: &[(u16, Vec<f64>)]) -> Option<[f64; 6]> {
+    dict_get(dict, OP_FONT_MATRIX)?.try_into().ok()
+}
+
+/// Matrix applying `first`, then `second`
+fn concat_matrices(first: &[f64; 6], second: &[f64; 6]) -> [f64; 6] {
+    let [a, b, c, d, e, f] = *first;
+    let [a2, b2, c2, d2, e2, f2] = *second;
+
+    [
+        a * a2 + b * c2,
+        a * b2 + b * d2,
+        c * a2 + d * c2,
+        c * b2 + d * d2,
+        e * a2 + f * c2 + e2,
+        e * b2 + f * d2 + f2,
+    ]
+}
+
+/// Scale a FontMatrix (charstring units to em) into font units, `None` for the identity
+fn font_units_transform(matrix: &[f64; 6], units_per_em: u16) -> Option<[f32; 6]> {
+    let scale = units_per_em as f64;
+    let transform = [
+        (matrix[0] * scale) as f32,
+        (matrix[1] * scale) as f32,
+        (matrix[2] * scale) as f32,
+        (matrix[3] * scale) as f32,
+        (matrix[4] * scale) as f32,
+        (matrix[5] * scale) as f32,
+    ];
+
+    let identity = [1.0, 0.0, 0.0, 1.0, 0.0, 0.0];
+    if transform
+        .iter()
+        .zip(identity.iter())
+        .all(|(v, i)| (v - i).abs() < 1e-6)
+    {
+        return None;
+    }
+
+    Some(transform)
+}
+
+fn transform_contours(contours: &mut Contours, transform: &[f32; 6]) {
+    let [a, b, c, d, e, f] = *transform;
+
+    for point in contours.iter_mut().flatten() {
+        let (x, y) = (point.x, point.y);
+        point.x = a * x + c * y + e;
+        point.y = b * x + d * y + f;
+    }
+
+    // a mirroring transform flips the contour orientation
+    if a * d - b * c < 0.0 {
+        for contour in contours.iter_mut() {
+            contour.reverse();
+        }
+    }
+}
+
+/// Glyph name string ids of a non-CID font, empty for the predefined expert charsets
+fn parse_charset(data: &[u8], offset: usize, num_glyphs: usize) -> Option<Vec<u16>> {
+    match offset {
+        // ISOAdobe: the string id of a glyph is its index
+        0 => return Some((0..num_glyphs.min(ISO_ADOBE_CHARSET_LEN) as u16).collect()),
+        1 | 2 => return Some(Vec::new()),
+        _ => (),
+    }
+
+    let mut r = Reader::at(data, offset)?;
+    // .notdef is implicit
+    let mut sids = vec![0];
+
+    match r.read_u8()? {
+        0 => {
+            for _ in 1..num_glyphs {
+                sids.push(r.read_u16()?);
+            }
+        }
+        format @ 1 | format @ 2 => {
+            while sids.len() < num_glyphs {
+                let first = r.read_u16()?;
+                let left = if format == 1 {
+                    r.read_u8()? as u16
+                } else {
+                    r.read_u16()?
+                };
+
+                for i in 0..=left {
+                    sids.push(first.checked_add(i)?);
+                }
+            }
+        }
+        _ => return None,
+    }
+
+    sids.truncate(num_glyphs);
+    Some(sids)
+}
+
+fn parse_fd_select(data: &[u8], offset: usize, num_glyphs: usize) -> Option<Vec<u8>> {
+    let mut r = Reader::at(data, offset)?;
+
+    match r.read_u8()? {
+        0 => Some(r.read_bytes(num_glyphs)?.to_vec()),
+        format @ 3 | format @ 4 => {
+            let wide = format == 4;
+            let read_glyph = |r: &mut Reader| {
+                if wide {
+                    r.read_u32().map(|v| v as usize)
+                } else {
+                    r.read_u16().map(|v| v as usize)
+                }
+            };
+
+            let num_ranges = read_glyph(&mut r)?;
+            let mut fd_select = vec![0; num_glyphs];
+
+            let mut first = read_glyph(&mut r)?;
+            for _ in 0..num_ranges {
+                let fd = if wide {
+                    r.read_u16()?.try_into().ok()?
+                } else {
+                    r.read_u8()?
+                };
+                let next = read_glyph(&mut r)?;
+
+                for entry in fd_select.iter_mut().take(next.min(num_glyphs)).skip(first) {
+                    *entry = fd;
+                }
+                first = next;
+            }
+
+            Some(fd_select)
+        }
+        _ => None,
+    }
+}
+
+/// Region counts of each ItemVariationData within the CFF2 variation store
+fn parse_region_counts(data: &[u8], offset: usize) -> Option<Vec<usize>> {
+    // variation store is prefixed by its u16 length
+    let store = offset + 2;
+    let mut r = Reader::at(data, store)?;
+
+    let _format = r.read_u16()?;
+    let _region_list_offset = r.read_u32()?;
+    let count = r.read_u16()?;
+
+    let mut counts = Vec::with_capacity(count as usize);
+    for _ in 0..count {
+        let data_offset = r.read_u32()? as usize;
+        let mut r = Reader::at(data, store + data_offset)?;
+        let _item_count = r.read_u16()?;
+        let _short_delta_count = r.read_u16()?;
+        counts.push(r.read_u16()? as usize);
+    }
+
+    Some(counts)
+}
+
+fn subr_bias(count: usize) -> usize {
+    if count < 1240 {
+        107
+    } else if count < 33900 {
+        1131
+    } else {
+        32768
+    }
+}
+
+struct Interpreter<'a, 'b> {
+    cff: &'b Cff<'a>,
+    local_subrs: &'b Index<'a>,
+    stack: Vec<f32>,
+    builder: ContourBuilder,
+    stems: usize,
+    width_parsed: bool,
+    vsindex: usize,
+    /// `adx ady bchar achar` of a `seac`-style endchar
+    seac: Option<[f32; 4]>,
+}
+
+impl<'a, 'b> Interpreter<'a, 'b> {
+    /// Drop the optional advance width which may precede the arguments of the first operator
+    fn take_width(&mut self, has_width: bool) {
+        if !self.width_parsed && has_width && !self.stack.is_empty() {
+            self.stack.remove(0);
+        }
+        self.width_parsed = true;
+    }
+
+    /// Returns `Some(true)` when `endchar` has been reached
+    fn run(&mut self, data: &[u8], depth: usize) -> Option<bool> {
+        if depth > MAX_CALL_DEPTH {
+            return None;
+        }
+
+        let mut r = Reader::new(data);
+
+        while let Some(b0) = r.read_u8() {
+            if self.stack.len() > MAX_STACK {
+                return None;
+            }
+
+            match b0 {
+                28 => self.stack.push(r.read_i16()? as f32),
+                32..=246 => self.stack.push(b0 as f32 - 139.0),
+                247..=250 => {
+                    let b1 = r.read_u8()? as f32;
+                    self.stack.push((b0 as f32 - 247.0) * 256.0 + b1 + 108.0);
+                }
+                251..=254 => {
+                    let b1 = r.read_u8()? as f32;
+                    self.stack.push(-(b0 as f32 - 251.0) * 256.0 - b1 - 108.0);
+                }
+                255 => self.stack.push(r.read_i32()? as f32 / 65536.0),
+
+                // hstem, vstem, hstemhm, vstemhm
+                1 | 3 | 18 | 23 => {
+                    self.take_width(self.stack.len() % 2 == 1);
+                    self.stems += self.stack.len() / 2;
+                    self.stack.clear();
+                }
+                // hintmask, cntrmask
+                19 | 20 => {
+                    self.take_width(self.stack.len() % 2 == 1);
+                    self.stems += self.stack.len() / 2;
+                    self.stack.clear();
+                    r.skip((self.stems + 7) / 8)?;
+                }
+                // rmoveto
+                21 => {
+                    self.take_width(self.stack.len() > 2);
+                    let (dx, dy) = (self.arg(0)?, self.arg(1)?);
+                    self.builder.move_rel(dx, dy);
+                    self.stack.clear();
+                }
+                // hmoveto
+                22 => {
+                    self.take_width(self.stack.len() > 1);
+                    let dx = self.arg(0)?;
+                    self.builder.move_rel(dx, 0.0);
+                    self.stack.clear();
+                }
+                // vmoveto
+                4 => {
+                    self.take_width(self.stack.len() > 1);
+                    let dy = self.arg(0)?;
+                    self.builder.move_rel(0.0, dy);
+                    self.stack.clear();
+                }
+                // rlineto
+                5 => {
+                    for pair in self.stack.chunks_exact(2) {
+                        self.builder.line_rel(pair[0], pair[1]);
+                    }
+                    self.stack.clear();
+                }
+                // hlineto, vlineto
+                6 | 7 => {
+                    let mut horizontal = b0 == 6;
+                    for &d in self.stack.iter() {
+                        if horizontal {
+                            self.builder.line_rel(d, 0.0);
+                        } else {
+                            self.builder.line_rel(0.0, d);
+                        }
+                        horizontal = !horizontal;
+                    }
+                    self.stack.clear();
+                }
+                // rrcurveto
+                8 => {
+                    for a in self.stack.chunks_exact(6) {
+                        self.builder.curve_rel(a[0], a[1], a[2], a[3], a[4], a[5]);
+                    }
+                    self.stack.clear();
+                }
+                // rcurveline
+                24 => {
+                    if self.stack.len() < 8 {
+                        return None;
+                    }
+                    let curves = (self.stack.len() - 2) / 6;
+                    for a in self.stack.chunks_exact(6).take(curves) {
+                        self.builder.curve_rel(a[0], a[1], a[2], a[3], a[4], a[5]);
+                    }
+                    let n = self.stack.len();
+                    self.builder.line_rel(self.stack[n - 2], self.stack[n - 1]);
+                    self.stack.clear();
+                }
+                // rlinecurve
+                25 => {
+                    if self.stack.len() < 8 {
+                        return None;
+                    }
+                    let lines = (self.stack.len() - 6) / 2;
+                    for pair in self.stack.chunks_exact(2).take(lines) {
+                        self.builder.line_rel(pair[0], pair[1]);
+                    }
+                    let a = &self.stack[lines * 2..];
+                    self.builder.curve_rel(a[0], a[1], a[2], a[3], a[4], a[5]);
+                    self.stack.clear();
+                }
+                // vvcurveto
+                26 => {
+                    let mut args = &self.stack[..];
+                    let mut dx1 = 0.0;
+                    if args.len() % 2 == 1 {
+                        dx1 = args[0];
+                        args = &args[1..];
+                    }
+                    for a in args.chunks_exact(4) {
+                        self.builder.curve_rel(dx1, a[0], a[1], a[2], 0.0, a[3]);
+                        dx1 = 0.0;
+                    }
+                    self.stack.clear();
+                }
+                // hhcurveto
+                27 => {
+                    let mut args = &self.stack[..];
+                    let mut dy1 = 0.0;
+                    if args.len() % 2 == 1 {
+                        dy1 = args[0];
+                        args = &args[1..];
+                    }
+                    for a in args.chunks_exact(4) {
+                        self.builder.curve_rel(a[0], dy1, a[1], a[2], a[3], 0.0);
+                        dy1 = 0.0;
+                    }
+                    self.stack.clear();
+                }
+                // vhcurveto, hvcurveto
+                30 | 31 => {
+                    let mut horizontal = b0 == 31;
+                    let args = &self.stack[..];
+                    let mut i = 0;
+                    while i + 4 <= args.len() {
+                        let last = if args.len() - i == 5 {
+                            args[i + 4]
+                        } else {
+                            0.0
+                        };
+                        let a = &args[i..i + 4];
+
+                        if horizontal {
+                            self.builder.curve_rel(a[0], 0.0, a[1], a[2], last, a[3]);
+                        } else {
+                            self.builder.curve_rel(0.0, a[0], a[1], a[2], a[3], last);
+                        }
+
+                        i += 4;
+                        horizontal = !horizontal;
+                    }
+                    self.stack.clear();
+                }
+                // callsubr, callgsubr
+                10 | 29 => {
+                    let subrs = if b0 == 10 {
+                        self.local_subrs
+                    } else {
+                        &self.cff.global_subrs
+                    };
+
+                    let index = self.stack.pop()? as i32 + subr_bias(subrs.len()) as i32;
+                    let subr = subrs.get(index.try_into().ok()?)?;
+
+                    if self.run(subr, depth + 1)? {
+                        return Some(true);
+                    }
+                }
+                // return
+                11 => return Some(false),
+                // endchar, with the arguments of the deprecated `seac` accent form
+                14 => {
+                    self.take_width(self.stack.len() == 1 || self.stack.len() == 5);
+                    if let [adx, ady, bchar, achar] = self.stack[..] {
+                        self.seac = Some([adx, ady, bchar, achar]);
+                    }
+                    self.stack.clear();
+                    return Some(true);
+                }
+                // vsindex
+                15 => {
+                    self.vsindex = self.stack.pop()? as usize;
+                }
+                // blend: keep the default values, drop the region deltas
+                16 => {
+                    let n = self.stack.pop()? as usize;
+                    let regions = *self.cff.region_counts.get(self.vsindex)?;
+                    let deltas = n.checked_mul(regions)?;
+                    let len = self.stack.len().checked_sub(deltas)?;
+                    self.stack.truncate(len);
+                }
+                12 => {
+                    let b1 = r.read_u8()?;
+                    self.flex(b1)?;
+                    self.stack.clear();
+                }
+                _ => {
+                    // reserved operators
+                    self.stack.clear();
+                }
+            }
+        }
+
+        Some(false)
+    }
+
+    fn arg(&self, index: usize) -> Option<f32> {
+        self.stack.get(index).copied()
+    }
+
+    fn flex(&mut self, op: u8) -> Option<()> {
+        let a = &self.stack;
+
+        match op {
+            // hflex
+            34 if a.len() >= 7 => {
+                self.builder.curve_rel(a[0], 0.0, a[1], a[2], a[3], 0.0);
+                self.builder.curve_rel(a[4], 0.0, a[5], -a[2], a[6], 0.0);
+            }
+            // flex
+            35 if a.len() >= 12 => {
+                self.builder.curve_rel(a[0], a[1], a[2], a[3], a[4], a[5]);
+                self.builder.curve_rel(a[6], a[7], a[8], a[9], a[10], a[11]);
+            }
+            // hflex1
+            36 if a.len() >= 9 => {
+                self.builder.curve_rel(a[0], a[1], a[2], a[3], a[4], 0.0);
+                let dy = -(a[1] + a[3] + a[7]);
+                self.builder.curve_rel(a[5], 0.0, a[6], a[7], a[8], dy);
+            }
+            // flex1
+            37 if a.len() >= 11 => {
+                let dx = a[0] + a[2] + a[4] + a[6] + a[8];
+                let dy = a[1] + a[3] + a[5] + a[7] + a[9];
+                let (dx6, dy6) = if dx.abs() > dy.abs() {
+                    (a[10], -dy)
+                } else {
+                    (-dx, a[10])
+                };
+
+                self.builder.curve_rel(a[0], a[1], a[2], a[3], a[4], a[5]);
+                self.builder.curve_rel(a[6], a[7], a[8], a[9], dx6, dy6);
+            }
+            34..=37 => return None,
+            _ => (),
+        }
+
+        Some(())
+    }
+}
+
+/// Collects charstring path commands as quadratic TrueType contours
+#[derive(Default)]
+struct ContourBuilder {
+    contours: Contours,
+    current: Vec<Point>,
+    x: f32,
+    y: f32,
+}
+
+impl ContourBuilder {
+    fn move_rel(&mut self, dx: f32, dy: f32) {
+        self.close();
+        self.x += dx;
+        self.y += dy;
+        self.current.push(on_curve(self.x, self.y));
+    }
+
+    fn line_rel(&mut self, dx: f32, dy: f32) {
+        self.x += dx;
+        self.y += dy;
+        self.current.push(on_curve(self.x, self.y));
+    }
+
+    #[allow(clippy::too_many_arguments)]
+    fn curve_rel(&mut self, dx1: f32, dy1: f32, dx2: f32, dy2: f32, dx3: f32, dy3: f32) {
+        let p0 = (self.x, self.y);
+        let p1 = (p0.0 + dx1, p0.1 + dy1);
+        let p2 = (p1.0 + dx2, p1.1 + dy2);
+        let p3 = (p2.0 + dx3, p2.1 + dy3);
+
+        cubic_to_quadratic(p0, p1, p2, p3, &mut self.current);
+
+        self.x = p3.0;
+        self.y = p3.1;
+    }
+
+    fn close(&mut self) {
+        let mut contour = std::mem::take(&mut self.current);
+
+        if contour.len() > 1 && contour.first() == contour.last() {
+            contour.pop();
+        }
+
+        if contour.len() > 1 {
+            // CFF outer contours run counter-clockwise, TrueType ones clockwise
+            contour.reverse();
+            self.contours.push(contour);
+        }
+    }
+
+    fn finish(mut self) -> Contours {
+        self.close();
+        self.contours
+    }
+}
+
+fn on_curve(x: f32, y: f32) -> Point {
+    Point {
+        x,
+        y,
+        on_curve: true,
+    }
+}
+
+/// Approximate a cubic Bézier with quadratic segments, pushing `(control, end)` point pairs
+pub(crate) fn cubic_to_quadratic(
+    p0: (f32, f32),
+    p1: (f32, f32),
+    p2: (f32, f32),
+    p3: (f32, f32),
+    out: &mut Vec<Point>,
+) {
+    // error of the single-quadratic approximation, decreases with the cube of the segment count
+    let dx = p3.0 - 3.0 * p2.0 + 3.0 * p1.0 - p0.0;
+    let dy = p3.1 - 3.0 * p2.1 + 3.0 * p1.1 - p0.1;
+    let error = (dx * dx + dy * dy).sqrt() * 3f32.sqrt() / 36.0;
+    let segments = (error / CUBIC_TOLERANCE).cbrt().ceil().max(1.0).min(16.0) as usize;
+
+    let point = |t: f32| {
+        let mt = 1.0 - t;
+        let (a, b, c, d) = (mt * mt * mt, 3.0 * mt * mt * t, 3.0 * mt * t * t, t * t * t);
+        (
+            a * p0.0 + b * p1.0 + c * p2.0 + d * p3.0,
+            a * p0.1 + b * p1.1 + c * p2.1 + d * p3.1,
+        )
+    };
+    let derivative = |t: f32| {
+        let mt = 1.0 - t;
+        let (a, b, c) = (3.0 * mt * mt, 6.0 * mt * t, 3.0 * t * t);
+        (
+            a * (p1.0 - p0.0) + b * (p2.0 - p1.0) + c * (p3.0 - p2.0),
+            a * (p1.1 - p0.1) + b * (p2.1 - p1.1) + c * (p3.1 - p2.1),
+        )
+    };
+
+    for i in 0..segments {
+        let (t0, t1) = (i as f32 / segments as f32, (i + 1) as f32 / segments as f32);
+        let h = (t1 - t0) / 3.0;
+
+        let (q0, q3) = (point(t0), point(t1));
+        let (d0, d3) = (derivative(t0), derivative(t1));
+        let q1 = (q0.0 + h * d0.0, q0.1 + h * d0.1);
+        let q2 = (q3.0 - h * d3.0, q3.1 - h * d3.1);
+
+        out.push(Point {
+            x: (3.0 * (q1.0 + q2.0) - q0.0 - q3.0) / 4.0,
+            y: (3.0 * (q1.1 + q2.1) - q0.1 - q3.1) / 4.0,
+            on_curve: false,
+        });
+        out.push(on_curve(q3.0, q3.1));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        sfnt::{self, Sfnt},
+        tables::glyf::{self, Glyf},
+    };
+
+    /// Encode an integer charstring operand
+    fn int(v: i32) -> Vec<u8> {
+        if (-107..=107).contains(&v) {
+            vec![(v + 139) as u8]
+        } else {
+            let mut out = vec![28];
+            out.extend_from_slice(&(v as i16).to_be_bytes());
+            out
+        }
+    }
+
+    fn index(items: &[Vec<u8>]) -> Vec<u8> {
+        let mut out = (items.len() as u16).to_be_bytes().to_vec();
+        if items.is_empty() {
+            return out;
+        }
+
+        out.push(4);
+        let mut offset = 1u32;
+        out.extend_from_slice(&offset.to_be_bytes());
+        for item in items {
+            offset += item.len() as u32;
+            out.extend_from_slice(&offset.to_be_bytes());
+        }
+        for item in items {
+            out.extend_from_slice(item);
+        }
+        out
+    }
+
+    /// Minimal CFF with a single glyph, using a global subroutine for part of the path.
+    /// `top_dict` entries precede the CharStrings offset
+    fn build_cff(charstring: Vec<u8>, gsubr: Vec<u8>, top_dict: &[u8]) -> Vec<u8> {
+        let header = vec![1, 0, 4, 4];
+        let names = index(&[b"Test".to_vec()]);
+        let strings = index(&[]);
+        let gsubrs = index(&[gsubr]);
+        let char_strings = index(&[charstring]);
+
+        // top DICT with a fixed-size (5 byte) CharStrings operand
+        let top_dict_len = top_dict.len() + 6;
+        let char_strings_offset = header.len()
+            + names.len()
+            + index(&[vec![0; top_dict_len]]).len()
+            + strings.len()
+            + gsubrs.len();
+
+        let mut top_dict = top_dict.to_vec();
+        top_dict.push(29);
+        top_dict.extend_from_slice(&(char_strings_offset as i32).to_be_bytes());
+        top_dict.push(OP_CHAR_STRINGS as u8);
+
+        let mut cff = header;
+        cff.extend(names);
+        cff.extend(index(&[top_dict]));
+        cff.extend(strings);
+        cff.extend(gsubrs);
+        cff.extend(char_strings);
+        cff
+    }
+
+    /// Width 500, square 100x100 at (50, 0), the last edge and endchar in a global subr
+    fn square(top_dict: &[u8]) -> Vec<u8> {
+        let mut gsubr = Vec::new();
+        gsubr.extend(int(-100));
+        gsubr.push(6); // hlineto
+        gsubr.push(14); // endchar
+
+        let mut charstring = Vec::new();
+        for v in &[500, 50, 0] {
+            charstring.extend(int(*v));
+        }
+        charstring.push(21); // rmoveto
+        for v in &[100, 100] {
+            charstring.extend(int(*v));
+        }
+        charstring.push(6); // hlineto: +100 x, +100 y
+        charstring.extend(int(-107));
+        charstring.push(29); // callgsubr 0 (bias 107)
+
+        build_cff(charstring, gsubr, top_dict)
+    }
+
+    fn points(contour: &[Point]) -> Vec<(f32, f32, bool)> {
+        contour.iter().map(|p| (p.x, p.y, p.on_curve)).collect()
+    }
+
+    #[test]
+    fn test_outline() {
+        let data = square(&[]);
+        let cff = Cff::parse(&data, false, 1000).unwrap();
+        let contours = cff.outline(0).unwrap();
+
+        assert_eq!(
+            points(&contours[0]),
+            vec![
+                (50.0, 100.0, true),
+                (150.0, 100.0, true),
+                (150.0, 0.0, true),
+                (50.0, 0.0, true),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_font_matrix() {
+        // FontMatrix [0.002 0 0 -0.002 0 0]: 500 units per em, mirrored vertically
+        let (positive, negative) = (vec![30, 0x0A, 0x00, 0x2F], vec![30, 0xE0, 0xA0, 0x02, 0xFF]);
+        let zero = int(0);
+        let matrix = [
+            positive,
+            zero.clone(),
+            zero.clone(),
+            negative,
+            zero.clone(),
+            zero,
+            vec![12, (OP_FONT_MATRIX - 1200) as u8],
+        ]
+        .concat();
+
+        let data = square(&matrix);
+        let cff = Cff::parse(&data, false, 1000).unwrap();
+
+        // scaled, mirrored and reversed back to clockwise
+        assert_eq!(
+            points(&cff.outline(0).unwrap()[0]),
+            vec![
+                (100.0, 0.0, true),
+                (300.0, 0.0, true),
+                (300.0, -200.0, true),
+                (100.0, -200.0, true),
+            ]
+        );
+
+        // the default FontMatrix is 1000 units per em
+        let data = square(&[]);
+        let cff = Cff::parse(&data, false, 2000).unwrap();
+        assert_eq!(cff.outline(0).unwrap()[0][1], on_curve(300.0, 200.0));
+    }
+
+    /// Contours of a glyph of FiraMono-Medium.ttf
+    fn truetype_outline(glyph: u16) -> Contours {
+        let data = crate::tests::read_font(None);
+        let sfnt = Sfnt::parse(&data).unwrap();
+        let head = sfnt.table(b"head").unwrap();
+        let glyf = Glyf::parse(
+            sfnt.table(b"glyf").unwrap(),
+            sfnt.table(b"loca").unwrap(),
+            glyf::index_to_loc_format(head).unwrap(),
+            glyf::num_glyphs(sfnt.table(b"maxp").unwrap()).unwrap(),
+        )
+        .unwrap();
+
+        glyf::flatten(&mut |index| glyf.glyph(index), glyph, 0).unwrap()
+    }
+
+    /// The outline matches within rounding: same contours and bounds, and all the on-curve
+    /// points of the TrueType outline
+    fn assert_outline_eq(outline: &[Vec<Point>], expected: &[Vec<Point>]) {
+        assert_eq!(outline.len(), expected.len());
+
+        let (bounds, expected_bounds) = (glyf::bounds(outline), glyf::bounds(expected));
+        let (a, b) = (bounds.unwrap(), expected_bounds.unwrap());
+        for (v, e) in [(a.0, b.0), (a.1, b.1), (a.2, b.2), (a.3, b.3)].iter() {
+            assert!((v - e).abs() <= 1, "{:?} != {:?}", bounds, expected_bounds);
+        }
+
+        for p in expected.iter().flatten().filter(|p| p.on_curve) {
+            assert!(
+                outline
+                    .iter()
+                    .flatten()
+                    .any(|q| q.on_curve && (q.x - p.x).abs() <= 1.0 && (q.y - p.y).abs() <= 1.0),
+                "missing point {:?}",
+                p
+            );
+        }
+    }
+
+    /// Glyphs of the fixture fonts built by `fonts/build_cff_fonts.py`: `(glyph, FiraMono glyph)`
+    const FIXTURE_GLYPHS: [(u16, u16); 7] = [
+        (1, 3),
+        (2, 4),
+        (3, 27),
+        (4, 74),
+        (5, 210),
+        (6, 170),
+        (7, 1442),
+    ];
+
+    fn fixture_cff(font_file: &str, cff2: bool, test: impl Fn(&Cff)) {
+        let data = crate::tests::read_font(Some(font_file));
+        let sfnt = Sfnt::parse(&data).unwrap();
+        let units_per_em = sfnt::get_u16(sfnt.table(b"head").unwrap(), 18).unwrap();
+        let table = sfnt.table(if cff2 { b"CFF2" } else { b"CFF " }).unwrap();
+
+        test(&Cff::parse(table, cff2, units_per_em).unwrap());
+    }
+
+    #[test]
+    fn test_fixture_outlines() {
+        let test = |cff: &Cff| {
+            for &(glyph, expected) in FIXTURE_GLYPHS.iter() {
+                let outline = cff.outline(glyph).unwrap();
+                let expected = truetype_outline(expected);
+                if expected.is_empty() {
+                    assert!(outline.is_empty());
+                } else {
+                    assert_outline_eq(&outline, &expected);
+                }
+            }
+        };
+
+        fixture_cff("FiraMono-Medium-CFF.otf", false, test);
+        fixture_cff("FiraMono-Medium-CFF2.otf", true, test);
+    }
+
+    #[test]
+    fn test_seac() {
+        fixture_cff("FiraMono-Medium-CFF.otf", false, |cff| {
+            // Eacute: E, and acute offset by (21, 0), like the composite glyph of FiraMono
+            assert_outline_eq(&cff.outline(8).unwrap(), &truetype_outline(28));
+        });
+    }
+
+    #[test]
+    fn test_cubic_to_quadratic() {
+        let mut out = Vec::new();
+        cubic_to_quadratic(
+            (0.0, 0.0),
+            (0.0, 552.0),
+            (448.0, 1000.0),
+            (1000.0, 1000.0),
+            &mut out,
+        );
+
+        assert!(out.len() >= 4);
+        assert_eq!(out.last(), Some(&on_curve(1000.0, 1000.0)));
+
+        // a quadratic curve expressed as a cubic is converted exactly
+        let mut out = Vec::new();
+        cubic_to_quadratic(
+            (0.0, 0.0),
+            (200.0, 400.0),
+            (400.0, 400.0),
+            (600.0, 0.0),
+            &mut out,
+        );
+
+        assert_eq!(out.len(), 2);
+        assert_eq!((out[0].x, out[0].y, out[0].on_curve), (300.0, 600.0, false));
+    }
+}
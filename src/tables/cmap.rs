@@ -0,0 +1,133 @@
+//! `cmap` - character to glyph mapping of characters outside the Basic Multilingual Plane, which
+//! the C library (mapping UTF-16 code units) does not look up
+use crate::sfnt::{get_u16, Reader};
+
+/// Subtable format of full Unicode encodings: groups of consecutive characters and glyphs
+const SEGMENTED_COVERAGE: u16 = 12;
+
+/// Size of a sequential map group: first and last character, and the glyph of the first one
+const GROUP_SIZE: usize = 12;
+
+/// Glyph of a character in the first format 12 subtable. `None` for fonts without one, and for
+/// characters which it does not map
+pub(crate) fn glyph(cmap: &[u8], char: u32) -> Option<u16> {
+    let mut r = Reader::new(cmap);
+    let _version = r.read_u16()?;
+    let num_tables = r.read_u16()?;
+
+    let mut subtable = None;
+    for _ in 0..num_tables {
+        let _platform_id = r.read_u16()?;
+        let _encoding_id = r.read_u16()?;
+        let offset = r.read_u32()? as usize;
+
+        if get_u16(cmap, offset) == Some(SEGMENTED_COVERAGE) {
+            subtable = Some(offset);
+            break;
+        }
+    }
+
+    // format, reserved, length and language before the groups
+    let mut r = Reader::at(cmap, subtable? + 12)?;
+    let num_groups = r.read_u32()? as usize;
+    let groups = r.pos();
+
+    let (mut low, mut high) = (0, num_groups);
+    while low < high {
+        let middle = (low + high) / 2;
+        let mut r = Reader::at(cmap, groups + middle * GROUP_SIZE)?;
+        let start = r.read_u32()?;
+        let end = r.read_u32()?;
+        let start_glyph = r.read_u32()?;
+
+        if char < start {
+            high = middle;
+        } else if char > end {
+            low = middle + 1;
+        } else {
+            let glyph = start_glyph.checked_add(char - start)?;
+            return Some(glyph as u16).filter(|&glyph| glyph != 0);
+        }
+    }
+
+    None
+}
+
+#[cfg(test)]
+pub(crate) mod tests {
+    use super::*;
+    use crate::sfnt::WriteBe;
+
+    /// Copy of a `cmap` table whose format 12 subtables are replaced by a Windows full Unicode
+    /// (3, 10) one, mapping groups of first and last character to the glyph of the first one
+    pub(crate) fn with_segmented_coverage(cmap: &[u8], groups: &[(u32, u32, u32)]) -> Vec<u8> {
+        let mut r = Reader::new(cmap);
+        let version = r.read_u16().unwrap();
+        let num_tables = r.read_u16().unwrap() as usize;
+        let records = r
+            .read_bytes(num_tables * 8)
+            .unwrap()
+            .chunks(8)
+            .filter(|record| {
+                let offset = u32::from_be_bytes([record[4], record[5], record[6], record[7]]);
+                get_u16(cmap, offset as usize) != Some(SEGMENTED_COVERAGE)
+            })
+            .collect::<Vec<_>>();
+
+        // the subtables move with the size of the encoding records
+        let (header, new_header) = (4 + num_tables * 8, 4 + (records.len() + 1) * 8);
+        let mut out = Vec::new();
+        out.put_u16(version);
+        out.put_u16(records.len() as u16 + 1);
+        for record in records {
+            let offset = u32::from_be_bytes([record[4], record[5], record[6], record[7]]);
+            out.extend_from_slice(&record[..4]);
+            out.put_u32((offset as usize - header + new_header) as u32);
+        }
+        out.put_u16(3);
+        out.put_u16(10);
+        out.put_u32((cmap.len() - header + new_header) as u32);
+        out.extend_from_slice(&cmap[header..]);
+
+        out.put_u16(SEGMENTED_COVERAGE);
+        out.put_u16(0);
+        out.put_u32(16 + (groups.len() * GROUP_SIZE) as u32);
+        out.put_u32(0);
+        out.put_u32(groups.len() as u32);
+        for &(start, end, start_glyph) in groups {
+            out.put_u32(start);
+            out.put_u32(end);
+            out.put_u32(start_glyph);
+        }
+
+        out
+    }
+
+    #[test]
+    fn test_glyph() {
+        let mut cmap = Vec::new();
+        cmap.put_u16(0);
+        cmap.put_u16(0);
+
+        assert_eq!(glyph(&cmap, 0x1F600), None);
+
+        let cmap = with_segmented_coverage(&cmap, &[(0x41, 0x5A, 36), (0x1F600, 0x1F64F, 900)]);
+        assert_eq!(glyph(&cmap, 0x41), Some(36));
+        assert_eq!(glyph(&cmap, 0x5A), Some(61));
+        assert_eq!(glyph(&cmap, 0x1F600), Some(900));
+        assert_eq!(glyph(&cmap, 0x1F60A), Some(910));
+        assert_eq!(glyph(&cmap, 0x1F650), None);
+        assert_eq!(glyph(&cmap, 0x10), None);
+        assert_eq!(glyph(&[], 0x1F600), None);
+
+        // the format 12 subtable of FiraMono is replaced
+        let font = crate::tests::read_font(None);
+        let sfnt = crate::sfnt::Sfnt::parse(&font).unwrap();
+        let firamono = sfnt.table(b"cmap").unwrap();
+        assert_eq!(glyph(firamono, 0x41), Some(4));
+
+        let cmap = with_segmented_coverage(firamono, &[(0x1F600, 0x1F600, 4)]);
+        assert_eq!(glyph(&cmap, 0x1F600), Some(4));
+        assert_eq!(glyph(&cmap, 0x41), None);
+    }
+}
@@ -0,0 +1,317 @@
+//! `COLR` - color glyphs as layers of glyphs filled with palette colors
+use crate::sfnt::{get_u16, Reader};
+
+/// Palette index of layers drawn in the text color
+pub(crate) const FOREGROUND: u16 = 0xFFFF;
+
+/// Deepest nesting of version 1 paint tables which is followed, against cycles
+const MAX_DEPTH: usize = 32;
+
+/// Size of base glyph records (version 0) and base glyph paint records (version 1)
+const BASE_RECORD_SIZE: usize = 6;
+
+/// Size of layer records (version 0)
+const LAYER_RECORD_SIZE: usize = 4;
+
+const PAINT_COLR_LAYERS: u8 = 1;
+const PAINT_SOLID: u8 = 2;
+const PAINT_VAR_SOLID: u8 = 3;
+/// Linear, radial and sweep gradients, and their variable versions
+const PAINT_GRADIENTS: (u8, u8) = (4, 9);
+const PAINT_GLYPH: u8 = 10;
+const PAINT_COLR_GLYPH: u8 = 11;
+
+/// A glyph filled with a color
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub(crate) struct Layer {
+    pub glyph: u16,
+    /// Index of the color in a palette, or [`FOREGROUND`]
+    pub palette_index: u16,
+    /// Multiplied with the alpha of the palette color
+    pub alpha: f32,
+}
+
+struct Header {
+    num_base_glyphs: usize,
+    base_glyphs: usize,
+    layers: usize,
+    /// Version 1 lists, 0 when missing
+    base_glyph_list: usize,
+    layer_list: usize,
+}
+
+impl Header {
+    fn parse(colr: &[u8]) -> Option<Self> {
+        let mut r = Reader::new(colr);
+
+        let version = r.read_u16()?;
+        let num_base_glyphs = r.read_u16()? as usize;
+        let base_glyphs = r.read_u32()? as usize;
+        let layers = r.read_u32()? as usize;
+        let _num_layers = r.read_u16()?;
+
+        let (base_glyph_list, layer_list) = if version >= 1 {
+            (r.read_u32()? as usize, r.read_u32()? as usize)
+        } else {
+            (0, 0)
+        };
+
+        Some(Self {
+            num_base_glyphs,
+            base_glyphs,
+            layers,
+            base_glyph_list,
+            layer_list,
+        })
+    }
+
+    /// Offset of the root paint of a glyph in the version 1 base glyph list
+    fn base_paint(&self, colr: &[u8], glyph: u16) -> Option<usize> {
+        if self.base_glyph_list == 0 {
+            return None;
+        }
+
+        let count = Reader::at(colr, self.base_glyph_list)?.read_u32()? as usize;
+        let record = find_record(colr, self.base_glyph_list + 4, count, glyph)?;
+        let paint = Reader::at(colr, record + 2)?.read_u32()? as usize;
+
+        Some(self.base_glyph_list + paint)
+    }
+}
+
+/// Layers of a color glyph, bottom first. `None` for glyphs without color
+///
+/// Version 1 glyphs are read when their paint graph is made of layers of glyphs filled with
+/// solid colors. Gradient fills use the color of their first stop, and other paints
+/// (transforms, compositing) are skipped
+pub(crate) fn layers(colr: &[u8], glyph: u16) -> Option<Vec<Layer>> {
+    let header = Header::parse(colr)?;
+
+    if let Some(paint) = header.base_paint(colr, glyph) {
+        let mut layers = Vec::new();
+        collect(colr, &header, paint, 0, &mut layers);
+
+        return Some(layers);
+    }
+
+    let record = find_record(colr, header.base_glyphs, header.num_base_glyphs, glyph)?;
+    let mut r = Reader::at(colr, record + 2)?;
+    let first = r.read_u16()? as usize;
+    let count = r.read_u16()? as usize;
+
+    (first..first + count)
+        .map(|index| {
+            let mut r = Reader::at(colr, header.layers + index * LAYER_RECORD_SIZE)?;
+
+            Some(Layer {
+                glyph: r.read_u16()?,
+                palette_index: r.read_u16()?,
+                alpha: 1.0,
+            })
+        })
+        .collect()
+}
+
+/// Add the layers of a version 1 paint table
+fn collect(
+    colr: &[u8],
+    header: &Header,
+    paint: usize,
+    depth: usize,
+    layers: &mut Vec<Layer>,
+) -> Option<()> {
+    if depth > MAX_DEPTH {
+        return None;
+    }
+
+    let mut r = Reader::at(colr, paint)?;
+    match r.read_u8()? {
+        PAINT_COLR_LAYERS => {
+            let count = r.read_u8()? as usize;
+            let first = r.read_u32()? as usize;
+
+            for index in first..first + count {
+                let offset = Reader::at(colr, header.layer_list + 4 + index * 4)?.read_u32()?;
+                collect(
+                    colr,
+                    header,
+                    header.layer_list + offset as usize,
+                    depth + 1,
+                    layers,
+                );
+            }
+        }
+        PAINT_GLYPH => {
+            let fill = paint + r.read_u24()? as usize;
+            let glyph = r.read_u16()?;
+            let (palette_index, alpha) = solid_fill(colr, fill)?;
+
+            layers.push(Layer {
+                glyph,
+                palette_index,
+                alpha,
+            });
+        }
+        PAINT_COLR_GLYPH => {
+            let paint = header.base_paint(colr, r.read_u16()?)?;
+            collect(colr, header, paint, depth + 1, layers);
+        }
+        _ => return None,
+    }
+
+    Some(())
+}
+
+/// Palette index and alpha of a fill paint
+fn solid_fill(colr: &[u8], paint: usize) -> Option<(u16, f32)> {
+    let mut r = Reader::at(colr, paint)?;
+
+    match r.read_u8()? {
+        PAINT_SOLID | PAINT_VAR_SOLID => Some((r.read_u16()?, r.read_f2dot14()?)),
+        format if (PAINT_GRADIENTS.0..=PAINT_GRADIENTS.1).contains(&format) => {
+            // color line: extend mode, number of stops, then stops of offset, color and alpha
+            let mut r = Reader::at(colr, paint + r.read_u24()? as usize)?;
+            r.skip(3)?;
+            let _stop_offset = r.read_f2dot14()?;
+
+            Some((r.read_u16()?, r.read_f2dot14()?))
+        }
+        _ => None,
+    }
+}
+
+/// Offset of the record of a glyph in an array of records sorted by glyph
+fn find_record(colr: &[u8], offset: usize, count: usize, glyph: u16) -> Option<usize> {
+    let (mut low, mut high) = (0, count);
+
+    while low < high {
+        let middle = (low + high) / 2;
+        let record = offset + middle * BASE_RECORD_SIZE;
+        let id = get_u16(colr, record)?;
+
+        if id < glyph {
+            low = middle + 1;
+        } else if id > glyph {
+            high = middle;
+        } else {
+            return Some(record);
+        }
+    }
+
+    None
+}
+
+#[cfg(test)]
+pub(crate) mod tests {
+    use super::*;
+    use crate::sfnt::WriteBe;
+
+    /// Version 0 table from base glyphs (sorted) and their layers of glyph and palette index
+    pub(crate) fn build(base_glyphs: &[(u16, &[(u16, u16)])]) -> Vec<u8> {
+        let num_layers = base_glyphs.iter().map(|(_, l)| l.len()).sum::<usize>();
+        let layers_offset = 14 + base_glyphs.len() * BASE_RECORD_SIZE;
+        let mut colr = Vec::new();
+
+        colr.put_u16(0);
+        colr.put_u16(base_glyphs.len() as u16);
+        colr.put_u32(14);
+        colr.put_u32(layers_offset as u32);
+        colr.put_u16(num_layers as u16);
+
+        let mut first = 0;
+        for (glyph, layers) in base_glyphs {
+            colr.put_u16(*glyph);
+            colr.put_u16(first);
+            colr.put_u16(layers.len() as u16);
+            first += layers.len() as u16;
+        }
+        for layers in base_glyphs.iter().map(|(_, layers)| layers) {
+            for &(glyph, palette_index) in layers.iter() {
+                colr.put_u16(glyph);
+                colr.put_u16(palette_index);
+            }
+        }
+
+        colr
+    }
+
+    fn put_u24(data: &mut Vec<u8>, value: u32) {
+        data.extend_from_slice(&value.to_be_bytes()[1..]);
+    }
+
+    fn layer(glyph: u16, palette_index: u16, alpha: f32) -> Layer {
+        Layer {
+            glyph,
+            palette_index,
+            alpha,
+        }
+    }
+
+    #[test]
+    fn test_layers_v0() {
+        let colr = build(&[(5, &[(10, 0), (11, FOREGROUND)]), (9, &[(12, 1)])]);
+
+        assert_eq!(
+            layers(&colr, 5).unwrap(),
+            &[layer(10, 0, 1.0), layer(11, FOREGROUND, 1.0)]
+        );
+        assert_eq!(layers(&colr, 9).unwrap(), &[layer(12, 1, 1.0)]);
+        assert_eq!(layers(&colr, 7), None);
+    }
+
+    #[test]
+    fn test_layers_v1() {
+        let mut colr = Vec::new();
+        colr.put_u16(1);
+        colr.put_u16(0);
+        colr.put_u32(0);
+        colr.put_u32(0);
+        colr.put_u16(0);
+        colr.put_u32(34); // base glyph list
+        colr.put_u32(34 + 25); // layer list
+        colr.put_u32(0);
+        colr.put_u32(0);
+        colr.put_u32(0);
+
+        // base glyph list: glyph 5 with layers, glyph 7 reusing glyph 5
+        colr.put_u32(2);
+        colr.put_u16(5);
+        colr.put_u32(16);
+        colr.put_u16(7);
+        colr.put_u32(22);
+        colr.push(PAINT_COLR_LAYERS);
+        colr.push(3);
+        colr.put_u32(0);
+        colr.push(PAINT_COLR_GLYPH);
+        colr.put_u16(5);
+
+        // layer list: solid and gradient fills, and an unsupported transform
+        colr.put_u32(3);
+        colr.put_u32(16);
+        colr.put_u32(27);
+        colr.put_u32(58);
+        colr.push(PAINT_GLYPH);
+        put_u24(&mut colr, 6);
+        colr.put_u16(20);
+        colr.push(PAINT_SOLID);
+        colr.put_u16(3);
+        colr.put_u16(0x2000);
+        colr.push(PAINT_GLYPH);
+        put_u24(&mut colr, 6);
+        colr.put_u16(21);
+        colr.push(PAINT_GRADIENTS.0);
+        put_u24(&mut colr, 16);
+        colr.extend_from_slice(&[0; 12]);
+        colr.push(0);
+        colr.put_u16(1);
+        colr.put_u16(0);
+        colr.put_u16(4);
+        colr.put_u16(0x4000);
+        colr.push(12);
+
+        let expected = [layer(20, 3, 0.5), layer(21, 4, 1.0)];
+        assert_eq!(layers(&colr, 5).unwrap(), &expected);
+        assert_eq!(layers(&colr, 7).unwrap(), &expected);
+        assert_eq!(layers(&colr, 6), None);
+    }
+}
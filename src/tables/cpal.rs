@@ -0,0 +1,84 @@
+//! `CPAL` - color palettes of `COLR` glyphs
+use std::convert::TryInto;
+
+use crate::sfnt::Reader;
+
+/// Size of a color record: blue, green, red and alpha
+const COLOR_RECORD_SIZE: usize = 4;
+
+/// Number of palettes in the table
+pub(crate) fn palette_count(cpal: &[u8]) -> usize {
+    let mut r = Reader::new(cpal);
+
+    r.skip(4)
+        .and_then(|_| r.read_u16())
+        .map_or(0, |count| count as usize)
+}
+
+/// Colors of a palette as RGBA
+pub(crate) fn palette(cpal: &[u8], index: usize) -> Option<Vec<[u8; 4]>> {
+    let mut r = Reader::new(cpal);
+
+    let _version = r.read_u16()?;
+    let num_entries = r.read_u16()? as usize;
+    let num_palettes = r.read_u16()? as usize;
+    let _num_color_records = r.read_u16()?;
+    let color_records = r.read_u32()? as usize;
+    if index >= num_palettes {
+        return None;
+    }
+
+    r.skip(index * 2)?;
+    let first = r.read_u16()? as usize;
+
+    let mut r = Reader::at(cpal, color_records + first * COLOR_RECORD_SIZE)?;
+    (0..num_entries)
+        .map(|_| {
+            let [blue, green, red, alpha]: [u8; 4] = r.read_bytes(4)?.try_into().ok()?;
+            Some([red, green, blue, alpha])
+        })
+        .collect()
+}
+
+#[cfg(test)]
+pub(crate) mod tests {
+    use super::*;
+    use crate::sfnt::WriteBe;
+
+    /// Version 0 table with palettes of BGRA colors sharing their color records
+    pub(crate) fn build(palettes: &[&[[u8; 4]]]) -> Vec<u8> {
+        let num_entries = palettes[0].len();
+        let mut cpal = Vec::new();
+
+        cpal.put_u16(0);
+        cpal.put_u16(num_entries as u16);
+        cpal.put_u16(palettes.len() as u16);
+        cpal.put_u16((palettes.len() * num_entries) as u16);
+        cpal.put_u32(12 + palettes.len() as u32 * 2);
+        for index in 0..palettes.len() {
+            cpal.put_u16((index * num_entries) as u16);
+        }
+        for color in palettes.iter().flat_map(|palette| palette.iter()) {
+            cpal.extend_from_slice(color);
+        }
+
+        cpal
+    }
+
+    #[test]
+    fn test_palette() {
+        let cpal = build(&[
+            &[[0, 0, 255, 255], [255, 0, 0, 128]],
+            &[[1, 2, 3, 4], [5, 6, 7, 8]],
+        ]);
+
+        assert_eq!(palette_count(&cpal), 2);
+        assert_eq!(
+            palette(&cpal, 0).unwrap(),
+            &[[255, 0, 0, 255], [0, 0, 255, 128]]
+        );
+        assert_eq!(palette(&cpal, 1).unwrap(), &[[3, 2, 1, 4], [7, 6, 5, 8]]);
+        assert_eq!(palette(&cpal, 2), None);
+        assert_eq!(palette_count(&[]), 0);
+    }
+}
@@ -0,0 +1,77 @@
+//! `fvar` - font variations table
+use crate::sfnt::{Reader, Tag};
+
+/// Flag for axes that should not be exposed in user interfaces
+const HIDDEN_AXIS: u16 = 0x0001;
+
+#[derive(Debug, Clone, PartialEq)]
+pub(crate) struct Axis {
+    pub tag: Tag,
+    pub min: f32,
+    pub default: f32,
+    pub max: f32,
+    pub hidden: bool,
+}
+
+impl Axis {
+    /// Map a user-space coordinate to normalized -1.0..1.0 space (before `avar` mapping)
+    pub fn normalize(&self, value: f32) -> f32 {
+        let value = value.max(self.min).min(self.max);
+
+        if value < self.default {
+            if self.default == self.min {
+                0.0
+            } else {
+                -(self.default - value) / (self.default - self.min)
+            }
+        } else if value > self.default {
+            if self.default == self.max {
+                0.0
+            } else {
+                (value - self.default) / (self.max - self.default)
+            }
+        } else {
+            0.0
+        }
+    }
+}
+
+pub(crate) fn parse(data: &[u8]) -> Option<Vec<Axis>> {
+    let mut r = Reader::new(data);
+
+    let major_version = r.read_u16()?;
+    if major_version != 1 {
+        return None;
+    }
+
+    let _minor_version = r.read_u16()?;
+    let axes_offset = r.read_u16()? as usize;
+    let _reserved = r.read_u16()?;
+    let axis_count = r.read_u16()?;
+    let axis_size = r.read_u16()? as usize;
+
+    if axis_size < 20 {
+        return None;
+    }
+
+    let mut axes = Vec::with_capacity(axis_count as usize);
+    for i in 0..axis_count as usize {
+        let mut r = Reader::at(data, axes_offset + i * axis_size)?;
+
+        let tag = r.read_tag()?;
+        let min = r.read_fixed()?;
+        let default = r.read_fixed()?;
+        let max = r.read_fixed()?;
+        let flags = r.read_u16()?;
+
+        axes.push(Axis {
+            tag,
+            min: min.min(default),
+            default,
+            max: max.max(default),
+            hidden: flags & HIDDEN_AXIS != 0,
+        });
+    }
+
+    Some(axes)
+}
@@ -0,0 +1,479 @@
+//! `glyf` / `loca` - TrueType glyph outlines
+use crate::sfnt::{get_u16, Reader, WriteBe};
+
+const ON_CURVE_POINT: u8 = 0x01;
+const X_SHORT_VECTOR: u8 = 0x02;
+const Y_SHORT_VECTOR: u8 = 0x04;
+const REPEAT_FLAG: u8 = 0x08;
+const X_IS_SAME_OR_POSITIVE: u8 = 0x10;
+const Y_IS_SAME_OR_POSITIVE: u8 = 0x20;
+
+const ARG_1_AND_2_ARE_WORDS: u16 = 0x0001;
+const ARGS_ARE_XY_VALUES: u16 = 0x0002;
+const WE_HAVE_A_SCALE: u16 = 0x0008;
+const MORE_COMPONENTS: u16 = 0x0020;
+const WE_HAVE_AN_X_AND_Y_SCALE: u16 = 0x0040;
+const WE_HAVE_A_TWO_BY_TWO: u16 = 0x0080;
+const SCALED_COMPONENT_OFFSET: u16 = 0x0800;
+
+/// Maximum nesting of composite glyphs, guards against cyclic references
+const MAX_COMPONENT_DEPTH: usize = 8;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub(crate) struct Point {
+    pub x: f32,
+    pub y: f32,
+    pub on_curve: bool,
+}
+
+/// A glyph outline in font units, as a list of closed contours
+pub(crate) type Contours = Vec<Vec<Point>>;
+
+#[derive(Debug, Clone, PartialEq)]
+pub(crate) struct Component {
+    pub glyph: u16,
+    /// Offset (when `xy_offset`) or `(parent point, child point)` indices to match
+    pub arg1: i32,
+    pub arg2: i32,
+    pub xy_offset: bool,
+    pub scaled_offset: bool,
+    /// `[xx, xy, yx, yy]` transform
+    pub transform: [f32; 4],
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub(crate) enum GlyphData {
+    Empty,
+    Simple {
+        contours: Contours,
+        x_min: i16,
+    },
+    Composite {
+        components: Vec<Component>,
+        x_min: i16,
+    },
+}
+
+impl GlyphData {
+    pub fn x_min(&self) -> i16 {
+        match self {
+            GlyphData::Empty => 0,
+            GlyphData::Simple { x_min, .. } | GlyphData::Composite { x_min, .. } => *x_min,
+        }
+    }
+}
+
+/// `glyf` table together with its `loca` offsets
+pub(crate) struct Glyf<'a> {
+    glyf: &'a [u8],
+    offsets: Vec<usize>,
+}
+
+impl<'a> Glyf<'a> {
+    /// `index_to_loc_format` is read from `head`, `num_glyphs` from `maxp`
+    pub fn parse(
+        glyf: &'a [u8],
+        loca: &[u8],
+        index_to_loc_format: i16,
+        num_glyphs: u16,
+    ) -> Option<Self> {
+        let mut r = Reader::new(loca);
+        let mut offsets = Vec::with_capacity(num_glyphs as usize + 1);
+
+        for _ in 0..=num_glyphs {
+            let offset = match index_to_loc_format {
+                0 => r.read_u16()? as usize * 2,
+                _ => r.read_u32()? as usize,
+            };
+            offsets.push(offset.min(glyf.len()));
+        }
+
+        Some(Self { glyf, offsets })
+    }
+
+    pub fn glyph(&self, index: u16) -> Option<GlyphData> {
+        let index = index as usize;
+        let start = *self.offsets.get(index)?;
+        let end = *self.offsets.get(index + 1)?;
+
+        if end <= start {
+            return Some(GlyphData::Empty);
+        }
+
+        parse_glyph(&self.glyf[start..end])
+    }
+
+    /// `yMax` from the glyph header, `None` for empty glyphs
+    pub fn y_max(&self, index: u16) -> Option<i16> {
+        let index = index as usize;
+        let start = *self.offsets.get(index)?;
+        let end = *self.offsets.get(index + 1)?;
+
+        if end <= start {
+            return None;
+        }
+
+        get_u16(&self.glyf[start..end], 8).map(|v| v as i16)
+    }
+}
+
+fn parse_glyph(data: &[u8]) -> Option<GlyphData> {
+    let mut r = Reader::new(data);
+
+    let number_of_contours = r.read_i16()?;
+    let x_min = r.read_i16()?;
+    r.skip(6)?;
+
+    if number_of_contours >= 0 {
+        let contours = parse_simple(&mut r, number_of_contours as usize)?;
+        Some(GlyphData::Simple { contours, x_min })
+    } else {
+        let components = parse_composite(&mut r)?;
+        Some(GlyphData::Composite { components, x_min })
+    }
+}
+
+fn parse_simple(r: &mut Reader, number_of_contours: usize) -> Option<Contours> {
+    let mut end_points = Vec::with_capacity(number_of_contours);
+    for _ in 0..number_of_contours {
+        end_points.push(r.read_u16()? as usize);
+    }
+
+    let num_points = match end_points.last() {
+        Some(last) => last + 1,
+        None => return Some(Vec::new()),
+    };
+
+    let instruction_length = r.read_u16()? as usize;
+    r.skip(instruction_length)?;
+
+    let mut flags = Vec::with_capacity(num_points);
+    while flags.len() < num_points {
+        let flag = r.read_u8()?;
+        flags.push(flag);
+
+        if flag & REPEAT_FLAG != 0 {
+            let repeat = r.read_u8()?;
+            for _ in 0..repeat {
+                flags.push(flag);
+            }
+        }
+    }
+    flags.truncate(num_points);
+
+    let xs = parse_coordinates(r, &flags, X_SHORT_VECTOR, X_IS_SAME_OR_POSITIVE)?;
+    let ys = parse_coordinates(r, &flags, Y_SHORT_VECTOR, Y_IS_SAME_OR_POSITIVE)?;
+
+    let mut contours = Vec::with_capacity(number_of_contours);
+    let mut start = 0;
+    for end in end_points {
+        if end < start || end >= num_points {
+            return None;
+        }
+
+        contours.push(
+            (start..=end)
+                .map(|i| Point {
+                    x: xs[i] as f32,
+                    y: ys[i] as f32,
+                    on_curve: flags[i] & ON_CURVE_POINT != 0,
+                })
+                .collect(),
+        );
+        start = end + 1;
+    }
+
+    Some(contours)
+}
+
+fn parse_coordinates(r: &mut Reader, flags: &[u8], short: u8, same: u8) -> Option<Vec<i32>> {
+    let mut value = 0i32;
+    let mut coordinates = Vec::with_capacity(flags.len());
+
+    for flag in flags {
+        if flag & short != 0 {
+            let delta = r.read_u8()? as i32;
+            value += if flag & same != 0 { delta } else { -delta };
+        } else if flag & same == 0 {
+            value += r.read_i16()? as i32;
+        }
+
+        coordinates.push(value);
+    }
+
+    Some(coordinates)
+}
+
+fn parse_composite(r: &mut Reader) -> Option<Vec<Component>> {
+    let mut components = Vec::new();
+
+    loop {
+        let flags = r.read_u16()?;
+        let glyph = r.read_u16()?;
+
+        let (arg1, arg2) = match (
+            flags & ARG_1_AND_2_ARE_WORDS != 0,
+            flags & ARGS_ARE_XY_VALUES != 0,
+        ) {
+            (true, true) => (r.read_i16()? as i32, r.read_i16()? as i32),
+            (true, false) => (r.read_u16()? as i32, r.read_u16()? as i32),
+            (false, true) => (r.read_i8()? as i32, r.read_i8()? as i32),
+            (false, false) => (r.read_u8()? as i32, r.read_u8()? as i32),
+        };
+
+        let transform = if flags & WE_HAVE_A_SCALE != 0 {
+            let scale = r.read_f2dot14()?;
+            [scale, 0.0, 0.0, scale]
+        } else if flags & WE_HAVE_AN_X_AND_Y_SCALE != 0 {
+            let (x_scale, y_scale) = (r.read_f2dot14()?, r.read_f2dot14()?);
+            [x_scale, 0.0, 0.0, y_scale]
+        } else if flags & WE_HAVE_A_TWO_BY_TWO != 0 {
+            [
+                r.read_f2dot14()?,
+                r.read_f2dot14()?,
+                r.read_f2dot14()?,
+                r.read_f2dot14()?,
+            ]
+        } else {
+            [1.0, 0.0, 0.0, 1.0]
+        };
+
+        components.push(Component {
+            glyph,
+            arg1,
+            arg2,
+            xy_offset: flags & ARGS_ARE_XY_VALUES != 0,
+            scaled_offset: flags & SCALED_COMPONENT_OFFSET != 0,
+            transform,
+        });
+
+        if flags & MORE_COMPONENTS == 0 {
+            break;
+        }
+    }
+
+    Some(components)
+}
+
+/// Expand a glyph into contours. `lookup` provides (possibly modified) glyph data by index
+pub(crate) fn flatten<F>(lookup: &mut F, index: u16, depth: usize) -> Option<Contours>
+where
+    F: FnMut(u16) -> Option<GlyphData>,
+{
+    if depth > MAX_COMPONENT_DEPTH {
+        return None;
+    }
+
+    match lookup(index)? {
+        GlyphData::Empty => Some(Vec::new()),
+        GlyphData::Simple { contours, .. } => Some(contours),
+        GlyphData::Composite { components, .. } => {
+            let mut contours: Contours = Vec::new();
+
+            for component in components {
+                let child = flatten(lookup, component.glyph, depth + 1)?;
+                let [xx, xy, yx, yy] = component.transform;
+                let transform = |p: &Point| (xx * p.x + yx * p.y, xy * p.x + yy * p.y);
+
+                let (dx, dy) = if component.xy_offset {
+                    let (dx, dy) = (component.arg1 as f32, component.arg2 as f32);
+                    if component.scaled_offset {
+                        (xx * dx + yx * dy, xy * dx + yy * dy)
+                    } else {
+                        (dx, dy)
+                    }
+                } else {
+                    let parent = contours.iter().flatten().nth(component.arg1 as usize);
+                    let child = child.iter().flatten().nth(component.arg2 as usize);
+                    match (parent, child) {
+                        (Some(parent), Some(child)) => {
+                            let (cx, cy) = transform(child);
+                            (parent.x - cx, parent.y - cy)
+                        }
+                        _ => (0.0, 0.0),
+                    }
+                };
+
+                contours.extend(child.into_iter().map(|contour| {
+                    contour
+                        .iter()
+                        .map(|p| {
+                            let (x, y) = transform(p);
+                            Point {
+                                x: x + dx,
+                                y: y + dy,
+                                on_curve: p.on_curve,
+                            }
+                        })
+                        .collect()
+                }));
+            }
+
+            Some(contours)
+        }
+    }
+}
+
+/// Bounding box `(x_min, y_min, x_max, y_max)` of contours, rounded to font units
+pub(crate) fn bounds(contours: &[Vec<Point>]) -> Option<(i16, i16, i16, i16)> {
+    let mut points = contours.iter().flatten();
+    let first = points.next()?;
+
+    let (mut x_min, mut y_min, mut x_max, mut y_max) = (first.x, first.y, first.x, first.y);
+    for p in points {
+        x_min = x_min.min(p.x);
+        y_min = y_min.min(p.y);
+        x_max = x_max.max(p.x);
+        y_max = y_max.max(p.y);
+    }
+
+    Some((to_i16(x_min), to_i16(y_min), to_i16(x_max), to_i16(y_max)))
+}
+
+fn to_i16(value: f32) -> i16 {
+    value.round().max(i16::MIN as f32).min(i16::MAX as f32) as i16
+}
+
+/// Encode contours and hinting instructions as a simple glyph
+pub(crate) fn encode_simple(contours: &[Vec<Point>], instructions: &[u8]) -> Vec<u8> {
+    let contours = contours
+        .iter()
+        .filter(|c| !c.is_empty())
+        .cloned()
+        .collect::<Vec<_>>();
+
+    let (x_min, y_min, x_max, y_max) = match bounds(&contours) {
+        Some(bounds) => bounds,
+        None => return Vec::new(),
+    };
+
+    let mut out = Vec::new();
+    out.put_i16(contours.len() as i16);
+    out.put_i16(x_min);
+    out.put_i16(y_min);
+    out.put_i16(x_max);
+    out.put_i16(y_max);
+
+    let mut end = 0usize;
+    for contour in contours.iter() {
+        end += contour.len();
+        out.put_u16((end - 1) as u16);
+    }
+    out.put_u16(instructions.len() as u16);
+    out.extend_from_slice(instructions);
+
+    let (mut flags, mut xs, mut ys) = (Vec::new(), Vec::new(), Vec::new());
+    let (mut last_x, mut last_y) = (0i32, 0i32);
+
+    for p in contours.iter().flatten() {
+        let (x, y) = (to_i16(p.x) as i32, to_i16(p.y) as i32);
+        let mut flag = if p.on_curve { ON_CURVE_POINT } else { 0 };
+
+        flag |= encode_delta(x - last_x, &mut xs, X_SHORT_VECTOR, X_IS_SAME_OR_POSITIVE);
+        flag |= encode_delta(y - last_y, &mut ys, Y_SHORT_VECTOR, Y_IS_SAME_OR_POSITIVE);
+
+        flags.push(flag);
+        last_x = x;
+        last_y = y;
+    }
+
+    out.extend_from_slice(&flags);
+    out.extend_from_slice(&xs);
+    out.extend_from_slice(&ys);
+
+    out
+}
+
+fn encode_delta(delta: i32, out: &mut Vec<u8>, short: u8, same: u8) -> u8 {
+    if delta == 0 {
+        same
+    } else if delta.abs() < 256 {
+        out.push(delta.abs() as u8);
+        if delta > 0 {
+            short | same
+        } else {
+            short
+        }
+    } else {
+        out.put_i16(delta as i16);
+        0
+    }
+}
+
+/// Build `glyf` and long-format `loca` tables from encoded glyphs
+pub(crate) fn build_tables(glyphs: &[Vec<u8>]) -> (Vec<u8>, Vec<u8>) {
+    let mut glyf = Vec::new();
+    let mut loca = Vec::with_capacity((glyphs.len() + 1) * 4);
+
+    for glyph in glyphs {
+        loca.put_u32(glyf.len() as u32);
+        glyf.extend_from_slice(glyph);
+        glyf.resize((glyf.len() + 3) & !3, 0);
+    }
+    loca.put_u32(glyf.len() as u32);
+
+    (glyf, loca)
+}
+
+/// `head.indexToLocFormat`
+pub(crate) fn index_to_loc_format(head: &[u8]) -> Option<i16> {
+    get_u16(head, 50).map(|v| v as i16)
+}
+
+/// `maxp.numGlyphs`
+pub(crate) fn num_glyphs(maxp: &[u8]) -> Option<u16> {
+    get_u16(maxp, 4)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{sfnt::Sfnt, tests::read_font};
+
+    #[test]
+    fn test_encode_roundtrip() {
+        let data = read_font(None);
+        let sfnt = Sfnt::parse(&data).unwrap();
+
+        let num_glyphs = num_glyphs(sfnt.table(b"maxp").unwrap()).unwrap();
+        let glyf = Glyf::parse(
+            sfnt.table(b"glyf").unwrap(),
+            sfnt.table(b"loca").unwrap(),
+            index_to_loc_format(sfnt.table(b"head").unwrap()).unwrap(),
+            num_glyphs,
+        )
+        .unwrap();
+
+        assert_eq!(num_glyphs, 1485);
+
+        for index in 0..num_glyphs {
+            let contours = flatten(&mut |glyph| glyf.glyph(glyph), index, 0).unwrap();
+            let encoded = encode_simple(&contours, &[]);
+
+            if encoded.is_empty() {
+                assert!(contours.iter().all(|c| c.is_empty()));
+                continue;
+            }
+
+            let decoded = match parse_glyph(&encoded).unwrap() {
+                GlyphData::Simple { contours, .. } => contours,
+                _ => panic!("expected a simple glyph"),
+            };
+
+            let rounded = contours
+                .iter()
+                .map(|c| {
+                    c.iter()
+                        .map(|p| Point {
+                            x: p.x.round(),
+                            y: p.y.round(),
+                            on_curve: p.on_curve,
+                        })
+                        .collect::<Vec<_>>()
+                })
+                .collect::<Vec<_>>();
+
+            assert_eq!(decoded, rounded);
+        }
+    }
+}
@@ -0,0 +1,329 @@
+//! `GSUB` - glyph substitution: single, alternate and ligature lookups
+use crate::sfnt::{Reader, Tag};
+
+const SINGLE: u16 = 1;
+const ALTERNATE: u16 = 3;
+const LIGATURE: u16 = 4;
+const EXTENSION: u16 = 7;
+
+/// No required feature in a language system
+const NO_REQUIRED_FEATURE: u16 = 0xFFFF;
+
+/// Scripts whose default language system is used, in order of preference. Fonts without
+/// either use their first script
+const SCRIPTS: [&Tag; 2] = [b"DFLT", b"latn"];
+
+/// A glyph being substituted: glyph index and the index of its first character
+pub(crate) type Item = (u16, usize);
+
+pub(crate) struct Gsub<'a> {
+    data: &'a [u8],
+    feature_list: usize,
+    lookup_list: usize,
+    /// Offset of the language system whose features are used
+    lang_sys: Option<usize>,
+}
+
+impl<'a> Gsub<'a> {
+    pub(crate) fn parse(data: &'a [u8]) -> Option<Self> {
+        let mut r = Reader::new(data);
+
+        let major_version = r.read_u16()?;
+        if major_version != 1 {
+            return None;
+        }
+
+        let _minor_version = r.read_u16()?;
+        let script_list = r.read_u16()? as usize;
+        let feature_list = r.read_u16()? as usize;
+        let lookup_list = r.read_u16()? as usize;
+
+        Some(Self {
+            data,
+            feature_list,
+            lookup_list,
+            lang_sys: lang_sys(data, script_list),
+        })
+    }
+
+    /// Tags of the features of the language system, without duplicates
+    pub(crate) fn features(&self) -> Vec<Tag> {
+        let mut tags = Vec::new();
+
+        for index in self.feature_indices() {
+            if let Some((tag, _)) = self.feature(index) {
+                if !tags.contains(&tag) {
+                    tags.push(tag);
+                }
+            }
+        }
+
+        tags
+    }
+
+    /// Indices of the lookups of enabled features (and of the required feature), in the order
+    /// they are applied
+    pub(crate) fn lookups(&self, features: &[Tag]) -> Vec<u16> {
+        let mut lookups = Vec::new();
+
+        for index in self.feature_indices() {
+            let (tag, offset) = match self.feature(index) {
+                Some(feature) => feature,
+                None => continue,
+            };
+            if !features.contains(&tag) && Some(index) != self.required_feature() {
+                continue;
+            }
+
+            lookups.extend(read_u16_array(self.data, offset + 2).unwrap_or_default());
+        }
+
+        lookups.sort_unstable();
+        lookups.dedup();
+        lookups
+    }
+
+    /// Apply a lookup to each glyph of a sequence. Alternate substitutions pick the first
+    /// alternate
+    pub(crate) fn apply(&self, lookup: u16, glyphs: &mut Vec<Item>) -> Option<()> {
+        let lookup_offsets = read_u16_array(self.data, self.lookup_list)?;
+        let lookup = self.lookup_list + *lookup_offsets.get(lookup as usize)? as usize;
+
+        let mut r = Reader::at(self.data, lookup)?;
+        let lookup_type = r.read_u16()?;
+        let _lookup_flag = r.read_u16()?;
+        let subtables = read_u16_array(self.data, lookup + 4)?
+            .into_iter()
+            .filter_map(|offset| {
+                resolve_extension(self.data, lookup_type, lookup + offset as usize)
+            })
+            .collect::<Vec<_>>();
+
+        // the first subtable which covers a glyph applies. Ligatures replace the glyphs after
+        // the first one, so the next glyph is the one following them
+        let mut index = 0;
+        while index < glyphs.len() {
+            for &(lookup_type, subtable) in &subtables {
+                if apply_subtable(self.data, lookup_type, subtable, glyphs, index).is_some() {
+                    break;
+                }
+            }
+            index += 1;
+        }
+
+        Some(())
+    }
+
+    fn feature_indices(&self) -> Vec<u16> {
+        let mut indices = self
+            .lang_sys
+            .and_then(|offset| read_u16_array(self.data, offset + 4))
+            .unwrap_or_default();
+
+        if let Some(required) = self.required_feature() {
+            indices.insert(0, required);
+        }
+
+        indices
+    }
+
+    fn required_feature(&self) -> Option<u16> {
+        let mut r = Reader::at(self.data, self.lang_sys? + 2)?;
+
+        Some(r.read_u16()?).filter(|&index| index != NO_REQUIRED_FEATURE)
+    }
+
+    /// Tag and offset of a feature table
+    fn feature(&self, index: u16) -> Option<(Tag, usize)> {
+        let mut r = Reader::at(self.data, self.feature_list + 2 + index as usize * 6)?;
+        let tag = r.read_tag()?;
+        let offset = self.feature_list + r.read_u16()? as usize;
+
+        Some((tag, offset))
+    }
+}
+
+/// Offset of the default language system of the preferred script, or of its first language
+/// system
+fn lang_sys(data: &[u8], script_list: usize) -> Option<usize> {
+    let mut r = Reader::at(data, script_list)?;
+    let count = r.read_u16()?;
+
+    let mut scripts = Vec::with_capacity(count as usize);
+    for _ in 0..count {
+        scripts.push((r.read_tag()?, script_list + r.read_u16()? as usize));
+    }
+
+    let script = SCRIPTS
+        .iter()
+        .find_map(|tag| scripts.iter().find(|(t, _)| t == *tag))
+        .or_else(|| scripts.first())?
+        .1;
+
+    let mut r = Reader::at(data, script)?;
+    let default = r.read_u16()?;
+    if default != 0 {
+        return Some(script + default as usize);
+    }
+
+    // first language system record: tag, then offset
+    let _count = r.read_u16()?;
+    let _tag = r.read_tag()?;
+    let first = r.read_u16()?;
+
+    Some(script + first as usize)
+}
+
+/// Type and offset of a subtable, following extension subtables to the actual one
+fn resolve_extension(data: &[u8], lookup_type: u16, subtable: usize) -> Option<(u16, usize)> {
+    if lookup_type != EXTENSION {
+        return Some((lookup_type, subtable));
+    }
+
+    let mut r = Reader::at(data, subtable)?;
+    let _format = r.read_u16()?;
+    let lookup_type = r.read_u16()?;
+    let offset = r.read_u32()? as usize;
+
+    Some((lookup_type, subtable + offset))
+}
+
+/// Apply a subtable at a glyph. `None` if the subtable does not cover it
+fn apply_subtable(
+    data: &[u8],
+    lookup_type: u16,
+    subtable: usize,
+    glyphs: &mut Vec<Item>,
+    index: usize,
+) -> Option<()> {
+    let mut r = Reader::at(data, subtable)?;
+    let format = r.read_u16()?;
+    let coverage_index = coverage(data, subtable + r.read_u16()? as usize, glyphs[index].0)?;
+
+    match (lookup_type, format) {
+        (SINGLE, 1) => {
+            let delta = r.read_i16()?;
+            glyphs[index].0 = glyphs[index].0.wrapping_add(delta as u16);
+        }
+        (SINGLE, 2) => {
+            let substitutes = read_u16_array(data, r.pos())?;
+            glyphs[index].0 = *substitutes.get(coverage_index)?;
+        }
+        (ALTERNATE, 1) => {
+            let sets = read_u16_array(data, r.pos())?;
+            let set = subtable + *sets.get(coverage_index)? as usize;
+            glyphs[index].0 = *read_u16_array(data, set)?.first()?;
+        }
+        (LIGATURE, 1) => {
+            let sets = read_u16_array(data, r.pos())?;
+            let set = subtable + *sets.get(coverage_index)? as usize;
+
+            // the first ligature whose components follow the glyph
+            let ligature = read_u16_array(data, set)?.into_iter().find_map(|offset| {
+                let mut r = Reader::at(data, set + offset as usize)?;
+                let ligature = r.read_u16()?;
+                let count = r.read_u16()? as usize;
+
+                let components = glyphs.get(index + 1..index + count.max(1))?;
+                for component in components {
+                    if r.read_u16()? != component.0 {
+                        return None;
+                    }
+                }
+
+                Some((ligature, count.max(1)))
+            })?;
+
+            glyphs[index].0 = ligature.0;
+            glyphs.drain(index + 1..index + ligature.1);
+        }
+        _ => return None,
+    }
+
+    Some(())
+}
+
+/// Index of a glyph in a coverage table
+fn coverage(data: &[u8], offset: usize, glyph: u16) -> Option<usize> {
+    let mut r = Reader::at(data, offset)?;
+    let format = r.read_u16()?;
+    let count = r.read_u16()?;
+
+    match format {
+        1 => (0..count as usize).find(|_| r.read_u16() == Some(glyph)),
+        2 => {
+            for _ in 0..count {
+                let start = r.read_u16()?;
+                let end = r.read_u16()?;
+                let start_index = r.read_u16()?;
+
+                if (start..=end).contains(&glyph) {
+                    return Some((start_index + (glyph - start)) as usize);
+                }
+            }
+
+            None
+        }
+        _ => None,
+    }
+}
+
+/// A count followed by as many `u16` values
+fn read_u16_array(data: &[u8], offset: usize) -> Option<Vec<u16>> {
+    let mut r = Reader::at(data, offset)?;
+    let count = r.read_u16()?;
+
+    (0..count).map(|_| r.read_u16()).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{sfnt::Sfnt, tests::read_font};
+
+    /// Glyph indices of `0`, `f`, `i` and `l`, and their substitutes in FiraMono
+    const ZERO: u16 = 898;
+    const F: u16 = 169;
+    const I: u16 = 178;
+    const L: u16 = 196;
+
+    fn substitute(gsub: &Gsub, features: &[&Tag], glyphs: &[u16]) -> Vec<Item> {
+        let features = features.iter().map(|&&tag| tag).collect::<Vec<_>>();
+        let mut items = glyphs.iter().enumerate().map(|(i, &g)| (g, i)).collect();
+
+        for lookup in gsub.lookups(&features) {
+            gsub.apply(lookup, &mut items).unwrap();
+        }
+
+        items
+    }
+
+    #[test]
+    fn test_substitutions() {
+        let font = read_font(None);
+        let gsub = Gsub::parse(Sfnt::parse(&font).unwrap().table(b"GSUB").unwrap()).unwrap();
+
+        let features = gsub.features();
+        for tag in &[b"aalt", b"dlig", b"onum", b"zero"] {
+            assert!(features.contains(tag));
+        }
+        assert!(!features.contains(b"locl"));
+
+        // ligatures keep the index of their first character
+        let glyphs = [F, I, ZERO, F, L, F];
+        assert_eq!(
+            substitute(&gsub, &[b"dlig"], &glyphs),
+            &[(273, 0), (ZERO, 2), (274, 3), (F, 5)]
+        );
+
+        // single substitutions, applied in lookup order
+        assert_eq!(substitute(&gsub, &[b"zero"], &[ZERO]), &[(918, 0)]);
+        assert_eq!(substitute(&gsub, &[b"onum"], &[ZERO]), &[(908, 0)]);
+        assert_eq!(substitute(&gsub, &[b"zero", b"onum"], &[ZERO]), &[(919, 0)]);
+
+        // the first alternate
+        assert_eq!(substitute(&gsub, &[b"aalt"], &[ZERO]), &[(957, 0)]);
+
+        assert_eq!(substitute(&gsub, &[], &glyphs[..2]), &[(F, 0), (I, 1)]);
+    }
+}
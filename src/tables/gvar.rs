@@ -0,0 +1,410 @@
+//! `gvar` - glyph variations table
+use crate::sfnt::Reader;
+
+const SHARED_POINT_NUMBERS: u16 = 0x8000;
+const COUNT_MASK: u16 = 0x0FFF;
+
+const EMBEDDED_PEAK_TUPLE: u16 = 0x8000;
+const INTERMEDIATE_REGION: u16 = 0x4000;
+const PRIVATE_POINT_NUMBERS: u16 = 0x2000;
+const TUPLE_INDEX_MASK: u16 = 0x0FFF;
+
+const POINTS_ARE_WORDS: u8 = 0x80;
+const POINT_RUN_COUNT_MASK: u8 = 0x7F;
+
+const DELTAS_ARE_ZERO: u8 = 0x80;
+const DELTAS_ARE_WORDS: u8 = 0x40;
+const DELTA_RUN_COUNT_MASK: u8 = 0x3F;
+
+/// Number of phantom points appended to each glyph's points
+pub(crate) const PHANTOM_POINTS: usize = 4;
+
+pub(crate) struct Gvar<'a> {
+    data: &'a [u8],
+    axis_count: usize,
+    shared_tuples: Vec<Vec<f32>>,
+    /// Absolute offsets of glyph variation data, `glyph_count + 1` entries
+    offsets: Vec<usize>,
+}
+
+impl<'a> Gvar<'a> {
+    pub fn parse(data: &'a [u8]) -> Option<Self> {
+        let mut r = Reader::new(data);
+
+        let major_version = r.read_u16()?;
+        if major_version != 1 {
+            return None;
+        }
+
+        let _minor_version = r.read_u16()?;
+        let axis_count = r.read_u16()? as usize;
+        let shared_tuple_count = r.read_u16()?;
+        let shared_tuples_offset = r.read_u32()? as usize;
+        let glyph_count = r.read_u16()?;
+        let flags = r.read_u16()?;
+        let array_offset = r.read_u32()? as usize;
+
+        let mut offsets = Vec::with_capacity(glyph_count as usize + 1);
+        for _ in 0..=glyph_count {
+            let offset = if flags & 1 != 0 {
+                r.read_u32()? as usize
+            } else {
+                r.read_u16()? as usize * 2
+            };
+            offsets.push((array_offset + offset).min(data.len()));
+        }
+
+        let mut r = Reader::at(data, shared_tuples_offset)?;
+        let mut shared_tuples = Vec::with_capacity(shared_tuple_count as usize);
+        for _ in 0..shared_tuple_count {
+            shared_tuples.push(read_tuple(&mut r, axis_count)?);
+        }
+
+        Some(Self {
+            data,
+            axis_count,
+            shared_tuples,
+            offsets,
+        })
+    }
+
+    /// Compute the accumulated deltas of a glyph at normalized `coords`
+    ///
+    /// `points` are the original point coordinates including the trailing phantom points. When
+    /// `contour_ends` is given (simple glyphs), points without explicit deltas are interpolated.
+    /// Returns `None` for malformed data; glyphs without variations get all-zero deltas.
+    pub fn deltas(
+        &self,
+        glyph: u16,
+        coords: &[f32],
+        points: &[(f32, f32)],
+        contour_ends: Option<&[usize]>,
+    ) -> Option<Vec<(f32, f32)>> {
+        let mut deltas = vec![(0.0, 0.0); points.len()];
+
+        let glyph = glyph as usize;
+        let (start, end) = match (self.offsets.get(glyph), self.offsets.get(glyph + 1)) {
+            (Some(&start), Some(&end)) if end > start => (start, end),
+            _ => return Some(deltas),
+        };
+
+        let data = &self.data[start..end];
+        let mut r = Reader::new(data);
+
+        let tuple_count = r.read_u16()?;
+        let data_offset = r.read_u16()? as usize;
+
+        let mut headers = Vec::with_capacity((tuple_count & COUNT_MASK) as usize);
+        for _ in 0..tuple_count & COUNT_MASK {
+            let size = r.read_u16()? as usize;
+            let tuple_index = r.read_u16()?;
+
+            let peak = if tuple_index & EMBEDDED_PEAK_TUPLE != 0 {
+                read_tuple(&mut r, self.axis_count)?
+            } else {
+                self.shared_tuples
+                    .get((tuple_index & TUPLE_INDEX_MASK) as usize)?
+                    .clone()
+            };
+
+            let region = if tuple_index & INTERMEDIATE_REGION != 0 {
+                let start = read_tuple(&mut r, self.axis_count)?;
+                let end = read_tuple(&mut r, self.axis_count)?;
+                Some((start, end))
+            } else {
+                None
+            };
+
+            headers.push((size, tuple_index, peak, region));
+        }
+
+        let mut serialized = Reader::at(data, data_offset)?;
+        let shared_points = if tuple_count & SHARED_POINT_NUMBERS != 0 {
+            parse_points(&mut serialized)?
+        } else {
+            None
+        };
+
+        let mut offset = serialized.pos();
+        for (size, tuple_index, peak, region) in headers {
+            let chunk = data.get(offset..offset + size)?;
+            offset += size;
+
+            let scalar = tuple_scalar(coords, &peak, region.as_ref());
+            if scalar == 0.0 {
+                continue;
+            }
+
+            let mut r = Reader::new(chunk);
+            let private_points;
+            let tuple_points = if tuple_index & PRIVATE_POINT_NUMBERS != 0 {
+                private_points = parse_points(&mut r)?;
+                &private_points
+            } else {
+                &shared_points
+            };
+
+            let count = match tuple_points {
+                Some(indices) => indices.len(),
+                None => points.len(),
+            };
+
+            let xs = parse_deltas(&mut r, count)?;
+            let ys = parse_deltas(&mut r, count)?;
+
+            let tuple_deltas = match tuple_points {
+                None => xs
+                    .iter()
+                    .zip(ys.iter())
+                    .map(|(&x, &y)| Some((x as f32, y as f32)))
+                    .collect::<Vec<_>>(),
+                Some(indices) => {
+                    let mut sparse = vec![None; points.len()];
+                    for (i, &index) in indices.iter().enumerate() {
+                        if let Some(delta) = sparse.get_mut(index as usize) {
+                            *delta = Some((xs[i] as f32, ys[i] as f32));
+                        }
+                    }
+
+                    if let Some(contour_ends) = contour_ends {
+                        interpolate_untouched(&mut sparse, points, contour_ends);
+                    }
+                    sparse
+                }
+            };
+
+            for (delta, tuple_delta) in deltas.iter_mut().zip(tuple_deltas) {
+                if let Some((x, y)) = tuple_delta {
+                    delta.0 += x * scalar;
+                    delta.1 += y * scalar;
+                }
+            }
+        }
+
+        Some(deltas)
+    }
+}
+
+fn read_tuple(r: &mut Reader, axis_count: usize) -> Option<Vec<f32>> {
+    (0..axis_count).map(|_| r.read_f2dot14()).collect()
+}
+
+/// Packed point numbers. `None` means that the deltas apply to all points
+fn parse_points(r: &mut Reader) -> Option<Option<Vec<u16>>> {
+    let first = r.read_u8()?;
+    if first == 0 {
+        return Some(None);
+    }
+
+    let count = if first & POINTS_ARE_WORDS != 0 {
+        ((first & POINT_RUN_COUNT_MASK) as usize) << 8 | r.read_u8()? as usize
+    } else {
+        first as usize
+    };
+
+    let mut points = Vec::with_capacity(count);
+    let mut point = 0u16;
+    while points.len() < count {
+        let control = r.read_u8()?;
+        let run = (control & POINT_RUN_COUNT_MASK) as usize + 1;
+
+        for _ in 0..run.min(count - points.len()) {
+            let delta = if control & POINTS_ARE_WORDS != 0 {
+                r.read_u16()?
+            } else {
+                r.read_u8()? as u16
+            };
+
+            point = point.wrapping_add(delta);
+            points.push(point);
+        }
+    }
+
+    Some(Some(points))
+}
+
+fn parse_deltas(r: &mut Reader, count: usize) -> Option<Vec<i16>> {
+    let mut deltas = Vec::with_capacity(count);
+
+    while deltas.len() < count {
+        let control = r.read_u8()?;
+        let run = ((control & DELTA_RUN_COUNT_MASK) as usize + 1).min(count - deltas.len());
+
+        for _ in 0..run {
+            let delta = if control & DELTAS_ARE_ZERO != 0 {
+                0
+            } else if control & DELTAS_ARE_WORDS != 0 {
+                r.read_i16()?
+            } else {
+                r.read_i8()? as i16
+            };
+
+            deltas.push(delta);
+        }
+    }
+
+    Some(deltas)
+}
+
+/// Contribution of a tuple variation, or of a variation region with explicit `region` bounds,
+/// at normalized `coords`
+pub(crate) fn tuple_scalar(
+    coords: &[f32],
+    peak: &[f32],
+    region: Option<&(Vec<f32>, Vec<f32>)>,
+) -> f32 {
+    let mut scalar = 1.0;
+
+    for (i, &peak) in peak.iter().enumerate() {
+        let coord = coords.get(i).copied().unwrap_or(0.0);
+
+        if peak == 0.0 || coord == peak {
+            continue;
+        }
+
+        match region {
+            Some((start, end)) => {
+                let (start, end) = (start[i], end[i]);
+                if start > peak || peak > end || (start < 0.0 && end > 0.0) {
+                    continue;
+                }
+
+                if coord < start || coord > end {
+                    return 0.0;
+                }
+
+                if coord < peak {
+                    scalar *= (coord - start) / (peak - start);
+                } else {
+                    scalar *= (end - coord) / (end - peak);
+                }
+            }
+            None => {
+                if coord == 0.0 || coord < peak.min(0.0) || coord > peak.max(0.0) {
+                    return 0.0;
+                }
+
+                scalar *= coord / peak;
+            }
+        }
+    }
+
+    scalar
+}
+
+/// Infer deltas for points without explicit deltas (IUP), contour by contour
+fn interpolate_untouched(
+    deltas: &mut [Option<(f32, f32)>],
+    points: &[(f32, f32)],
+    contour_ends: &[usize],
+) {
+    let mut start = 0;
+
+    for &end in contour_ends {
+        if end < start || end >= deltas.len() {
+            return;
+        }
+
+        let touched = (start..=end)
+            .filter(|&i| deltas[i].is_some())
+            .collect::<Vec<_>>();
+
+        if touched.len() == 1 {
+            let delta = deltas[touched[0]];
+            for delta_ in deltas[start..=end].iter_mut() {
+                *delta_ = delta;
+            }
+        } else if touched.len() > 1 {
+            for (n, &prev) in touched.iter().enumerate() {
+                let next = touched[(n + 1) % touched.len()];
+                let (d1, d2) = (deltas[prev].unwrap(), deltas[next].unwrap());
+
+                let mut i = if prev == end { start } else { prev + 1 };
+                while i != next {
+                    let x = interpolate(points[i].0, points[prev].0, points[next].0, d1.0, d2.0);
+                    let y = interpolate(points[i].1, points[prev].1, points[next].1, d1.1, d2.1);
+                    deltas[i] = Some((x, y));
+
+                    i = if i == end { start } else { i + 1 };
+                }
+            }
+        }
+
+        start = end + 1;
+    }
+}
+
+fn interpolate(p: f32, in1: f32, in2: f32, d1: f32, d2: f32) -> f32 {
+    if in1 == in2 {
+        return if d1 == d2 { d1 } else { 0.0 };
+    }
+
+    let (in1, in2, d1, d2) = if in1 > in2 {
+        (in2, in1, d2, d1)
+    } else {
+        (in1, in2, d1, d2)
+    };
+
+    if p <= in1 {
+        d1
+    } else if p >= in2 {
+        d2
+    } else {
+        d1 + (p - in1) * (d2 - d1) / (in2 - in1)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_points() {
+        assert_eq!(parse_points(&mut Reader::new(&[0])), Some(None));
+
+        // 3 points as bytes: 1, 1+2, 1+2+4
+        let data = [3, 0x02, 1, 2, 4];
+        assert_eq!(
+            parse_points(&mut Reader::new(&data)),
+            Some(Some(vec![1, 3, 7]))
+        );
+
+        // 2 points as words: 300, 300+300
+        let data = [2, 0x81, 0x01, 0x2C, 0x01, 0x2C];
+        assert_eq!(
+            parse_points(&mut Reader::new(&data)),
+            Some(Some(vec![300, 600]))
+        );
+    }
+
+    #[test]
+    fn test_parse_deltas() {
+        // 2 zero deltas, 2 byte deltas, 1 word delta
+        let data = [0x81, 0x01, 5, 0xFB, 0x40, 0x01, 0x00];
+        assert_eq!(
+            parse_deltas(&mut Reader::new(&data), 5),
+            Some(vec![0, 0, 5, -5, 256])
+        );
+    }
+
+    #[test]
+    fn test_tuple_scalar() {
+        assert_eq!(tuple_scalar(&[0.5], &[1.0], None), 0.5);
+        assert_eq!(tuple_scalar(&[-0.5], &[1.0], None), 0.0);
+        assert_eq!(tuple_scalar(&[1.0, 0.25], &[1.0, 0.5], None), 0.5);
+
+        let region = (vec![0.0], vec![1.0]);
+        assert_eq!(tuple_scalar(&[0.75], &[0.5], Some(&region)), 0.5);
+    }
+
+    #[test]
+    fn test_interpolate_untouched() {
+        let points = [(0.0, 0.0), (50.0, 0.0), (100.0, 0.0), (50.0, 100.0)];
+        let mut deltas = [Some((10.0, 0.0)), None, Some((20.0, 10.0)), None];
+
+        interpolate_untouched(&mut deltas, &points, &[3]);
+
+        assert_eq!(deltas[1], Some((15.0, 0.0)));
+        assert_eq!(deltas[3], Some((15.0, 0.0)));
+    }
+}
@@ -0,0 +1,60 @@
+//! `hmtx` / `hhea` - horizontal metrics
+use crate::sfnt::{get_u16, set_u16, Reader, WriteBe};
+
+/// Offset of `numberOfHMetrics` in `hhea` (same layout for `numOfLongVerMetrics` in `vhea`)
+const NUMBER_OF_METRICS_OFFSET: usize = 34;
+
+/// Offset of `advanceWidthMax` in `hhea`
+const ADVANCE_MAX_OFFSET: usize = 10;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub(crate) struct Metric {
+    pub advance: u16,
+    pub side_bearing: i16,
+}
+
+/// Parse `hmtx` (or `vmtx`) into a metric for each glyph
+pub(crate) fn parse(mtx: &[u8], hea: &[u8], num_glyphs: u16) -> Option<Vec<Metric>> {
+    let number_of_metrics = get_u16(hea, NUMBER_OF_METRICS_OFFSET)?.min(num_glyphs);
+    if number_of_metrics == 0 {
+        return None;
+    }
+
+    let mut r = Reader::new(mtx);
+    let mut metrics = Vec::with_capacity(num_glyphs as usize);
+
+    for _ in 0..number_of_metrics {
+        metrics.push(Metric {
+            advance: r.read_u16()?,
+            side_bearing: r.read_i16()?,
+        });
+    }
+
+    let last_advance = metrics[metrics.len() - 1].advance;
+    for _ in number_of_metrics..num_glyphs {
+        metrics.push(Metric {
+            advance: last_advance,
+            side_bearing: r.read_i16().unwrap_or(0),
+        });
+    }
+
+    Some(metrics)
+}
+
+/// Build full-length `hmtx` (or `vmtx`) and the matching updated `hhea` (or `vhea`)
+pub(crate) fn build(metrics: &[Metric], hea: &[u8]) -> (Vec<u8>, Vec<u8>) {
+    let mut mtx = Vec::with_capacity(metrics.len() * 4);
+    for metric in metrics {
+        mtx.put_u16(metric.advance);
+        mtx.put_i16(metric.side_bearing);
+    }
+
+    let mut hea = hea.to_vec();
+    if hea.len() >= NUMBER_OF_METRICS_OFFSET + 2 {
+        let advance_max = metrics.iter().map(|m| m.advance).max().unwrap_or(0);
+        set_u16(&mut hea, ADVANCE_MAX_OFFSET, advance_max);
+        set_u16(&mut hea, NUMBER_OF_METRICS_OFFSET, metrics.len() as u16);
+    }
+
+    (mtx, hea)
+}
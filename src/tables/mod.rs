@@ -0,0 +1,14 @@
+//! Parsers for sfnt tables that are not exposed by the C library
+pub(crate) mod avar;
+pub(crate) mod cff;
+pub(crate) mod cmap;
+pub(crate) mod colr;
+pub(crate) mod cpal;
+pub(crate) mod fvar;
+pub(crate) mod glyf;
+pub(crate) mod gsub;
+pub(crate) mod gvar;
+pub(crate) mod hmtx;
+pub(crate) mod mvar;
+pub(crate) mod name;
+pub(crate) mod post;
@@ -0,0 +1,216 @@
+//! `MVAR` - metrics variations: deltas of font-wide metrics (ascender, underline, strikeout...)
+//! from an item variation store
+use crate::{
+    sfnt::{get_u16, set_u16, Reader, Tag},
+    tables::gvar,
+};
+
+/// Size of a value record: value tag, outer and inner delta set index
+const VALUE_RECORD_SIZE: usize = 8;
+
+/// The `i16` metrics varied by `MVAR`: value tag, then the table and offset of the value
+const METRICS: [(&Tag, &Tag, usize); 23] = [
+    (b"hasc", b"OS/2", 68),
+    (b"hdsc", b"OS/2", 70),
+    (b"hlgp", b"OS/2", 72),
+    (b"xhgt", b"OS/2", 86),
+    (b"cpht", b"OS/2", 88),
+    (b"sbxs", b"OS/2", 10),
+    (b"sbys", b"OS/2", 12),
+    (b"sbxo", b"OS/2", 14),
+    (b"sbyo", b"OS/2", 16),
+    (b"spxs", b"OS/2", 18),
+    (b"spys", b"OS/2", 20),
+    (b"spxo", b"OS/2", 22),
+    (b"spyo", b"OS/2", 24),
+    (b"strs", b"OS/2", 26),
+    (b"stro", b"OS/2", 28),
+    (b"hcrs", b"hhea", 18),
+    (b"hcrn", b"hhea", 20),
+    (b"hcof", b"hhea", 22),
+    (b"vasc", b"vhea", 4),
+    (b"vdsc", b"vhea", 6),
+    (b"vlgp", b"vhea", 8),
+    (b"undo", b"post", 8),
+    (b"unds", b"post", 10),
+];
+
+/// Deltas of the metrics at normalized `coords`, by value tag. Records whose delta set is
+/// missing are skipped
+pub(crate) fn deltas(mvar: &[u8], coords: &[f32]) -> Option<Vec<(Tag, f32)>> {
+    let mut r = Reader::new(mvar);
+    let _version = r.read_u32()?;
+    let _reserved = r.read_u16()?;
+    let record_size = r.read_u16()? as usize;
+    let record_count = r.read_u16()? as usize;
+    let store = r.read_u16()? as usize;
+
+    if record_size < VALUE_RECORD_SIZE || store == 0 {
+        return None;
+    }
+
+    let records = r.pos();
+    let deltas = (0..record_count)
+        .filter_map(|i| {
+            let mut r = Reader::at(mvar, records + i * record_size)?;
+            let tag = r.read_tag()?;
+            let outer = r.read_u16()?;
+            let inner = r.read_u16()?;
+
+            Some((tag, item_delta(mvar, store, outer, inner, coords)?))
+        })
+        .collect();
+
+    Some(deltas)
+}
+
+/// Add the deltas of the metrics stored in `table`, rounded to font units
+pub(crate) fn apply(deltas: &[(Tag, f32)], tag: &Tag, table: &mut [u8]) {
+    for (value_tag, delta) in deltas {
+        let metrics = METRICS
+            .iter()
+            .filter(|(value, table, _)| *value == value_tag && *table == tag);
+
+        for &(_, _, offset) in metrics {
+            if let Some(value) = get_u16(table, offset) {
+                let value = (value as i16 as f32 + delta).round();
+                let value = value.max(i16::MIN as f32).min(i16::MAX as f32) as i16;
+                set_u16(table, offset, value as u16);
+            }
+        }
+    }
+}
+
+/// Delta of an item (delta set) of the item variation store at `store`
+fn item_delta(data: &[u8], store: usize, outer: u16, inner: u16, coords: &[f32]) -> Option<f32> {
+    let mut r = Reader::at(data, store)?;
+    let _format = r.read_u16()?;
+    let region_list = store + r.read_u32()? as usize;
+    let data_count = r.read_u16()?;
+    if outer >= data_count {
+        return None;
+    }
+
+    r.skip(outer as usize * 4)?;
+    let mut r = Reader::at(data, store + r.read_u32()? as usize)?;
+    let item_count = r.read_u16()?;
+    let word_delta_count = r.read_u16()?;
+    let region_index_count = r.read_u16()? as usize;
+
+    let regions = (0..region_index_count)
+        .map(|_| r.read_u16())
+        .collect::<Option<Vec<_>>>()?;
+
+    // the first `word_count` deltas of a row are words, the others bytes; double sizes with
+    // long words
+    let long_words = word_delta_count & 0x8000 != 0;
+    let word_count = (word_delta_count & 0x7FFF) as usize;
+    if inner >= item_count || word_count > region_index_count {
+        return None;
+    }
+
+    let (word_size, short_size) = if long_words { (4, 2) } else { (2, 1) };
+    let row_size = word_count * word_size + (region_index_count - word_count) * short_size;
+    r.skip(inner as usize * row_size)?;
+
+    let mut delta = 0.0;
+    for (i, &region) in regions.iter().enumerate() {
+        let value = match (i < word_count, long_words) {
+            (true, true) => r.read_i32()? as f32,
+            (true, false) | (false, true) => r.read_i16()? as f32,
+            (false, false) => r.read_i8()? as f32,
+        };
+
+        delta += value * region_scalar(data, region_list, region, coords)?;
+    }
+
+    Some(delta)
+}
+
+/// Contribution of a variation region at normalized `coords`
+fn region_scalar(data: &[u8], region_list: usize, region: u16, coords: &[f32]) -> Option<f32> {
+    let mut r = Reader::at(data, region_list)?;
+    let axis_count = r.read_u16()? as usize;
+    let region_count = r.read_u16()?;
+    if region >= region_count {
+        return None;
+    }
+
+    r.skip(region as usize * axis_count * 6)?;
+
+    let (mut start, mut peak, mut end) = (Vec::new(), Vec::new(), Vec::new());
+    for _ in 0..axis_count {
+        start.push(r.read_f2dot14()?);
+        peak.push(r.read_f2dot14()?);
+        end.push(r.read_f2dot14()?);
+    }
+
+    Some(gvar::tuple_scalar(coords, &peak, Some(&(start, end))))
+}
+
+#[cfg(test)]
+pub(crate) mod tests {
+    use super::*;
+    use crate::sfnt::WriteBe;
+
+    /// Table of one axis, with the deltas of metrics at the maximum of the axis
+    pub(crate) fn build(deltas: &[(&Tag, i16)]) -> Vec<u8> {
+        let store = 12 + deltas.len() * VALUE_RECORD_SIZE;
+
+        let mut mvar = Vec::new();
+        mvar.put_u32(0x0001_0000);
+        mvar.put_u16(0);
+        mvar.put_u16(VALUE_RECORD_SIZE as u16);
+        mvar.put_u16(deltas.len() as u16);
+        mvar.put_u16(store as u16);
+        for (i, (tag, _)) in deltas.iter().enumerate() {
+            mvar.extend_from_slice(*tag);
+            mvar.put_u16(0);
+            mvar.put_u16(i as u16);
+        }
+
+        // store: format, region list offset, one item variation data
+        mvar.put_u16(1);
+        mvar.put_u32(12);
+        mvar.put_u16(1);
+        mvar.put_u32(22);
+
+        // region list: one axis, one region peaking at the maximum
+        mvar.put_u16(1);
+        mvar.put_u16(1);
+        for value in &[0, 0x4000, 0x4000] {
+            mvar.put_u16(*value);
+        }
+
+        // item variation data: a word delta for each item
+        mvar.put_u16(deltas.len() as u16);
+        mvar.put_u16(1);
+        mvar.put_u16(1);
+        mvar.put_u16(0);
+        for (_, delta) in deltas {
+            mvar.put_i16(*delta);
+        }
+
+        mvar
+    }
+
+    #[test]
+    fn test_deltas() {
+        let mvar = build(&[(b"undo", -40), (b"vasc", 100)]);
+
+        assert_eq!(
+            deltas(&mvar, &[0.5]).unwrap(),
+            vec![(*b"undo", -20.0), (*b"vasc", 50.0)]
+        );
+        assert_eq!(
+            deltas(&mvar, &[-0.5]).unwrap(),
+            vec![(*b"undo", 0.0), (*b"vasc", 0.0)]
+        );
+
+        let mut post = vec![0; 32];
+        set_u16(&mut post, 8, -100i16 as u16);
+        apply(&deltas(&mvar, &[1.0]).unwrap(), b"post", &mut post);
+        assert_eq!(get_u16(&post, 8), Some(-140i16 as u16));
+        assert_eq!(get_u16(&post, 10), Some(0));
+    }
+}
@@ -0,0 +1,73 @@
+//! `name` - naming table
+use crate::sfnt::Reader;
+
+pub(crate) const FAMILY: u16 = 1;
+pub(crate) const SUBFAMILY: u16 = 2;
+pub(crate) const FULL_NAME: u16 = 4;
+pub(crate) const TYPOGRAPHIC_FAMILY: u16 = 16;
+pub(crate) const TYPOGRAPHIC_SUBFAMILY: u16 = 17;
+
+const PLATFORM_UNICODE: u16 = 0;
+const PLATFORM_MAC: u16 = 1;
+const PLATFORM_WINDOWS: u16 = 3;
+
+const LANGUAGE_EN_US: u16 = 0x0409;
+
+/// Find a name by its id. English Windows names are preferred, then any Unicode name, then
+/// Macintosh Roman names
+pub(crate) fn find(name: &[u8], name_id: u16) -> Option<String> {
+    let mut r = Reader::new(name);
+    let _format = r.read_u16()?;
+    let count = r.read_u16()?;
+    let storage = r.read_u16()? as usize;
+
+    let mut best: Option<(u8, String)> = None;
+
+    for _ in 0..count {
+        let platform = r.read_u16()?;
+        let encoding = r.read_u16()?;
+        let language = r.read_u16()?;
+        let id = r.read_u16()?;
+        let length = r.read_u16()? as usize;
+        let offset = r.read_u16()? as usize;
+
+        if id != name_id {
+            continue;
+        }
+
+        let priority = match (platform, encoding, language) {
+            (PLATFORM_WINDOWS, 1, LANGUAGE_EN_US) | (PLATFORM_WINDOWS, 10, LANGUAGE_EN_US) => 3,
+            (PLATFORM_WINDOWS, 1, _) | (PLATFORM_WINDOWS, 10, _) => 2,
+            (PLATFORM_UNICODE, _, _) => 2,
+            (PLATFORM_MAC, 0, 0) => 1,
+            _ => continue,
+        };
+
+        if best.as_ref().map_or(false, |(p, _)| *p >= priority) {
+            continue;
+        }
+
+        let bytes = match name.get(storage + offset..storage + offset + length) {
+            Some(bytes) => bytes,
+            None => continue,
+        };
+
+        let value = if platform == PLATFORM_MAC {
+            // ASCII subset of Mac Roman
+            bytes
+                .iter()
+                .map(|&b| if b < 0x80 { b as char } else { '\u{FFFD}' })
+                .collect()
+        } else {
+            let units = bytes
+                .chunks_exact(2)
+                .map(|c| u16::from_be_bytes([c[0], c[1]]))
+                .collect::<Vec<_>>();
+            String::from_utf16_lossy(&units)
+        };
+
+        best = Some((priority, value));
+    }
+
+    best.map(|(_, value)| value)
+}
@@ -0,0 +1,354 @@
+//! `post` - PostScript glyph names
+use crate::sfnt::Reader;
+
+const VERSION_1: u32 = 0x0001_0000;
+const VERSION_2: u32 = 0x0002_0000;
+const VERSION_2_5: u32 = 0x0002_5000;
+
+/// Size of the header, before the glyph name data
+const HEADER_SIZE: usize = 32;
+
+/// Names of the standard Macintosh glyph order, used by version 1 tables and referenced by
+/// index from version 2 tables
+const MAC_GLYPH_NAMES: [&str; 258] = [
+    ".notdef",
+    ".null",
+    "nonmarkingreturn",
+    "space",
+    "exclam",
+    "quotedbl",
+    "numbersign",
+    "dollar",
+    "percent",
+    "ampersand",
+    "quotesingle",
+    "parenleft",
+    "parenright",
+    "asterisk",
+    "plus",
+    "comma",
+    "hyphen",
+    "period",
+    "slash",
+    "zero",
+    "one",
+    "two",
+    "three",
+    "four",
+    "five",
+    "six",
+    "seven",
+    "eight",
+    "nine",
+    "colon",
+    "semicolon",
+    "less",
+    "equal",
+    "greater",
+    "question",
+    "at",
+    "A",
+    "B",
+    "C",
+    "D",
+    "E",
+    "F",
+    "G",
+    "H",
+    "I",
+    "J",
+    "K",
+    "L",
+    "M",
+    "N",
+    "O",
+    "P",
+    "Q",
+    "R",
+    "S",
+    "T",
+    "U",
+    "V",
+    "W",
+    "X",
+    "Y",
+    "Z",
+    "bracketleft",
+    "backslash",
+    "bracketright",
+    "asciicircum",
+    "underscore",
+    "grave",
+    "a",
+    "b",
+    "c",
+    "d",
+    "e",
+    "f",
+    "g",
+    "h",
+    "i",
+    "j",
+    "k",
+    "l",
+    "m",
+    "n",
+    "o",
+    "p",
+    "q",
+    "r",
+    "s",
+    "t",
+    "u",
+    "v",
+    "w",
+    "x",
+    "y",
+    "z",
+    "braceleft",
+    "bar",
+    "braceright",
+    "asciitilde",
+    "Adieresis",
+    "Aring",
+    "Ccedilla",
+    "Eacute",
+    "Ntilde",
+    "Odieresis",
+    "Udieresis",
+    "aacute",
+    "agrave",
+    "acircumflex",
+    "adieresis",
+    "atilde",
+    "aring",
+    "ccedilla",
+    "eacute",
+    "egrave",
+    "ecircumflex",
+    "edieresis",
+    "iacute",
+    "igrave",
+    "icircumflex",
+    "idieresis",
+    "ntilde",
+    "oacute",
+    "ograve",
+    "ocircumflex",
+    "odieresis",
+    "otilde",
+    "uacute",
+    "ugrave",
+    "ucircumflex",
+    "udieresis",
+    "dagger",
+    "degree",
+    "cent",
+    "sterling",
+    "section",
+    "bullet",
+    "paragraph",
+    "germandbls",
+    "registered",
+    "copyright",
+    "trademark",
+    "acute",
+    "dieresis",
+    "notequal",
+    "AE",
+    "Oslash",
+    "infinity",
+    "plusminus",
+    "lessequal",
+    "greaterequal",
+    "yen",
+    "mu",
+    "partialdiff",
+    "summation",
+    "product",
+    "pi",
+    "integral",
+    "ordfeminine",
+    "ordmasculine",
+    "Omega",
+    "ae",
+    "oslash",
+    "questiondown",
+    "exclamdown",
+    "logicalnot",
+    "radical",
+    "florin",
+    "approxequal",
+    "Delta",
+    "guillemotleft",
+    "guillemotright",
+    "ellipsis",
+    "nonbreakingspace",
+    "Agrave",
+    "Atilde",
+    "Otilde",
+    "OE",
+    "oe",
+    "endash",
+    "emdash",
+    "quotedblleft",
+    "quotedblright",
+    "quoteleft",
+    "quoteright",
+    "divide",
+    "lozenge",
+    "ydieresis",
+    "Ydieresis",
+    "fraction",
+    "currency",
+    "guilsinglleft",
+    "guilsinglright",
+    "fi",
+    "fl",
+    "daggerdbl",
+    "periodcentered",
+    "quotesinglbase",
+    "quotedblbase",
+    "perthousand",
+    "Acircumflex",
+    "Ecircumflex",
+    "Aacute",
+    "Edieresis",
+    "Egrave",
+    "Iacute",
+    "Icircumflex",
+    "Idieresis",
+    "Igrave",
+    "Oacute",
+    "Ocircumflex",
+    "apple",
+    "Ograve",
+    "Uacute",
+    "Ucircumflex",
+    "Ugrave",
+    "dotlessi",
+    "circumflex",
+    "tilde",
+    "macron",
+    "breve",
+    "dotaccent",
+    "ring",
+    "cedilla",
+    "hungarumlaut",
+    "ogonek",
+    "caron",
+    "Lslash",
+    "lslash",
+    "Scaron",
+    "scaron",
+    "Zcaron",
+    "zcaron",
+    "brokenbar",
+    "Eth",
+    "eth",
+    "Yacute",
+    "yacute",
+    "Thorn",
+    "thorn",
+    "minus",
+    "multiply",
+    "onesuperior",
+    "twosuperior",
+    "threesuperior",
+    "onehalf",
+    "onequarter",
+    "threequarters",
+    "franc",
+    "Gbreve",
+    "gbreve",
+    "Idotaccent",
+    "Scedilla",
+    "scedilla",
+    "Cacute",
+    "cacute",
+    "Ccaron",
+    "ccaron",
+    "dcroat",
+];
+
+/// Names of all glyphs, by glyph index. `None` for tables without names (version 3, used by
+/// CFF fonts) or unsupported versions. Glyphs without a valid name get an empty one
+pub(crate) fn glyph_names(post: &[u8]) -> Option<Vec<String>> {
+    let mut r = Reader::new(post);
+
+    match r.read_u32()? {
+        VERSION_1 => Some(
+            MAC_GLYPH_NAMES
+                .iter()
+                .map(|name| name.to_string())
+                .collect(),
+        ),
+        VERSION_2 => {
+            r.skip(HEADER_SIZE - 4)?;
+            let num_glyphs = r.read_u16()?;
+            let indices = (0..num_glyphs)
+                .map(|_| r.read_u16())
+                .collect::<Option<Vec<_>>>()?;
+
+            // Pascal strings up to the end of the table
+            let mut custom = Vec::new();
+            while let Some(length) = r.read_u8() {
+                let bytes = match r.read_bytes(length as usize) {
+                    Some(bytes) => bytes,
+                    None => break,
+                };
+                custom.push(String::from_utf8_lossy(bytes).into_owned());
+            }
+
+            let name = |index: u16| match index as usize {
+                index if index < MAC_GLYPH_NAMES.len() => MAC_GLYPH_NAMES[index].to_string(),
+                index => custom
+                    .get(index - MAC_GLYPH_NAMES.len())
+                    .cloned()
+                    .unwrap_or_default(),
+            };
+            Some(indices.into_iter().map(name).collect())
+        }
+        VERSION_2_5 => {
+            r.skip(HEADER_SIZE - 4)?;
+            let num_glyphs = r.read_u16()?;
+
+            (0..num_glyphs)
+                .map(|glyph| {
+                    let index = glyph as i32 + r.read_i8()? as i32;
+                    let name = MAC_GLYPH_NAMES.get(index as usize).filter(|_| index >= 0);
+                    Some(name.map(|name| name.to_string()).unwrap_or_default())
+                })
+                .collect()
+        }
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{sfnt::Sfnt, tests::read_font};
+
+    #[test]
+    fn test_glyph_names() {
+        let font = read_font(None);
+        let post = Sfnt::parse(&font).unwrap().table(b"post").unwrap();
+
+        let names = glyph_names(post).unwrap();
+        assert_eq!(names.len(), 1485);
+        assert_eq!(
+            &names[..5],
+            &[".notdef", "null", "nonmarkingreturn", "space", "A"]
+        );
+        assert_eq!(names[918], "zero.zero");
+        assert_eq!(names[1059], "Euro");
+
+        // version 1 and 3 tables
+        let mut header = vec![0; HEADER_SIZE];
+        header[1] = 1;
+        assert_eq!(glyph_names(&header).unwrap()[36], "A");
+        header[1] = 3;
+        assert_eq!(glyph_names(&header), None);
+    }
+}
@@ -1,10 +1,21 @@
 use core::slice;
 
-use std::{convert::TryInto, mem::MaybeUninit, path::Path};
+#[cfg(not(target_arch = "wasm32"))]
+use std::path::Path;
+use std::{convert::TryInto, mem::MaybeUninit};
 
 use ttf2mesh_sys as sys;
 
-use crate::{path_to_cstring, Error, Glyph, Quality};
+use crate::{
+    collection,
+    loader::{self, FontData},
+    sfnt::Sfnt,
+    synthetic,
+    tables::{cmap, post},
+    variation, Error, FaceInfo, Glyph, SyntheticStyle, VariationAxis,
+};
+#[cfg(not(target_arch = "wasm32"))]
+use crate::{path_to_cstring, Quality};
 
 /// A decoded TTF file instance. Contains a list of [`Glyph`]'s
 ///
@@ -30,30 +41,126 @@ use crate::{path_to_cstring, Error, Glyph, Quality};
 /// let mesh_2d: Mesh<Mesh2d> = glyph.to_2d_mesh(Quality::Medium).unwrap();
 ///
 /// // work with Mesh vertices, faces (indices). See Mesh documentation for more
+/// # if cfg!(not(feature = "pure-rust")) {
 /// assert_eq!(mesh_2d.vertices_len(), 56);
 /// assert_eq!(mesh_2d.iter_vertices().next().unwrap().val(), (0.555, 0.656));
 ///
 /// assert_eq!(mesh_2d.faces_len(), 54);
 /// assert_eq!(mesh_2d.iter_faces().next().unwrap().val(), (53, 52, 5));
+/// # } else {
+/// #     // the pure-rust mesher triangulates differently
+/// #     assert!(mesh_2d.vertices_len() > 0);
+/// #     let (a, b, c) = mesh_2d.iter_faces().next().unwrap().val();
+/// #     assert!((a.max(b).max(c) as usize) < mesh_2d.vertices_len());
+/// # }
 ///
 /// // 3d mesh with depth of 0.5
 /// let mesh_3d: Mesh<Mesh3d> = glyph.to_3d_mesh(Quality::Medium, 0.5).unwrap();
 /// ```
-pub struct TTFFile {
+///
+/// The C library copies everything it needs while loading. The font data itself is retained
+/// only for tables which are read on the Rust side (e.g. font variations): it is owned for
+/// [`TTFFile::from_buffer_vec`], borrowed for [`TTFFileRef::from_slice`] and memory-mapped for
+/// [`TTFFile::from_file`] with the `mmap` feature
+pub type TTFFile = TTFFileRef<'static>;
+
+/// A [`TTFFile`] which borrows its font data, see [`TTFFileRef::from_slice`]
+pub struct TTFFileRef<'a> {
     ttf: *mut sys::ttf_file,
+    data: FontData<'a>,
+    /// Font data of the selected variation instance, `None` for the default instance
+    instance: Option<Vec<u8>>,
+    variation: Vec<(String, f32)>,
+    synthetic: SyntheticStyle,
 }
 
-impl std::fmt::Debug for TTFFile {
+impl std::fmt::Debug for TTFFileRef<'_> {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         write!(f, "TTFFile<>")
     }
 }
 
 impl TTFFile {
-    /// Load TTF font from a memory buffer
+    /// Load TTF or OTF font from a memory buffer
     ///
-    /// Has to take ownership since the buffer is being modified at runtime
+    /// OpenType fonts with CFF/CFF2 outlines are converted to TrueType outlines first; CFF2
+    /// variable fonts load as static fonts at their default instance. For font collections, the first face is
+    /// loaded. WOFF and WOFF2 web fonts are decoded when the `woff` feature is enabled
     pub fn from_buffer_vec(data: Vec<u8>) -> Result<TTFFile, Error> {
+        Self::from_buffer_face(data, 0)
+    }
+
+    /// Load a face of a font collection (`.ttc`) from a memory buffer. See
+    /// [`TTFFile::collection_faces`]
+    pub fn from_buffer_face(data: Vec<u8>, index: usize) -> Result<TTFFile, Error> {
+        Self::from_data(FontData::Owned(data), index)
+    }
+
+    /// Load TTF or OTF font from a file
+    ///
+    /// With the `mmap` feature, the file is memory-mapped instead of being read into memory. The
+    /// file must not be modified while the font is loaded
+    #[cfg(not(target_arch = "wasm32"))]
+    pub fn from_file<P: AsRef<Path>>(path: P) -> Result<TTFFile, Error> {
+        Self::from_file_face(path, 0)
+    }
+
+    /// Load a face of a font collection (`.ttc`) from a file
+    ///
+    /// Usage:
+    /// ```rust,no_run
+    /// # use ttf2mesh::TTFFile;
+    /// let path = "/usr/share/fonts/opentype/noto/NotoSansCJK-Regular.ttc";
+    ///
+    /// for face in TTFFile::collection_faces(path).unwrap() {
+    ///     println!("{}: {} {}", face.index, face.family, face.style);
+    /// }
+    ///
+    /// let mut ttf = TTFFile::from_file_face(path, 1).unwrap();
+    /// ```
+    #[cfg(not(target_arch = "wasm32"))]
+    pub fn from_file_face<P: AsRef<Path>>(path: P, index: usize) -> Result<TTFFile, Error> {
+        Self::from_data(loader::read_file(path)?, index)
+    }
+
+    /// List the faces of a font collection (`.ttc`) with their names. Other font files have a
+    /// single face
+    #[cfg(not(target_arch = "wasm32"))]
+    pub fn collection_faces<P: AsRef<Path>>(path: P) -> Result<Vec<FaceInfo>, Error> {
+        collection::faces(&loader::decode(loader::read_file(path)?)?)
+    }
+
+    /// List the faces of a font collection in a buffer, see [`TTFFile::collection_faces`]
+    pub fn collection_faces_from_slice(data: &[u8]) -> Result<Vec<FaceInfo>, Error> {
+        collection::faces(&loader::decode(FontData::Borrowed(data))?)
+    }
+}
+
+impl<'a> TTFFileRef<'a> {
+    /// Load TTF or OTF font from a borrowed buffer, without copying it
+    ///
+    /// The buffer is copied only when it has to be converted (web fonts, font collections and
+    /// CFF outlines). See [`TTFFile::from_buffer_vec`]
+    pub fn from_slice(data: &'a [u8]) -> Result<TTFFileRef<'a>, Error> {
+        Self::from_data(FontData::Borrowed(data), 0)
+    }
+
+    fn from_data(data: FontData<'a>, index: usize) -> Result<TTFFileRef<'a>, Error> {
+        let data = loader::prepare(data, index)?;
+        let ttf = Self::load_from_mem(&data)?;
+
+        Ok(TTFFileRef {
+            ttf,
+            data,
+            instance: None,
+            variation: Vec::new(),
+            synthetic: SyntheticStyle::default(),
+        })
+    }
+}
+
+impl TTFFileRef<'_> {
+    fn load_from_mem(data: &[u8]) -> Result<*mut sys::ttf_file, Error> {
         let mut ttf = MaybeUninit::uninit();
         let error = unsafe {
             sys::ttf_load_from_mem(
@@ -66,32 +173,112 @@ impl TTFFile {
         Self::load(ttf, error)
     }
 
-    /// Load TTF font from a file
-    pub fn from_file<P: AsRef<Path>>(path: P) -> Result<TTFFile, Error> {
-        if !Path::new(path.as_ref().as_os_str()).exists() {
-            return Err(Error::FileNotFound);
+    fn load(ttf: MaybeUninit<*mut sys::ttf_file>, error: i32) -> Result<*mut sys::ttf_file, Error> {
+        if error != ttf2mesh_sys::TTF_DONE as i32 {
+            // fprintf(stderr, "Unable to load font: %s\n", ttf_error_str[error]);
+            return Err(Error::FontLoadError);
         }
 
-        let file_name = path_to_cstring(path);
+        Ok(unsafe { ttf.assume_init() })
+    }
 
-        let mut ttf = MaybeUninit::uninit();
-        let error = unsafe { sys::ttf_load_from_file(file_name.as_ptr(), ttf.as_mut_ptr(), false) };
+    /// Variation axes of a variable font. Empty for static fonts
+    pub fn axes(&self) -> Vec<VariationAxis> {
+        variation::axes(&self.data)
+    }
 
-        Self::load(ttf, error)
+    /// Select an instance of a variable font by user-space axis values, e.g. `&[("wght", 700.0)]`
+    ///
+    /// Axes which are not listed use their default value, and values are clamped to the axis
+    /// range. Glyph outlines and advances are varied before meshing. An empty list restores the
+    /// default instance. Fails with [`Error::VariationError`] for unknown axes, and for
+    /// non-default instances of fonts whose outlines have no `gvar` variations
+    ///
+    /// Usage:
+    /// ```rust,no_run
+    /// # use ttf2mesh::{TTFFile, Quality};
+    /// let mut ttf = TTFFile::from_file("./fonts/MyVariableFont.ttf").unwrap();
+    ///
+    /// for axis in ttf.axes() {
+    ///     println!("{}: {}..{}", axis.tag, axis.min, axis.max);
+    /// }
+    ///
+    /// ttf.set_variation(&[("wght", 700.0), ("wdth", 75.0)]).unwrap();
+    /// let mesh = ttf.glyph_from_char('A').unwrap().to_2d_mesh(Quality::Medium).unwrap();
+    /// ```
+    pub fn set_variation(&mut self, variations: &[(&str, f32)]) -> Result<(), Error> {
+        self.reload(variations, self.synthetic)?;
+        self.variation = variations
+            .iter()
+            .map(|(tag, value)| (tag.to_string(), *value))
+            .collect();
+
+        Ok(())
     }
 
-    fn load(ttf: MaybeUninit<*mut sys::ttf_file>, error: i32) -> Result<TTFFile, Error> {
-        if error != ttf2mesh_sys::TTF_DONE as i32 {
-            // fprintf(stderr, "Unable to load font: %s\n", ttf_error_str[error]);
-            return Err(Error::FontLoadError);
+    /// Axis values selected with [`TTFFile::set_variation`]. Empty for the default instance
+    pub fn variation(&self) -> &[(String, f32)] {
+        &self.variation
+    }
+
+    /// Embolden and skew all glyph outlines, for fonts which have no bold or italic face. The
+    /// outlines are changed before meshing, so all glyph meshes (and [`Glyph::contains`]) use
+    /// the synthetic style. The default style restores the original outlines
+    ///
+    /// Emboldening also widens the glyph advances used by [`TTFFile::layout`]; text shaped with
+    /// the `shaping` feature keeps the advances of the font
+    ///
+    /// Usage:
+    /// ```rust
+    /// # use ttf2mesh::{TTFFile, Quality, SyntheticStyle};
+    /// let mut ttf = TTFFile::from_file("./fonts/FiraMono-Medium.ttf").unwrap();
+    ///
+    /// ttf.set_synthetic_style(SyntheticStyle { embolden: 0.02, skew: 0.2 }).unwrap();
+    /// let mesh = ttf.glyph_from_char('A').unwrap().to_2d_mesh(Quality::Medium).unwrap();
+    /// ```
+    pub fn set_synthetic_style(&mut self, style: SyntheticStyle) -> Result<(), Error> {
+        let variation = self.variation.clone();
+        let variations = variation
+            .iter()
+            .map(|(tag, value)| (tag.as_str(), *value))
+            .collect::<Vec<_>>();
+
+        self.reload(&variations, style)?;
+        self.synthetic = style;
+
+        Ok(())
+    }
+
+    /// Style selected with [`TTFFile::set_synthetic_style`]
+    pub fn synthetic_style(&self) -> SyntheticStyle {
+        self.synthetic
+    }
+
+    /// Load the glyphs again for a variation instance and a synthetic style
+    fn reload(&mut self, variations: &[(&str, f32)], style: SyntheticStyle) -> Result<(), Error> {
+        // the default instance is the font itself, static fonts have no `fvar` to instantiate
+        let instance = if variations.is_empty() {
+            None
+        } else {
+            variation::instantiate(&self.data, variations)?
+        };
+        let ttf = Self::load_from_mem(instance.as_deref().unwrap_or(&self.data))?;
+
+        unsafe { sys::ttf_free(self.ttf) };
+        self.ttf = ttf;
+        self.instance = instance;
+
+        if !style.is_none() {
+            for glyph in self.glyphs_mut() {
+                synthetic::apply(glyph, &style);
+            }
         }
 
-        Ok(Self {
-            ttf: unsafe { ttf.assume_init() },
-        })
+        Ok(())
     }
 
     /// Export all glyphs to a .obj -file
+    #[cfg(not(target_arch = "wasm32"))]
     pub fn export_to_obj<P: AsRef<Path>>(
         &mut self,
         obj_path: P,
@@ -112,16 +299,37 @@ impl TTFFile {
 
     /// Get a glyph for a character
     pub fn glyph_from_char<'a>(&'a mut self, char: char) -> Result<Glyph<'a>, Error> {
-        let mut bytes = [0; 2];
-        char.encode_utf16(&mut bytes);
+        let index = self.glyph_index(char).ok_or(Error::GlyphNotFound)?;
 
-        let index = unsafe { sys::ttf_find_glyph(self.ttf, bytes[0]) };
+        self.glyph_by_index(index)
+    }
 
-        if index < 0 {
-            return Err(Error::GlyphNotFound);
-        }
+    /// Glyph index of a character, if the font maps it
+    pub(crate) fn glyph_index(&self, char: char) -> Option<usize> {
+        // the C library looks up UTF-16 code units, so characters outside the Basic Multilingual
+        // Plane (e.g. emoji) are read from the full Unicode `cmap` subtable instead
+        let code_unit: u16 = match (char as u32).try_into() {
+            Ok(code_unit) => code_unit,
+            Err(_) => {
+                let cmap = Sfnt::parse(&self.data)?.table(b"cmap")?;
+                return cmap::glyph(cmap, char as u32).map(usize::from);
+            }
+        };
+
+        let index = unsafe { sys::ttf_find_glyph(self.ttf, code_unit) };
+
+        index.try_into().ok()
+    }
+
+    /// Font data for reading tables on the Rust side
+    pub(crate) fn data(&self) -> &[u8] {
+        &self.data
+    }
 
-        self.glyph_by_index(index.try_into().unwrap())
+    /// Font data of the selected variation instance, for metrics which vary (e.g. vertical
+    /// metrics). Same as [`TTFFileRef::data`] for the default instance
+    pub(crate) fn instance_data(&self) -> &[u8] {
+        self.instance.as_deref().unwrap_or(&self.data)
     }
 
     /// Total count of glyphs in a ttf file
@@ -134,13 +342,45 @@ impl TTFFile {
         let glyphs = unsafe { slice::from_raw_parts_mut((*self.ttf).glyphs, self.glyph_count()) };
 
         match glyphs.get_mut(index) {
-            Some(glyph) => Ok(Glyph::from_raw(glyph)),
+            Some(glyph) => Ok(Glyph::from_raw(glyph, index, &self.data)),
             None => Err(Error::GlyphNotFound),
         }
     }
+
+    /// Glyph of the C library, for the read-only queries of [`TTFFileRef::layout`] and
+    /// [`TTFFileRef::hit_test`] which need no mutable [`Glyph`]
+    pub(crate) fn raw_glyph(&self, index: usize) -> Option<&sys::ttf_glyph> {
+        let glyphs = unsafe { slice::from_raw_parts((*self.ttf).glyphs, self.glyph_count()) };
+
+        glyphs.get(index)
+    }
+
+    /// Get a glyph by its PostScript name from the `post` table, e.g. `uni20AC`, `f_i` or an
+    /// unencoded alternate like `zero.zero`. See also [`Glyph::name`]
+    ///
+    /// Usage:
+    /// ```rust
+    /// # use ttf2mesh::TTFFile;
+    /// let mut ttf = TTFFile::from_file("./fonts/FiraMono-Medium.ttf").unwrap();
+    ///
+    /// let glyph = ttf.glyph_by_name("zero.zero").unwrap();
+    /// assert_eq!(glyph.name().unwrap(), "zero.zero");
+    /// ```
+    pub fn glyph_by_name<'a>(&'a mut self, name: &str) -> Result<Glyph<'a>, Error> {
+        let index = Sfnt::parse(&self.data)
+            .and_then(|sfnt| post::glyph_names(sfnt.table(b"post")?))
+            .and_then(|names| names.iter().position(|n| n == name))
+            .ok_or(Error::GlyphNotFound)?;
+
+        self.glyph_by_index(index)
+    }
+
+    fn glyphs_mut(&mut self) -> &mut [sys::ttf_glyph] {
+        unsafe { slice::from_raw_parts_mut((*self.ttf).glyphs, self.glyph_count()) }
+    }
 }
 
-impl Drop for TTFFile {
+impl Drop for TTFFileRef<'_> {
     fn drop(&mut self) {
         unsafe { sys::ttf_free(self.ttf) }
     }
@@ -0,0 +1,321 @@
+//! Mesh validation and repair
+use std::collections::{HashMap, VecDeque};
+
+use crate::OwnedMesh;
+
+/// Faces with a doubled area below this are considered degenerate
+const MIN_DOUBLED_AREA: f32 = 1e-12;
+
+type Edge = (i32, i32);
+
+/// Problems found in a mesh by [`OwnedMesh::validate`] or `Mesh::validate`
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct MeshReport {
+    /// Faces with a vertex index out of the vertex range
+    pub out_of_bounds_faces: Vec<usize>,
+    /// Faces with (nearly) zero area, including faces that repeat a vertex
+    pub zero_area_faces: Vec<usize>,
+    /// Vertices with the same position (and normal, for 3d meshes) as an earlier vertex, as
+    /// `(duplicate, original)`
+    pub duplicate_vertices: Vec<(usize, usize)>,
+    /// Edges shared by more than two faces
+    pub non_manifold_edges: Vec<Edge>,
+    /// Edges shared by two faces that run along the edge in the same direction, i.e. the faces
+    /// have opposite winding
+    pub inconsistent_edges: Vec<Edge>,
+    /// Vertices with a NaN or infinite coordinate or normal
+    pub non_finite_vertices: Vec<usize>,
+}
+
+impl MeshReport {
+    /// No problems were found
+    pub fn is_valid(&self) -> bool {
+        self == &MeshReport::default()
+    }
+}
+
+impl OwnedMesh {
+    /// Check the mesh for out of range indices, zero-area faces, duplicate vertices,
+    /// non-manifold edges, inconsistent winding and NaNs
+    ///
+    /// Usage:
+    /// ```rust
+    /// # use ttf2mesh::{TTFFile, Quality};
+    /// # let mut ttf = TTFFile::from_file("./fonts/FiraMono-Medium.ttf").unwrap();
+    /// # let mut glyph = ttf.glyph_from_char('€').unwrap();
+    /// let mesh = glyph.to_2d_mesh(Quality::Medium).unwrap().to_owned_mesh();
+    ///
+    /// let report = mesh.validate();
+    /// let mesh = if report.is_valid() { mesh } else { mesh.repair() };
+    /// ```
+    pub fn validate(&self) -> MeshReport {
+        let mut report = MeshReport {
+            non_finite_vertices: (0..self.vertices.len())
+                .filter(|&index| !self.is_finite(index))
+                .collect(),
+            ..MeshReport::default()
+        };
+
+        let mut first = HashMap::new();
+        for index in 0..self.vertices.len() {
+            if !self.is_finite(index) {
+                continue;
+            }
+
+            let key = self.vertex_key(index);
+            match first.get(&key) {
+                Some(&original) => report.duplicate_vertices.push((index, original)),
+                None => {
+                    first.insert(key, index);
+                }
+            }
+        }
+
+        let mut edges: HashMap<Edge, Vec<bool>> = HashMap::new();
+        for (index, face) in self.faces.iter().enumerate() {
+            if !self.in_bounds(*face) {
+                report.out_of_bounds_faces.push(index);
+                continue;
+            }
+
+            if self.doubled_area(*face) < MIN_DOUBLED_AREA {
+                report.zero_area_faces.push(index);
+            }
+
+            for &(from, to) in &face_edges(*face) {
+                if from != to {
+                    edges
+                        .entry((from.min(to), from.max(to)))
+                        .or_default()
+                        .push(from < to);
+                }
+            }
+        }
+
+        for (edge, directions) in edges {
+            match directions.len() {
+                0 | 1 => (),
+                2 if directions[0] != directions[1] => (),
+                2 => report.inconsistent_edges.push(edge),
+                _ => report.non_manifold_edges.push(edge),
+            }
+        }
+        report.non_manifold_edges.sort_unstable();
+        report.inconsistent_edges.sort_unstable();
+
+        report
+    }
+
+    /// Fix what [`OwnedMesh::validate`] reports, where possible: faces with out of range
+    /// indices, non-finite vertices or zero area are removed, duplicate vertices are merged and
+    /// faces are flipped to the winding of their neighbours. Non-manifold edges are kept
+    ///
+    /// Vertices are only removed when they are not finite or merged, so the repair of a valid
+    /// mesh returns the same mesh
+    pub fn repair(&self) -> OwnedMesh {
+        let mut mesh = OwnedMesh {
+            vertices: Vec::with_capacity(self.vertices.len()),
+            faces: Vec::with_capacity(self.faces.len()),
+            normals: Vec::with_capacity(self.normals.len()),
+        };
+
+        // new index of each vertex, `None` for removed ones
+        let mut first = HashMap::new();
+        let remap = (0..self.vertices.len())
+            .map(|index| {
+                if !self.is_finite(index) {
+                    return None;
+                }
+
+                let new = *first.entry(self.vertex_key(index)).or_insert_with(|| {
+                    mesh.vertices.push(self.vertices[index]);
+                    if self.is_3d() {
+                        mesh.normals.push(self.normals[index]);
+                    }
+                    mesh.vertices.len() as i32 - 1
+                });
+                Some(new)
+            })
+            .collect::<Vec<_>>();
+
+        for &face in &self.faces {
+            if !self.in_bounds(face) {
+                continue;
+            }
+
+            let vertex = |index: i32| remap[index as usize];
+            if let (Some(a), Some(b), Some(c)) = (vertex(face.0), vertex(face.1), vertex(face.2)) {
+                if mesh.doubled_area((a, b, c)) >= MIN_DOUBLED_AREA {
+                    mesh.faces.push((a, b, c));
+                }
+            }
+        }
+
+        mesh.orient_faces();
+        mesh
+    }
+
+    /// Flip faces so that neighbours across manifold edges have the same winding. The first face
+    /// of each connected part keeps its winding
+    fn orient_faces(&mut self) {
+        let mut edges: HashMap<Edge, Vec<usize>> = HashMap::new();
+        for (index, face) in self.faces.iter().enumerate() {
+            for &(from, to) in &face_edges(*face) {
+                edges
+                    .entry((from.min(to), from.max(to)))
+                    .or_default()
+                    .push(index);
+            }
+        }
+
+        let mut visited = vec![false; self.faces.len()];
+        let mut queue = VecDeque::new();
+
+        for seed in 0..self.faces.len() {
+            if visited[seed] {
+                continue;
+            }
+            visited[seed] = true;
+            queue.push_back(seed);
+
+            while let Some(index) = queue.pop_front() {
+                for &(from, to) in &face_edges(self.faces[index]) {
+                    let neighbours = &edges[&(from.min(to), from.max(to))];
+                    if neighbours.len() != 2 {
+                        continue;
+                    }
+
+                    let other = if neighbours[0] == index {
+                        neighbours[1]
+                    } else {
+                        neighbours[0]
+                    };
+                    if visited[other] {
+                        continue;
+                    }
+                    visited[other] = true;
+
+                    // a consistent neighbour runs along the edge from `to` to `from`
+                    if face_edges(self.faces[other]).contains(&(from, to)) {
+                        let (a, b, c) = self.faces[other];
+                        self.faces[other] = (a, c, b);
+                    }
+                    queue.push_back(other);
+                }
+            }
+        }
+    }
+
+    fn is_finite(&self, index: usize) -> bool {
+        let finite = |v: &(f32, f32, f32)| v.0.is_finite() && v.1.is_finite() && v.2.is_finite();
+
+        finite(&self.vertices[index]) && self.normals.get(index).map_or(true, finite)
+    }
+
+    pub(crate) fn in_bounds(&self, face: (i32, i32, i32)) -> bool {
+        let len = self.vertices.len() as i32;
+        [face.0, face.1, face.2]
+            .iter()
+            .all(|&index| index >= 0 && index < len)
+    }
+
+    /// Bits of the position and normal of a vertex, with `-0.0` and `0.0` being equal
+    fn vertex_key(&self, index: usize) -> [u32; 6] {
+        let v = self.vertices[index];
+        let n = self.normals.get(index).copied().unwrap_or_default();
+        let bits = |value: f32| (value + 0.0).to_bits();
+
+        [
+            bits(v.0),
+            bits(v.1),
+            bits(v.2),
+            bits(n.0),
+            bits(n.1),
+            bits(n.2),
+        ]
+    }
+
+    /// Doubled area of a face in 3d (length of the cross product of two edges)
+    fn doubled_area(&self, face: (i32, i32, i32)) -> f32 {
+        let p = |index: i32| self.vertices[index as usize];
+        let (a, b, c) = (p(face.0), p(face.1), p(face.2));
+        let (u, v) = (
+            (b.0 - a.0, b.1 - a.1, b.2 - a.2),
+            (c.0 - a.0, c.1 - a.1, c.2 - a.2),
+        );
+
+        let cross = (
+            u.1 * v.2 - u.2 * v.1,
+            u.2 * v.0 - u.0 * v.2,
+            u.0 * v.1 - u.1 * v.0,
+        );
+        (cross.0 * cross.0 + cross.1 * cross.1 + cross.2 * cross.2).sqrt()
+    }
+}
+
+fn face_edges((a, b, c): (i32, i32, i32)) -> [Edge; 3] {
+    [(a, b), (b, c), (c, a)]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// A unit square of two triangles
+    fn square() -> OwnedMesh {
+        OwnedMesh {
+            vertices: vec![
+                (0.0, 0.0, 0.0),
+                (1.0, 0.0, 0.0),
+                (1.0, 1.0, 0.0),
+                (0.0, 1.0, 0.0),
+            ],
+            faces: vec![(0, 1, 2), (0, 2, 3)],
+            normals: Vec::new(),
+        }
+    }
+
+    #[test]
+    fn test_validate() {
+        assert!(square().validate().is_valid());
+        assert_eq!(square().repair(), square());
+
+        let mut mesh = square();
+        mesh.vertices.push((0.0, 0.0, 0.0));
+        mesh.vertices.push((f32::NAN, 0.0, 0.0));
+        mesh.vertices.push((2.0, 0.5, 0.0));
+        mesh.vertices.push((1.5, 1.5, 1.0));
+        // flipped, out of bounds, zero area, and two more faces on the edge (1, 2)
+        mesh.faces[1] = (0, 3, 2);
+        mesh.faces.push((0, 1, 9));
+        mesh.faces.push((0, 4, 1));
+        mesh.faces.push((2, 1, 6));
+        mesh.faces.push((1, 2, 7));
+
+        let report = mesh.validate();
+        assert!(!report.is_valid());
+        assert_eq!(report.out_of_bounds_faces, &[2]);
+        assert_eq!(report.zero_area_faces, &[3]);
+        assert_eq!(report.duplicate_vertices, &[(4, 0)]);
+        assert_eq!(report.non_manifold_edges, &[(1, 2)]);
+        assert_eq!(report.inconsistent_edges, &[(0, 2)]);
+        assert_eq!(report.non_finite_vertices, &[5]);
+    }
+
+    #[test]
+    fn test_repair() {
+        let mut mesh = square();
+        // flipped second face, sharing a duplicate of the first vertex
+        mesh.vertices.push((0.0, 0.0, 0.0));
+        mesh.vertices.push((f32::INFINITY, 0.0, 0.0));
+        mesh.faces[1] = (4, 3, 2);
+        mesh.faces.push((0, 1, 5));
+        mesh.faces.push((0, 2, 2));
+
+        let repaired = mesh.repair();
+        assert!(repaired.validate().is_valid());
+        assert_eq!(repaired.vertices, square().vertices);
+        assert_eq!(repaired.faces, &[(0, 1, 2), (0, 2, 3)]);
+    }
+}
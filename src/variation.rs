@@ -0,0 +1,571 @@
+//! Variable font support: reading axes from `fvar` and instancing outlines with `gvar` deltas
+//! and font-wide metrics with `MVAR` deltas
+use std::str;
+
+use crate::{
+    sfnt::{self, Sfnt, SfntBuilder},
+    tables::{
+        avar::Avar,
+        fvar,
+        glyf::{self, Glyf, GlyphData, Point},
+        gvar::{Gvar, PHANTOM_POINTS},
+        hmtx::{self, Metric},
+        mvar,
+    },
+    Error,
+};
+
+/// Tables which describe variations or default-instance device metrics, dropped from an instance
+const DROPPED_TABLES: [&sfnt::Tag; 10] = [
+    b"fvar", b"avar", b"gvar", b"cvar", b"HVAR", b"VVAR", b"MVAR", b"hdmx", b"LTSH", b"VDMX",
+];
+
+/// A variation axis of a variable font. See [`crate::TTFFile::axes`]
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct VariationAxis {
+    /// Four-character axis tag, e.g. `wght` or `wdth`
+    pub tag: String,
+    /// Minimum axis value
+    pub min: f32,
+    /// Default axis value
+    pub default: f32,
+    /// Maximum axis value
+    pub max: f32,
+    /// Axis is not meant to be exposed in user interfaces
+    pub hidden: bool,
+}
+
+pub(crate) fn axes(data: &[u8]) -> Vec<VariationAxis> {
+    let axes = Sfnt::parse(data)
+        .and_then(|sfnt| sfnt.table(b"fvar"))
+        .and_then(fvar::parse)
+        .unwrap_or_default();
+
+    axes.into_iter()
+        .map(|axis| VariationAxis {
+            tag: String::from_utf8_lossy(&axis.tag).into_owned(),
+            min: axis.min,
+            default: axis.default,
+            max: axis.max,
+            hidden: axis.hidden,
+        })
+        .collect()
+}
+
+/// Build a static font instance at user-space axis values
+///
+/// Returns `None` when the requested instance is the default instance, which includes an empty
+/// list of variations for any font
+pub(crate) fn instantiate(
+    data: &[u8],
+    variations: &[(&str, f32)],
+) -> Result<Option<Vec<u8>>, Error> {
+    if variations.is_empty() {
+        return Ok(None);
+    }
+
+    let sfnt = Sfnt::parse(data).ok_or(Error::FontLoadError)?;
+
+    let axes = sfnt
+        .table(b"fvar")
+        .and_then(fvar::parse)
+        .ok_or(Error::VariationError)?;
+
+    let mut coords = vec![0.0; axes.len()];
+    for (tag, value) in variations {
+        let index = axes
+            .iter()
+            .position(|axis| str::from_utf8(&axis.tag) == Ok(*tag))
+            .ok_or(Error::VariationError)?;
+
+        coords[index] = axes[index].normalize(*value);
+    }
+
+    if let Some(avar) = sfnt.table(b"avar").and_then(Avar::parse) {
+        for (axis, coord) in coords.iter_mut().enumerate() {
+            *coord = avar.map(axis, *coord);
+        }
+    }
+
+    // normalized coordinates have F2DOT14 precision
+    for coord in coords.iter_mut() {
+        *coord = (*coord * 16384.0).round() / 16384.0;
+    }
+
+    if coords.iter().all(|&coord| coord == 0.0) {
+        return Ok(None);
+    }
+
+    // without `gvar` (e.g. CFF2 outlines, which are not varied) only the default instance exists
+    let gvar = sfnt
+        .table(b"gvar")
+        .and_then(Gvar::parse)
+        .ok_or(Error::VariationError)?;
+
+    instantiate_glyphs(&sfnt, &gvar, &coords)
+        .map(Some)
+        .ok_or(Error::VariationError)
+}
+
+/// A glyph with deltas applied, before composite glyphs are expanded
+struct VariedGlyph {
+    data: GlyphData,
+    advance: f32,
+    /// Horizontal position of the varied origin (first phantom point)
+    origin: f32,
+    /// Vertical shifts of the top and bottom phantom points: the vertical origin and advance
+    vertical_deltas: (f32, f32),
+}
+
+fn instantiate_glyphs(sfnt: &Sfnt, gvar: &Gvar, coords: &[f32]) -> Option<Vec<u8>> {
+    let head = sfnt.table(b"head")?;
+    let hhea = sfnt.table(b"hhea")?;
+    let maxp = sfnt.table(b"maxp")?;
+    let num_glyphs = glyf::num_glyphs(maxp)?;
+
+    let glyf = Glyf::parse(
+        sfnt.table(b"glyf")?,
+        sfnt.table(b"loca")?,
+        glyf::index_to_loc_format(head)?,
+        num_glyphs,
+    )?;
+    let metrics = hmtx::parse(sfnt.table(b"hmtx")?, hhea, num_glyphs)?;
+    let vertical = match (sfnt.table(b"vhea"), sfnt.table(b"vmtx")) {
+        (Some(vhea), Some(vmtx)) => Some((vhea, hmtx::parse(vmtx, vhea, num_glyphs)?)),
+        _ => None,
+    };
+
+    let mut varied = Vec::with_capacity(num_glyphs as usize);
+    for index in 0..num_glyphs {
+        let glyph = glyf.glyph(index).unwrap_or(GlyphData::Empty);
+        varied.push(vary_glyph(
+            gvar,
+            coords,
+            index,
+            glyph,
+            metrics[index as usize],
+        )?);
+    }
+
+    let mut encoded = Vec::with_capacity(varied.len());
+    let mut new_metrics = Vec::with_capacity(varied.len());
+    let mut new_vertical_metrics = Vec::with_capacity(varied.len());
+    let (mut max_points, mut max_contours) = (0, 0);
+    let mut font_bounds: Option<(i16, i16, i16, i16)> = None;
+
+    for index in 0..num_glyphs {
+        let mut lookup = |glyph: u16| varied.get(glyph as usize).map(|g| g.data.clone());
+        let mut contours = glyf::flatten(&mut lookup, index, 0).unwrap_or_default();
+
+        let glyph = &varied[index as usize];
+        for point in contours.iter_mut().flatten() {
+            point.x -= glyph.origin;
+        }
+
+        let bounds = glyf::bounds(&contours);
+        if let Some((x_min, y_min, x_max, y_max)) = bounds {
+            font_bounds = Some(match font_bounds {
+                Some((a, b, c, d)) => (a.min(x_min), b.min(y_min), c.max(x_max), d.max(y_max)),
+                None => (x_min, y_min, x_max, y_max),
+            });
+        }
+
+        max_points = max_points.max(contours.iter().map(|c| c.len()).sum::<usize>());
+        max_contours = max_contours.max(contours.len());
+
+        new_metrics.push(Metric {
+            advance: glyph.advance.round().max(0.0).min(u16::MAX as f32) as u16,
+            side_bearing: bounds.map(|b| b.0).unwrap_or(0),
+        });
+        encoded.push(glyf::encode_simple(&contours, &[]));
+
+        if let Some((_, vertical_metrics)) = &vertical {
+            let metric = vertical_metrics[index as usize];
+            let (top_delta, bottom_delta) = glyph.vertical_deltas;
+            let advance = metric.advance as f32 + top_delta - bottom_delta;
+
+            // the top side bearing is the distance of the varied top of the glyph to the
+            // varied vertical origin
+            let top = glyf
+                .y_max(index)
+                .map(|y_max| y_max as f32 + metric.side_bearing as f32 + top_delta);
+            let side_bearing = match (top, bounds) {
+                (Some(top), Some((_, _, _, y_max))) => (top - y_max as f32).round() as i16,
+                _ => metric.side_bearing,
+            };
+
+            new_vertical_metrics.push(Metric {
+                advance: advance.round().max(0.0).min(u16::MAX as f32) as u16,
+                side_bearing,
+            });
+        }
+    }
+
+    let (glyf_table, loca_table) = glyf::build_tables(&encoded);
+    let (hmtx_table, hhea_table) = hmtx::build(&new_metrics, hhea);
+
+    // font-wide metrics of the tables which are kept or rebuilt
+    let mut tables = vec![(*b"hhea", hhea_table), (*b"hmtx", hmtx_table)];
+    if let Some((vhea, _)) = vertical {
+        let (vmtx_table, vhea_table) = hmtx::build(&new_vertical_metrics, vhea);
+        tables.push((*b"vhea", vhea_table));
+        tables.push((*b"vmtx", vmtx_table));
+    }
+    for tag in &[b"OS/2", b"post"] {
+        if let Some(table) = sfnt.table(tag) {
+            tables.push((**tag, table.to_vec()));
+        }
+    }
+
+    let metric_deltas = sfnt
+        .table(b"MVAR")
+        .and_then(|mvar| mvar::deltas(mvar, coords))
+        .unwrap_or_default();
+    for (tag, table) in tables.iter_mut() {
+        mvar::apply(&metric_deltas, tag, table);
+    }
+
+    let mut head = head.to_vec();
+    if let Some((x_min, y_min, x_max, y_max)) = font_bounds {
+        sfnt::set_u16(&mut head, 36, x_min as u16);
+        sfnt::set_u16(&mut head, 38, y_min as u16);
+        sfnt::set_u16(&mut head, 40, x_max as u16);
+        sfnt::set_u16(&mut head, 42, y_max as u16);
+    }
+    sfnt::set_u16(&mut head, 50, 1); // indexToLocFormat: long offsets
+
+    let mut maxp = maxp.to_vec();
+    if maxp.len() >= 32 {
+        sfnt::set_u16(&mut maxp, 6, max_points.min(u16::MAX as usize) as u16);
+        sfnt::set_u16(&mut maxp, 8, max_contours.min(u16::MAX as usize) as u16);
+        // composites have been expanded
+        for offset in &[10, 12, 28, 30] {
+            sfnt::set_u16(&mut maxp, *offset, 0);
+        }
+    }
+
+    let mut builder = SfntBuilder::from_sfnt(sfnt);
+    for tag in DROPPED_TABLES.iter() {
+        builder.remove(tag);
+    }
+    builder.insert(*b"head", head);
+    builder.insert(*b"maxp", maxp);
+    for (tag, table) in tables {
+        builder.insert(tag, table);
+    }
+    builder.insert(*b"glyf", glyf_table);
+    builder.insert(*b"loca", loca_table);
+
+    Some(builder.build())
+}
+
+fn vary_glyph(
+    gvar: &Gvar,
+    coords: &[f32],
+    index: u16,
+    glyph: GlyphData,
+    metric: Metric,
+) -> Option<VariedGlyph> {
+    let origin = glyph.x_min() as f32 - metric.side_bearing as f32;
+    let phantom = [
+        (origin, 0.0),
+        (origin + metric.advance as f32, 0.0),
+        (0.0, 0.0),
+        (0.0, 0.0),
+    ];
+
+    let (data, deltas) = match glyph {
+        GlyphData::Simple { contours, x_min } => {
+            let mut points = Vec::new();
+            let mut contour_ends = Vec::with_capacity(contours.len());
+            for contour in contours.iter() {
+                points.extend(contour.iter().map(|p| (p.x, p.y)));
+                contour_ends.push(points.len().wrapping_sub(1));
+            }
+            points.extend_from_slice(&phantom);
+
+            let deltas = gvar.deltas(index, coords, &points, Some(&contour_ends))?;
+
+            let mut deltas_iter = deltas.iter();
+            let contours = contours
+                .into_iter()
+                .map(|contour| {
+                    contour
+                        .into_iter()
+                        .zip(&mut deltas_iter)
+                        .map(|(p, d)| Point {
+                            x: p.x + d.0,
+                            y: p.y + d.1,
+                            on_curve: p.on_curve,
+                        })
+                        .collect()
+                })
+                .collect();
+
+            (GlyphData::Simple { contours, x_min }, deltas)
+        }
+        GlyphData::Composite { components, x_min } => {
+            let mut points = components
+                .iter()
+                .map(|c| {
+                    if c.xy_offset {
+                        (c.arg1 as f32, c.arg2 as f32)
+                    } else {
+                        (0.0, 0.0)
+                    }
+                })
+                .collect::<Vec<_>>();
+            points.extend_from_slice(&phantom);
+
+            let deltas = gvar.deltas(index, coords, &points, None)?;
+
+            let components = components
+                .into_iter()
+                .zip(deltas.iter())
+                .map(|(mut c, d)| {
+                    if c.xy_offset {
+                        c.arg1 += d.0.round() as i32;
+                        c.arg2 += d.1.round() as i32;
+                    }
+                    c
+                })
+                .collect();
+
+            (GlyphData::Composite { components, x_min }, deltas)
+        }
+        GlyphData::Empty => {
+            let deltas = gvar.deltas(index, coords, &phantom, None)?;
+            (GlyphData::Empty, deltas)
+        }
+    };
+
+    let phantom_deltas = &deltas[deltas.len() - PHANTOM_POINTS..];
+    let left = origin + phantom_deltas[0].0;
+    let right = origin + metric.advance as f32 + phantom_deltas[1].0;
+
+    Some(VariedGlyph {
+        data,
+        advance: right - left,
+        origin: left,
+        vertical_deltas: (phantom_deltas[2].1, phantom_deltas[3].1),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::sfnt::{WriteBe, VERSION_TRUETYPE};
+
+    /// Flags of a composite glyph component with word offsets
+    const COMPONENT_FLAGS: u16 = 0x0003;
+
+    /// Variable font with a `wght` axis (100..400..900, mapped by `avar` to half the
+    /// normalized value), a 100 units square (glyph 0) and a composite of it offset by 50 units
+    /// (glyph 1). At the maximum weight:
+    ///
+    /// - square: points 1 and 2 move right by 40 and point 2 up by 20, points 0 and 3 are
+    ///   interpolated, the advance grows by 40 and the vertical advance by 40
+    /// - composite: the component offset and the advance grow by 10
+    /// - `MVAR`: the vertical ascender grows by 100, the underline moves down by 40
+    fn variable_font() -> Vec<u8> {
+        let square = vec![[(0.0, 0.0), (100.0, 0.0), (100.0, 100.0), (0.0, 100.0)]
+            .iter()
+            .map(|&(x, y)| Point {
+                x,
+                y,
+                on_curve: true,
+            })
+            .collect()];
+
+        let mut composite = Vec::new();
+        for value in &[-1, 50, 0, 150, 100] {
+            composite.put_i16(*value);
+        }
+        composite.put_u16(COMPONENT_FLAGS);
+        composite.put_u16(0);
+        composite.put_i16(50);
+        composite.put_i16(0);
+
+        let (glyf, loca) = glyf::build_tables(&[glyf::encode_simple(&square, &[]), composite]);
+
+        let mut head = vec![0; 54];
+        sfnt::set_u16(&mut head, 18, 1000);
+        sfnt::set_u16(&mut head, 50, 1);
+        let mut hhea = vec![0; 36];
+        sfnt::set_u16(&mut hhea, 34, 2);
+        let mut maxp = vec![0; 32];
+        sfnt::set_u16(&mut maxp, 0, 1);
+        sfnt::set_u16(&mut maxp, 4, 2);
+
+        let mut hmtx = Vec::new();
+        for &(advance, side_bearing) in &[(200, 0), (250, 50)] {
+            hmtx.put_u16(advance);
+            hmtx.put_i16(side_bearing);
+        }
+
+        let mut vhea = vec![0; 36];
+        sfnt::set_u16(&mut vhea, 4, 800);
+        sfnt::set_u16(&mut vhea, 34, 2);
+        let mut vmtx = Vec::new();
+        for _ in 0..2 {
+            vmtx.put_u16(300);
+            vmtx.put_i16(10);
+        }
+
+        let mut post = vec![0; 32];
+        sfnt::set_u16(&mut post, 8, -100i16 as u16);
+
+        let mut fvar = Vec::new();
+        for value in &[1, 0, 16, 2, 1, 20, 0, 8] {
+            fvar.put_u16(*value);
+        }
+        fvar.extend_from_slice(b"wght");
+        for value in &[100, 400, 900] {
+            fvar.put_u32(value << 16);
+        }
+        fvar.put_u16(0);
+        fvar.put_u16(256);
+
+        let mut avar = Vec::new();
+        for value in &[1, 0, 0, 1, 3] {
+            avar.put_u16(*value);
+        }
+        for &(from, to) in &[(-1.0, -1.0), (0.0, 0.0), (1.0, 0.5)] {
+            avar.put_i16((from * 16384.0) as i16);
+            avar.put_i16((to * 16384.0) as i16);
+        }
+
+        // tuple variations at the maximum, with private point numbers
+        let tuple = |points: &[u8], x: &[u8], y: &[u8]| {
+            let mut data = Vec::new();
+            data.put_u16(1);
+            data.put_u16(10);
+            data.put_u16((points.len() + x.len() + y.len()) as u16);
+            data.put_u16(0xA000);
+            data.put_u16(0x4000);
+            data.extend_from_slice(points);
+            data.extend_from_slice(x);
+            data.extend_from_slice(y);
+            data.resize((data.len() + 1) & !1, 0);
+            data
+        };
+        let square_deltas = tuple(
+            &[4, 3, 1, 1, 3, 2],
+            &[3, 40, 40, 40, 0],
+            &[3, 0, 20, 0, -40i8 as u8],
+        );
+        let composite_deltas = tuple(&[0], &[4, 10, 0, 10, 0, 0], &[0x84]);
+
+        let mut gvar = Vec::new();
+        for value in &[1, 0, 1, 0] {
+            gvar.put_u16(*value);
+        }
+        gvar.put_u32(26);
+        gvar.put_u16(2);
+        gvar.put_u16(0);
+        gvar.put_u32(26);
+        gvar.put_u16(0);
+        gvar.put_u16(square_deltas.len() as u16 / 2);
+        gvar.put_u16((square_deltas.len() + composite_deltas.len()) as u16 / 2);
+        gvar.extend_from_slice(&square_deltas);
+        gvar.extend_from_slice(&composite_deltas);
+
+        let mut builder = SfntBuilder::new(VERSION_TRUETYPE);
+        builder.insert(*b"head", head);
+        builder.insert(*b"hhea", hhea);
+        builder.insert(*b"maxp", maxp);
+        builder.insert(*b"hmtx", hmtx);
+        builder.insert(*b"vhea", vhea);
+        builder.insert(*b"vmtx", vmtx);
+        builder.insert(*b"post", post);
+        builder.insert(*b"glyf", glyf);
+        builder.insert(*b"loca", loca);
+        builder.insert(*b"fvar", fvar);
+        builder.insert(*b"avar", avar);
+        builder.insert(*b"gvar", gvar);
+        builder.insert(
+            *b"MVAR",
+            mvar::tests::build(&[(b"vasc", 100), (b"undo", -40)]),
+        );
+        builder.build()
+    }
+
+    /// Outline points and advances of the glyphs of an instance
+    fn glyphs(data: &[u8]) -> Vec<(Vec<(f32, f32)>, u16)> {
+        let sfnt = Sfnt::parse(data).unwrap();
+        let head = sfnt.table(b"head").unwrap();
+        let glyf = Glyf::parse(
+            sfnt.table(b"glyf").unwrap(),
+            sfnt.table(b"loca").unwrap(),
+            glyf::index_to_loc_format(head).unwrap(),
+            2,
+        )
+        .unwrap();
+        let metrics = hmtx::parse(
+            sfnt.table(b"hmtx").unwrap(),
+            sfnt.table(b"hhea").unwrap(),
+            2,
+        );
+
+        (0..2)
+            .map(|index| {
+                let mut lookup = |glyph: u16| glyf.glyph(glyph);
+                let points = glyf::flatten(&mut lookup, index, 0)
+                    .unwrap()
+                    .iter()
+                    .flatten()
+                    .map(|p| (p.x, p.y))
+                    .collect();
+
+                (points, metrics.as_ref().unwrap()[index as usize].advance)
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_instantiate() {
+        let font = variable_font();
+        assert_eq!(axes(&font)[0].tag, "wght");
+
+        // default instance
+        assert_eq!(instantiate(&font, &[]).unwrap(), None);
+        assert_eq!(instantiate(&font, &[("wght", 400.0)]).unwrap(), None);
+        assert!(instantiate(&font, &[("wdth", 100.0)]).is_err());
+
+        // avar halves the deltas at the maximum
+        let instance = instantiate(&font, &[("wght", 900.0)]).unwrap().unwrap();
+        let glyphs = glyphs(&instance);
+        let square = [(20.0, 0.0), (120.0, 0.0), (120.0, 110.0), (20.0, 110.0)];
+        assert_eq!(glyphs[0], (square.to_vec(), 220));
+
+        let composite = square.iter().map(|&(x, y)| (x + 55.0, y)).collect();
+        assert_eq!(glyphs[1], (composite, 255));
+
+        // vertical metrics follow the phantom points, font-wide metrics `MVAR`
+        let sfnt = Sfnt::parse(&instance).unwrap();
+        let vhea = sfnt.table(b"vhea").unwrap();
+        let vmtx = hmtx::parse(sfnt.table(b"vmtx").unwrap(), vhea, 2).unwrap();
+        let metric = |advance, side_bearing| Metric {
+            advance,
+            side_bearing,
+        };
+        assert_eq!(vmtx, &[metric(320, 0), metric(300, 0)]);
+        assert_eq!(sfnt::get_u16(vhea, 4), Some(850));
+        let post = sfnt.table(b"post").unwrap();
+        assert_eq!(sfnt::get_u16(post, 8), Some(-120i16 as u16));
+
+        // an `fvar` without `gvar` has only the default instance
+        let sfnt = Sfnt::parse(&font).unwrap();
+        let mut builder = SfntBuilder::from_sfnt(&sfnt);
+        builder.remove(b"gvar");
+        let no_gvar = builder.build();
+        assert_eq!(instantiate(&no_gvar, &[("wght", 400.0)]).unwrap(), None);
+        assert!(instantiate(&no_gvar, &[("wght", 900.0)]).is_err());
+
+        // static fonts have no variations, but the default instance
+        let static_font = crate::tests::read_font(None);
+        assert_eq!(instantiate(&static_font, &[]).unwrap(), None);
+        assert!(instantiate(&static_font, &[("wght", 700.0)]).is_err());
+    }
+}
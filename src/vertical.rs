@@ -0,0 +1,236 @@
+//! Vertical text metrics from `vhea` / `vmtx`, synthesized from `OS/2`, `hhea` and `head` for
+//! fonts without them
+use crate::{
+    sfnt::{get_u16, Sfnt},
+    tables::{
+        glyf::{self, Glyf},
+        hmtx::{self, Metric},
+    },
+    SyntheticStyle,
+};
+
+/// Vertical metrics of a font, in em units
+pub(crate) struct VerticalMetrics<'a> {
+    units_per_em: f32,
+    vmtx: Option<Vec<Metric>>,
+    glyf: Option<Glyf<'a>>,
+    /// Horizontal ascender and descender, for synthesized metrics and rotated glyphs
+    ascender: f32,
+    descender: f32,
+    /// Distance between the center lines of columns, `None` when not known from `vhea`
+    column_width: Option<f32>,
+    /// Outward offset of emboldened glyphs, see [`SyntheticStyle::embolden`]
+    embolden: f32,
+}
+
+impl<'a> VerticalMetrics<'a> {
+    /// Metrics of glyphs with a synthetic `style`: emboldened glyphs grow up and down by the
+    /// offset, and keep their top side bearing
+    pub fn parse(data: &'a [u8], style: SyntheticStyle) -> Option<Self> {
+        let sfnt = Sfnt::parse(data)?;
+        let head = sfnt.table(b"head")?;
+
+        let units_per_em = get_u16(head, 18)? as f32;
+        if units_per_em == 0.0 {
+            return None;
+        }
+
+        let (ascender, descender) = horizontal_extents(&sfnt)?;
+        let num_glyphs = glyf::num_glyphs(sfnt.table(b"maxp")?)?;
+
+        let vhea = sfnt.table(b"vhea");
+        let vmtx = match (sfnt.table(b"vmtx"), vhea) {
+            (Some(vmtx), Some(vhea)) => hmtx::parse(vmtx, vhea, num_glyphs),
+            _ => None,
+        };
+
+        let column_width = vhea.and_then(|vhea| {
+            let ascender = get_u16(vhea, 4)? as i16 as f32;
+            let descender = get_u16(vhea, 6)? as i16 as f32;
+            let line_gap = get_u16(vhea, 8)? as i16 as f32;
+
+            let width = (ascender - descender + line_gap) / units_per_em;
+            if width > 0.0 {
+                Some(width)
+            } else {
+                None
+            }
+        });
+
+        let glyf = match (sfnt.table(b"glyf"), sfnt.table(b"loca")) {
+            (Some(glyf), Some(loca)) => {
+                Glyf::parse(glyf, loca, glyf::index_to_loc_format(head)?, num_glyphs)
+            }
+            _ => None,
+        };
+
+        Some(Self {
+            units_per_em,
+            vmtx,
+            glyf,
+            ascender: ascender / units_per_em,
+            descender: descender / units_per_em,
+            column_width,
+            embolden: style.embolden,
+        })
+    }
+
+    /// Vertical advance of a glyph. Without `vmtx`, the height of the em box
+    pub fn advance(&self, glyph: usize) -> f32 {
+        let advance = match self.metric(glyph) {
+            Some(metric) => metric.advance as f32 / self.units_per_em,
+            None => self.ascender - self.descender,
+        };
+
+        advance + 2.0 * self.embolden
+    }
+
+    /// Height of the vertical origin above the baseline: top of the glyph plus its top side
+    /// bearing. Without `vmtx`, the ascender
+    pub fn origin_y(&self, glyph: usize) -> f32 {
+        let y_max = self.glyf.as_ref().and_then(|glyf| glyf.y_max(glyph as u16));
+
+        let origin_y = match (self.metric(glyph), y_max) {
+            (Some(metric), Some(y_max)) => {
+                (y_max as f32 + metric.side_bearing as f32) / self.units_per_em
+            }
+            _ => self.ascender,
+        };
+
+        origin_y + self.embolden
+    }
+
+    pub fn column_width(&self) -> Option<f32> {
+        self.column_width
+    }
+
+    /// Middle of the em box above the baseline. Rotated glyphs are centered on the column with it
+    pub fn center(&self) -> f32 {
+        (self.ascender + self.descender) / 2.0
+    }
+
+    fn metric(&self, glyph: usize) -> Option<&Metric> {
+        self.vmtx.as_ref()?.get(glyph)
+    }
+}
+
+/// Ascender and descender in font units: typographic ones from `OS/2`, then `hhea`, then the
+/// font bounding box from `head`
+fn horizontal_extents(sfnt: &Sfnt) -> Option<(f32, f32)> {
+    let from_table = |tag, offset| {
+        let table = sfnt.table(tag)?;
+        let ascender = get_u16(table, offset)? as i16 as f32;
+        let descender = get_u16(table, offset + 2)? as i16 as f32;
+
+        if ascender > descender {
+            Some((ascender, descender))
+        } else {
+            None
+        }
+    };
+
+    from_table(b"OS/2", 68)
+        .or_else(|| from_table(b"hhea", 4))
+        .or_else(|| {
+            let head = sfnt.table(b"head")?;
+            let y_min = get_u16(head, 38)? as i16 as f32;
+            let y_max = get_u16(head, 42)? as i16 as f32;
+
+            Some((y_max, y_min))
+        })
+}
+
+/// Whether a character stays upright in vertical text. Approximates the Unicode vertical
+/// orientation property (UAX #50) by blocks: CJK scripts, fullwidth forms and symbols are
+/// upright, other characters (e.g. Latin) are rotated
+pub(crate) fn is_upright(char: char) -> bool {
+    matches!(char as u32,
+        0x1100..=0x11FF // Hangul Jamo
+        | 0x2E80..=0x2FFF // CJK radicals, Kangxi radicals, ideographic description
+        | 0x3000..=0x303F // CJK symbols and punctuation
+        | 0x3040..=0x33FF // Kana, Bopomofo, Hangul compatibility, enclosed CJK, compatibility
+        | 0x3400..=0x4DBF // CJK extension A
+        | 0x4DC0..=0x4DFF // Yijing hexagrams
+        | 0x4E00..=0x9FFF // CJK unified ideographs
+        | 0xA000..=0xA4CF // Yi
+        | 0xA960..=0xA97F // Hangul Jamo extended A
+        | 0xAC00..=0xD7FF // Hangul syllables, Jamo extended B
+        | 0xF900..=0xFAFF // CJK compatibility ideographs
+        | 0xFE10..=0xFE1F // vertical forms
+        | 0xFE30..=0xFE4F // CJK compatibility forms
+        | 0xFF00..=0xFFEF // halfwidth and fullwidth forms
+        | 0x1F000..=0x1FAFF // game symbols, enclosed ideographs, emoji
+        | 0x20000..=0x3FFFF // supplementary ideographic planes
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        sfnt::{set_u16, SfntBuilder, WriteBe},
+        tests::read_font,
+    };
+
+    #[test]
+    fn test_synthesized_metrics() {
+        let data = read_font(None);
+        let metrics = VerticalMetrics::parse(&data, SyntheticStyle::default()).unwrap();
+
+        // FiraMono has no vertical metrics: OS/2 typo ascender 935, descender -265
+        assert!(metrics.column_width().is_none());
+        assert!((metrics.advance(10) - 1.2).abs() < 1e-6);
+        assert!((metrics.origin_y(10) - 0.935).abs() < 1e-6);
+        assert!((metrics.center() - 0.335).abs() < 1e-6);
+    }
+
+    #[test]
+    fn test_vmtx() {
+        let data = read_font(None);
+        let sfnt = Sfnt::parse(&data).unwrap();
+
+        // column of 1em, 5 long metrics of 0.9em advance and 0.1em top side bearing
+        let mut vhea = vec![0; 36];
+        set_u16(&mut vhea, 4, 500);
+        set_u16(&mut vhea, 6, -500i16 as u16);
+        set_u16(&mut vhea, 34, 5);
+
+        let mut vmtx = Vec::new();
+        for _ in 0..5 {
+            vmtx.put_u16(900);
+            vmtx.put_i16(100);
+        }
+
+        let mut builder = SfntBuilder::from_sfnt(&sfnt);
+        builder.insert(*b"vhea", vhea);
+        builder.insert(*b"vmtx", vmtx);
+        let data = builder.build();
+
+        let metrics = VerticalMetrics::parse(&data, SyntheticStyle::default()).unwrap();
+        let y_max = metrics.glyf.as_ref().unwrap().y_max(4).unwrap() as f32;
+
+        assert_eq!(metrics.column_width(), Some(1.0));
+        assert!((metrics.advance(4) - 0.9).abs() < 1e-6);
+        assert!((metrics.origin_y(4) - (y_max + 100.0) / 1000.0).abs() < 1e-6);
+        // glyphs past the long metrics keep the last advance
+        assert!((metrics.advance(10) - 0.9).abs() < 1e-6);
+
+        // emboldened glyphs grow up and down, keeping their top side bearing
+        let style = SyntheticStyle {
+            embolden: 0.02,
+            skew: 0.2,
+        };
+        let bold = VerticalMetrics::parse(&data, style).unwrap();
+        assert!((bold.advance(4) - 0.94).abs() < 1e-6);
+        assert!((bold.origin_y(4) - metrics.origin_y(4) - 0.02).abs() < 1e-6);
+    }
+
+    #[test]
+    fn test_is_upright() {
+        assert!(is_upright('日'));
+        assert!(is_upright('あ'));
+        assert!(is_upright('Ａ'));
+        assert!(!is_upright('A'));
+        assert!(!is_upright('1'));
+    }
+}
@@ -0,0 +1,587 @@
+//! WOFF and WOFF2 web font decoding into sfnt fonts
+use std::{convert::TryInto, io::Read};
+
+use crate::{
+    sfnt::{self, Reader, SfntBuilder, Tag, WriteBe},
+    tables::glyf::{self, Point},
+    Error,
+};
+
+const SIGNATURE_WOFF: Tag = *b"wOFF";
+const SIGNATURE_WOFF2: Tag = *b"wOF2";
+
+const FLAVOR_COLLECTION: u32 = 0x7474_6366; // 'ttcf'
+
+/// Tables of WOFF2 files are either referred to by an index to this list, or by an explicit tag
+const KNOWN_TAGS: [&Tag; 63] = [
+    b"cmap", b"head", b"hhea", b"hmtx", b"maxp", b"name", b"OS/2", b"post", b"cvt ", b"fpgm",
+    b"glyf", b"loca", b"prep", b"CFF ", b"VORG", b"EBDT", b"EBLC", b"gasp", b"hdmx", b"kern",
+    b"LTSH", b"PCLT", b"VDMX", b"vhea", b"vmtx", b"BASE", b"GDEF", b"GPOS", b"GSUB", b"EBSC",
+    b"JSTF", b"MATH", b"CBDT", b"CBLC", b"COLR", b"CPAL", b"SVG ", b"sbix", b"acnt", b"avar",
+    b"bdat", b"bloc", b"bsln", b"cvar", b"fdsc", b"feat", b"fmtx", b"fvar", b"gvar", b"hsty",
+    b"just", b"lcar", b"mort", b"morx", b"opbd", b"prop", b"trak", b"Zapf", b"Silf", b"Glat",
+    b"Gloc", b"Feat", b"Sill",
+];
+
+/// Limit for the size of a decoded font, guards against decompression bombs
+const MAX_DECODED_SIZE: usize = 256 * 1024 * 1024;
+
+/// Decode WOFF / WOFF2 data into an sfnt font (or a font collection). Returns `None` for other
+/// data
+pub(crate) fn decode(data: &[u8]) -> Result<Option<Vec<u8>>, Error> {
+    let decoded = match Reader::new(data).read_tag() {
+        Some(SIGNATURE_WOFF) => decode_woff(data),
+        Some(SIGNATURE_WOFF2) => decode_woff2(data),
+        _ => return Ok(None),
+    };
+
+    decoded.map(Some).ok_or(Error::FontLoadError)
+}
+
+fn decode_woff(data: &[u8]) -> Option<Vec<u8>> {
+    let mut r = Reader::new(data);
+    r.skip(4)?; // signature
+    let flavor = r.read_u32()?;
+    let _length = r.read_u32()?;
+    let num_tables = r.read_u16()?;
+    r.skip(2 + 4 + 2 + 2 + 4 * 5)?; // reserved, totalSfntSize, version, metadata and private data
+
+    let mut builder = SfntBuilder::new(flavor);
+    for _ in 0..num_tables {
+        let tag = r.read_tag()?;
+        let offset = r.read_u32()? as usize;
+        let comp_length = r.read_u32()? as usize;
+        let orig_length = r.read_u32()? as usize;
+        let _orig_checksum = r.read_u32()?;
+
+        if orig_length > MAX_DECODED_SIZE {
+            return None;
+        }
+
+        let compressed = data.get(offset..offset.checked_add(comp_length)?)?;
+        let table = if comp_length < orig_length {
+            let mut table = Vec::with_capacity(orig_length);
+            flate2::read::ZlibDecoder::new(compressed)
+                .take(orig_length as u64)
+                .read_to_end(&mut table)
+                .ok()?;
+            table
+        } else {
+            compressed.to_vec()
+        };
+
+        if table.len() != orig_length {
+            return None;
+        }
+
+        builder.insert(tag, table);
+    }
+
+    Some(builder.build())
+}
+
+struct TableEntry {
+    tag: Tag,
+    orig_length: usize,
+    /// Table is stored in its WOFF2-specific transformed format
+    transformed: bool,
+    /// Range of the table within the decompressed stream
+    start: usize,
+    end: usize,
+}
+
+fn decode_woff2(data: &[u8]) -> Option<Vec<u8>> {
+    let mut r = Reader::new(data);
+    r.skip(4)?; // signature
+    let flavor = r.read_u32()?;
+    let _length = r.read_u32()?;
+    let num_tables = r.read_u16()?;
+    let _reserved = r.read_u16()?;
+    let _total_sfnt_size = r.read_u32()?;
+    let total_compressed_size = r.read_u32()? as usize;
+    r.skip(2 + 2 + 4 * 5)?; // version, metadata and private data
+
+    let mut entries = Vec::with_capacity(num_tables as usize);
+    let mut stream_len = 0usize;
+    for _ in 0..num_tables {
+        let flags = r.read_u8()?;
+        let tag = match flags & 0x3F {
+            0x3F => r.read_tag()?,
+            index => **KNOWN_TAGS.get(index as usize)?,
+        };
+
+        // glyf and loca are transformed with version 0, other tables with versions 1-3
+        let version = flags >> 6;
+        let transformed = if &tag == b"glyf" || &tag == b"loca" {
+            version == 0
+        } else {
+            version != 0
+        };
+
+        let orig_length = read_base128(&mut r)? as usize;
+        let length = if transformed {
+            read_base128(&mut r)? as usize
+        } else {
+            orig_length
+        };
+
+        entries.push(TableEntry {
+            tag,
+            orig_length,
+            transformed,
+            start: stream_len,
+            end: stream_len.checked_add(length)?,
+        });
+        stream_len = stream_len.checked_add(length)?;
+    }
+
+    if stream_len > MAX_DECODED_SIZE {
+        return None;
+    }
+
+    // faces of a collection, as indices to the table entries
+    let faces = if flavor == FLAVOR_COLLECTION {
+        let _version = r.read_u32()?;
+        let num_fonts = read_255_u16(&mut r)?;
+
+        let mut faces = Vec::with_capacity(num_fonts as usize);
+        for _ in 0..num_fonts {
+            let num_tables = read_255_u16(&mut r)?;
+            let flavor = r.read_u32()?;
+            let tables = (0..num_tables)
+                .map(|_| read_255_u16(&mut r).map(|index| index as usize))
+                .collect::<Option<Vec<_>>>()?;
+
+            faces.push((flavor, tables));
+        }
+        faces
+    } else {
+        vec![(flavor, (0..entries.len()).collect())]
+    };
+
+    let compressed = r.read_bytes(total_compressed_size)?;
+    let mut stream = Vec::with_capacity(stream_len);
+    brotli_decompressor::Decompressor::new(compressed, 4096)
+        .take(stream_len as u64)
+        .read_to_end(&mut stream)
+        .ok()?;
+
+    if stream.len() != stream_len {
+        return None;
+    }
+
+    let fonts = faces
+        .iter()
+        .map(|(flavor, tables)| {
+            let tables = tables
+                .iter()
+                .map(|&index| entries.get(index))
+                .collect::<Option<Vec<_>>>()?;
+
+            build_woff2_face(*flavor, &tables, &stream)
+        })
+        .collect::<Option<Vec<_>>>()?;
+
+    if flavor == FLAVOR_COLLECTION {
+        sfnt::build_collection(&fonts)
+    } else {
+        fonts.into_iter().next()
+    }
+}
+
+fn build_woff2_face(flavor: u32, tables: &[&TableEntry], stream: &[u8]) -> Option<Vec<u8>> {
+    let contents = |tag: &Tag| {
+        tables
+            .iter()
+            .find(|entry| &entry.tag == tag)
+            .map(|entry| (*entry, &stream[entry.start..entry.end]))
+    };
+
+    let mut builder = SfntBuilder::new(flavor);
+    for entry in tables {
+        if !entry.transformed {
+            builder.insert(entry.tag, stream[entry.start..entry.end].to_vec());
+        }
+    }
+
+    let mut x_mins = None;
+    if let Some((entry, glyf)) = contents(b"glyf") {
+        if entry.transformed {
+            let (glyf, loca, mins) = reconstruct_glyf(glyf)?;
+
+            let mut head = contents(b"head")?.1.to_vec();
+            if head.len() < 54 {
+                return None;
+            }
+            sfnt::set_u16(&mut head, 50, 1); // indexToLocFormat: long offsets
+
+            builder.insert(*b"head", head);
+            builder.insert(*b"glyf", glyf);
+            builder.insert(*b"loca", loca);
+            x_mins = Some(mins);
+        }
+    }
+
+    if let Some((entry, hmtx)) = contents(b"hmtx") {
+        if entry.transformed {
+            let hhea = contents(b"hhea")?.1;
+            let hmtx = reconstruct_hmtx(hmtx, hhea, x_mins.as_ref()?, entry.orig_length)?;
+            builder.insert(*b"hmtx", hmtx);
+        }
+    }
+
+    Some(builder.build())
+}
+
+/// UIntBase128: variable-length encoding with 7 bits per byte
+fn read_base128(r: &mut Reader) -> Option<u32> {
+    let mut value = 0u32;
+
+    for i in 0..5 {
+        let byte = r.read_u8()?;
+        if i == 0 && byte == 0x80 {
+            return None; // leading zeros
+        }
+        if value & 0xFE00_0000 != 0 {
+            return None; // overflow
+        }
+
+        value = value << 7 | (byte & 0x7F) as u32;
+        if byte & 0x80 == 0 {
+            return Some(value);
+        }
+    }
+
+    None
+}
+
+/// 255UInt16: variable-length encoding of small integers
+fn read_255_u16(r: &mut Reader) -> Option<u16> {
+    match r.read_u8()? {
+        253 => r.read_u16(),
+        254 => Some(r.read_u8()? as u16 + 253 * 2),
+        255 => Some(r.read_u8()? as u16 + 253),
+        code => Some(code as u16),
+    }
+}
+
+/// Decode a point coordinate delta, packed with a flag byte into 1-4 data bytes
+fn read_triplet(flag: u8, r: &mut Reader) -> Option<(i32, i32)> {
+    let with_sign = |flag: u8, value: i32| if flag & 1 != 0 { value } else { -value };
+    let flag_i = flag as i32;
+
+    Some(match flag {
+        0..=9 => {
+            let b0 = r.read_u8()? as i32;
+            (0, with_sign(flag, ((flag_i & 14) << 7) + b0))
+        }
+        10..=19 => {
+            let b0 = r.read_u8()? as i32;
+            (with_sign(flag, (((flag_i - 10) & 14) << 7) + b0), 0)
+        }
+        20..=83 => {
+            let b0 = flag_i - 20;
+            let b1 = r.read_u8()? as i32;
+            (
+                with_sign(flag, 1 + (b0 & 0x30) + (b1 >> 4)),
+                with_sign(flag >> 1, 1 + ((b0 & 0x0C) << 2) + (b1 & 0x0F)),
+            )
+        }
+        84..=119 => {
+            let b0 = flag_i - 84;
+            let (b1, b2) = (r.read_u8()? as i32, r.read_u8()? as i32);
+            (
+                with_sign(flag, 1 + ((b0 / 12) << 8) + b1),
+                with_sign(flag >> 1, 1 + (((b0 % 12) >> 2) << 8) + b2),
+            )
+        }
+        120..=123 => {
+            let (b1, b2, b3) = (
+                r.read_u8()? as i32,
+                r.read_u8()? as i32,
+                r.read_u8()? as i32,
+            );
+            (
+                with_sign(flag, (b1 << 4) + (b2 >> 4)),
+                with_sign(flag >> 1, ((b2 & 0x0F) << 8) + b3),
+            )
+        }
+        _ => {
+            let (x, y) = (r.read_u16()? as i32, r.read_u16()? as i32);
+            (with_sign(flag, x), with_sign(flag >> 1, y))
+        }
+    })
+}
+
+// composite glyph flags
+const ARG_1_AND_2_ARE_WORDS: u16 = 0x0001;
+const WE_HAVE_A_SCALE: u16 = 0x0008;
+const MORE_COMPONENTS: u16 = 0x0020;
+const WE_HAVE_AN_X_AND_Y_SCALE: u16 = 0x0040;
+const WE_HAVE_A_TWO_BY_TWO: u16 = 0x0080;
+const WE_HAVE_INSTRUCTIONS: u16 = 0x0100;
+
+/// Rebuild `glyf` and a long-format `loca` from the transformed `glyf` table. Also returns the
+/// `xMin` of each glyph, needed for a transformed `hmtx`
+fn reconstruct_glyf(data: &[u8]) -> Option<(Vec<u8>, Vec<u8>, Vec<i16>)> {
+    let mut r = Reader::new(data);
+    let _version = r.read_u16()?;
+    let _option_flags = r.read_u16()?;
+    let num_glyphs = r.read_u16()? as usize;
+    let _index_format = r.read_u16()?;
+
+    let mut stream_sizes = [0usize; 7];
+    for size in stream_sizes.iter_mut() {
+        *size = r.read_u32()? as usize;
+    }
+
+    let mut streams = Vec::with_capacity(7);
+    for size in stream_sizes.iter() {
+        streams.push(r.read_bytes(*size)?);
+    }
+
+    let mut n_contours = Reader::new(streams[0]);
+    let mut n_points = Reader::new(streams[1]);
+    let mut flags = Reader::new(streams[2]);
+    let mut glyphs = Reader::new(streams[3]);
+    let mut composite = Reader::new(streams[4]);
+    let mut bbox = Reader::new(streams[5]);
+    let mut instructions = Reader::new(streams[6]);
+
+    let bbox_bitmap = bbox.read_bytes(((num_glyphs + 31) >> 5) << 2)?;
+    let has_bbox = |glyph: usize| bbox_bitmap[glyph >> 3] & (0x80 >> (glyph & 7)) != 0;
+
+    let mut encoded = Vec::with_capacity(num_glyphs);
+    let mut x_mins = Vec::with_capacity(num_glyphs);
+
+    for glyph in 0..num_glyphs {
+        let contours = n_contours.read_i16()?;
+
+        if contours == 0 {
+            if has_bbox(glyph) {
+                return None;
+            }
+
+            encoded.push(Vec::new());
+            x_mins.push(0);
+        } else if contours < 0 {
+            // composite glyphs must have an explicit bounding box
+            if !has_bbox(glyph) {
+                return None;
+            }
+            let bounds = bbox.read_bytes(8)?;
+
+            let start = composite.pos();
+            let mut have_instructions = false;
+            loop {
+                let flags = composite.read_u16()?;
+                let _glyph_index = composite.read_u16()?;
+
+                let mut len = if flags & ARG_1_AND_2_ARE_WORDS != 0 {
+                    4
+                } else {
+                    2
+                };
+                if flags & WE_HAVE_A_SCALE != 0 {
+                    len += 2;
+                } else if flags & WE_HAVE_AN_X_AND_Y_SCALE != 0 {
+                    len += 4;
+                } else if flags & WE_HAVE_A_TWO_BY_TWO != 0 {
+                    len += 8;
+                }
+                composite.skip(len)?;
+
+                have_instructions |= flags & WE_HAVE_INSTRUCTIONS != 0;
+                if flags & MORE_COMPONENTS == 0 {
+                    break;
+                }
+            }
+
+            let mut out = Vec::new();
+            out.put_i16(-1);
+            out.extend_from_slice(bounds);
+
+            out.extend_from_slice(&streams[4][start..composite.pos()]);
+
+            if have_instructions {
+                let len = read_255_u16(&mut glyphs)?;
+                out.put_u16(len);
+                out.extend_from_slice(instructions.read_bytes(len as usize)?);
+            }
+
+            encoded.push(out);
+            x_mins.push(i16::from_be_bytes(bounds[0..2].try_into().ok()?));
+        } else {
+            let mut outline = Vec::with_capacity(contours as usize);
+            let (mut x, mut y) = (0i32, 0i32);
+
+            for _ in 0..contours {
+                let points = read_255_u16(&mut n_points)?;
+                let mut contour = Vec::with_capacity(points as usize);
+
+                for _ in 0..points {
+                    let flag = flags.read_u8()?;
+                    let (dx, dy) = read_triplet(flag & 0x7F, &mut glyphs)?;
+                    x += dx;
+                    y += dy;
+
+                    contour.push(Point {
+                        x: x as f32,
+                        y: y as f32,
+                        on_curve: flag & 0x80 == 0,
+                    });
+                }
+
+                outline.push(contour);
+            }
+
+            let len = read_255_u16(&mut glyphs)?;
+            let glyph_instructions = instructions.read_bytes(len as usize)?;
+
+            let x_min = if has_bbox(glyph) {
+                let bounds = bbox.read_bytes(8)?;
+                i16::from_be_bytes(bounds[0..2].try_into().ok()?)
+            } else {
+                glyf::bounds(&outline).map_or(0, |b| b.0)
+            };
+
+            encoded.push(glyf::encode_simple(&outline, glyph_instructions));
+            x_mins.push(x_min);
+        }
+    }
+
+    let (glyf, loca) = glyf::build_tables(&encoded);
+
+    Some((glyf, loca, x_mins))
+}
+
+/// Rebuild `hmtx` from the transformed table, where left side bearings may be omitted when they
+/// equal the glyph `xMin`
+fn reconstruct_hmtx(data: &[u8], hhea: &[u8], x_mins: &[i16], length: usize) -> Option<Vec<u8>> {
+    let num_glyphs = x_mins.len();
+    let num_h_metrics = (sfnt::get_u16(hhea, 34)? as usize).min(num_glyphs);
+
+    let mut r = Reader::new(data);
+    let flags = r.read_u8()?;
+
+    let advances = (0..num_h_metrics)
+        .map(|_| r.read_u16())
+        .collect::<Option<Vec<_>>>()?;
+
+    let mut read_bearings = |present: bool, range: std::ops::Range<usize>| {
+        range
+            .map(|glyph| {
+                if present {
+                    r.read_i16()
+                } else {
+                    Some(x_mins[glyph])
+                }
+            })
+            .collect::<Option<Vec<_>>>()
+    };
+
+    let proportional = read_bearings(flags & 1 == 0, 0..num_h_metrics)?;
+    let monospaced = read_bearings(flags & 2 == 0, num_h_metrics..num_glyphs)?;
+
+    let mut out = Vec::with_capacity(length);
+    for (advance, bearing) in advances.iter().zip(proportional.iter()) {
+        out.put_u16(*advance);
+        out.put_i16(*bearing);
+    }
+    for bearing in monospaced {
+        out.put_i16(bearing);
+    }
+
+    Some(out)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io::Write;
+
+    use super::*;
+    use crate::{sfnt::Sfnt, tests::read_font};
+
+    #[test]
+    fn test_base128() {
+        assert_eq!(read_base128(&mut Reader::new(&[0x3F])), Some(63));
+        assert_eq!(read_base128(&mut Reader::new(&[0x81, 0x00])), Some(128));
+        assert_eq!(read_base128(&mut Reader::new(&[0x80, 0x01])), None);
+        assert_eq!(read_base128(&mut Reader::new(&[0xFF; 5])), None);
+    }
+
+    #[test]
+    fn test_255_u16() {
+        assert_eq!(read_255_u16(&mut Reader::new(&[252])), Some(252));
+        assert_eq!(read_255_u16(&mut Reader::new(&[255, 0])), Some(253));
+        assert_eq!(read_255_u16(&mut Reader::new(&[254, 0])), Some(506));
+        assert_eq!(
+            read_255_u16(&mut Reader::new(&[253, 0x03, 0xE8])),
+            Some(1000)
+        );
+    }
+
+    #[test]
+    fn test_triplet() {
+        // flag 1: positive y delta in a single byte
+        assert_eq!(read_triplet(1, &mut Reader::new(&[10])), Some((0, 10)));
+        // flag 20: 4-bit x and y deltas, both negative
+        assert_eq!(read_triplet(20, &mut Reader::new(&[0x12])), Some((-2, -3)));
+        // flag 127: 16-bit x and y deltas, both positive
+        assert_eq!(
+            read_triplet(127, &mut Reader::new(&[0x01, 0x00, 0x02, 0x00])),
+            Some((256, 512))
+        );
+    }
+
+    #[test]
+    fn test_decode_woff() {
+        let font = read_font(None);
+        let sfnt = Sfnt::parse(&font).unwrap();
+
+        let tables = sfnt
+            .tables()
+            .map(|(tag, table)| {
+                let mut encoder =
+                    flate2::write::ZlibEncoder::new(Vec::new(), flate2::Compression::default());
+                encoder.write_all(table).unwrap();
+                let compressed = encoder.finish().unwrap();
+
+                // tables which do not shrink are stored uncompressed
+                if compressed.len() < table.len() {
+                    (tag, table, compressed)
+                } else {
+                    (tag, table, table.to_vec())
+                }
+            })
+            .collect::<Vec<_>>();
+
+        let mut woff = Vec::new();
+        woff.extend_from_slice(&SIGNATURE_WOFF);
+        woff.put_u32(sfnt.version());
+        woff.put_u32(0);
+        woff.put_u16(tables.len() as u16);
+        woff.resize(44, 0);
+
+        let mut offset = 44 + 20 * tables.len();
+        for (tag, table, compressed) in tables.iter() {
+            woff.extend_from_slice(tag);
+            woff.put_u32(offset as u32);
+            woff.put_u32(compressed.len() as u32);
+            woff.put_u32(table.len() as u32);
+            woff.put_u32(sfnt::checksum(table));
+            offset += compressed.len();
+        }
+        for (_, _, compressed) in tables.iter() {
+            woff.extend_from_slice(compressed);
+        }
+
+        let decoded = decode(&woff).unwrap().unwrap();
+        let parsed = Sfnt::parse(&decoded).unwrap();
+        assert_eq!(parsed.table(b"glyf"), sfnt.table(b"glyf"));
+        assert_eq!(parsed.table(b"cmap"), sfnt.table(b"cmap"));
+
+        // plain fonts need no decoding
+        assert!(decode(&font).unwrap().is_none());
+    }
+}
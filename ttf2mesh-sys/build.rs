@@ -1,14 +1,20 @@
+#[cfg(feature = "regenerate-bindings")]
 extern crate bindgen;
 
-use std::env;
-use std::path::PathBuf;
+use std::{env, path::PathBuf};
 
 fn main() {
     println!("cargo:rerun-if-changed=wrapper.h");
     println!("cargo:rerun-if-changed=ttf2mesh/ttf2mesh.c");
+    println!("cargo:rerun-if-changed=wasm/libc.c");
+    println!("cargo:rerun-if-env-changed=WASI_SYSROOT");
 
-    #[cfg(unix)]
-    println!("cargo:rustc-link-lib=m");
+    let target_family = env::var("CARGO_CFG_TARGET_FAMILY").unwrap_or_default();
+    let wasm = env::var("CARGO_CFG_TARGET_ARCH").map_or(false, |arch| arch == "wasm32");
+
+    if target_family.split(',').any(|family| family == "unix") {
+        println!("cargo:rustc-link-lib=m");
+    }
 
     let manifest_dir = PathBuf::from(env!("CARGO_MANIFEST_DIR"));
 
@@ -17,22 +23,66 @@ fn main() {
         panic!("ttf2mesh.h not found - have you initialized the submodule? (`git submodule update --init`)");
     }
 
-    let bindings = bindgen::Builder::default()
+    #[cfg(feature = "regenerate-bindings")]
+    generate_bindings();
+
+    let mut build = cc::Build::new();
+    build
+        .flag("-Wall")
+        .flag("-pedantic")
+        .flag("-std=c99")
+        .file("ttf2mesh/ttf2mesh.c");
+
+    if wasm {
+        // wasm32 has no system C library: the headers come from a wasi-libc sysroot, e.g. the one
+        // of wasi-sdk. On wasip1 Rust links wasi-libc itself
+        let sysroot = env::var("WASI_SYSROOT").ok();
+        if let Some(sysroot) = &sysroot {
+            build.flag(&format!("--sysroot={}", sysroot));
+        }
+
+        // wasi-libc imports WASI system calls, which browsers don't provide, so only its headers
+        // are used and the needed functions are built from wasm/libc.c and src/wasm.rs
+        if env::var("CARGO_CFG_TARGET_OS").map_or(false, |os| os == "unknown") {
+            if let Some(sysroot) = &sysroot {
+                build.flag(&format!("-isystem{}/include/wasm32-wasi", sysroot));
+            }
+            build.file("wasm/libc.c");
+
+            // the allocator of src/wasm.rs has prefixed symbols, which don't clash with another
+            // C library linked into the module
+            for function in &["malloc", "calloc", "realloc", "free"] {
+                build.define(function, format!("ttf2mesh_{}", function).as_str());
+            }
+        }
+    } else {
+        build.flag("-D_POSIX_C_SOURCE=199309L");
+    }
+
+    build.compile("ttf2mesh");
+}
+
+/// Generate bindings from the header with bindgen (needs libclang). Without the
+/// `regenerate-bindings` feature, the checked-in `src/bindings.rs` is used instead
+#[cfg(feature = "regenerate-bindings")]
+fn generate_bindings() {
+    let mut builder = bindgen::Builder::default()
         .header("wrapper.h")
-        .parse_callbacks(Box::new(bindgen::CargoCallbacks::new()))
-        .generate()
-        .expect("Unable to generate bindings");
+        .allowlist_function("ttf_.*")
+        .allowlist_type("ttf_.*")
+        .allowlist_var("TTF_.*")
+        // the checked-in bindings are shared by 32-bit (wasm32) and 64-bit targets
+        .layout_tests(false)
+        .parse_callbacks(Box::new(bindgen::CargoCallbacks::new()));
+
+    if let Ok(sysroot) = env::var("WASI_SYSROOT") {
+        builder = builder.clang_arg(format!("--sysroot={}", sysroot));
+    }
+
+    let bindings = builder.generate().expect("Unable to generate bindings");
 
     let out_path = PathBuf::from(env::var("OUT_DIR").unwrap());
     bindings
         .write_to_file(out_path.join("bindings.rs"))
         .expect("Couldn't write bindings!");
-
-    cc::Build::new()
-        .flag("-Wall")
-        .flag("-pedantic")
-        .flag("-std=c99")
-        .flag("-D_POSIX_C_SOURCE=199309L")
-        .file("ttf2mesh/ttf2mesh.c")
-        .compile("ttf2mesh");
 }
@@ -0,0 +1,738 @@
+/* automatically generated by rust-bindgen 0.69.5 */
+
+#[repr(C)]
+#[derive(Copy, Clone, Debug, Default, Eq, Hash, Ord, PartialEq, PartialOrd)]
+pub struct __BindgenBitfieldUnit<Storage> {
+    storage: Storage,
+}
+impl<Storage> __BindgenBitfieldUnit<Storage> {
+    #[inline]
+    pub const fn new(storage: Storage) -> Self {
+        Self { storage }
+    }
+}
+impl<Storage> __BindgenBitfieldUnit<Storage>
+where
+    Storage: AsRef<[u8]> + AsMut<[u8]>,
+{
+    #[inline]
+    pub fn get_bit(&self, index: usize) -> bool {
+        debug_assert!(index / 8 < self.storage.as_ref().len());
+        let byte_index = index / 8;
+        let byte = self.storage.as_ref()[byte_index];
+        let bit_index = if cfg!(target_endian = "big") {
+            7 - (index % 8)
+        } else {
+            index % 8
+        };
+        let mask = 1 << bit_index;
+        byte & mask == mask
+    }
+    #[inline]
+    pub fn set_bit(&mut self, index: usize, val: bool) {
+        debug_assert!(index / 8 < self.storage.as_ref().len());
+        let byte_index = index / 8;
+        let byte = &mut self.storage.as_mut()[byte_index];
+        let bit_index = if cfg!(target_endian = "big") {
+            7 - (index % 8)
+        } else {
+            index % 8
+        };
+        let mask = 1 << bit_index;
+        if val {
+            *byte |= mask;
+        } else {
+            *byte &= !mask;
+        }
+    }
+    #[inline]
+    pub fn get(&self, bit_offset: usize, bit_width: u8) -> u64 {
+        debug_assert!(bit_width <= 64);
+        debug_assert!(bit_offset / 8 < self.storage.as_ref().len());
+        debug_assert!((bit_offset + (bit_width as usize)) / 8 <= self.storage.as_ref().len());
+        let mut val = 0;
+        for i in 0..(bit_width as usize) {
+            if self.get_bit(i + bit_offset) {
+                let index = if cfg!(target_endian = "big") {
+                    bit_width as usize - 1 - i
+                } else {
+                    i
+                };
+                val |= 1 << index;
+            }
+        }
+        val
+    }
+    #[inline]
+    pub fn set(&mut self, bit_offset: usize, bit_width: u8, val: u64) {
+        debug_assert!(bit_width <= 64);
+        debug_assert!(bit_offset / 8 < self.storage.as_ref().len());
+        debug_assert!((bit_offset + (bit_width as usize)) / 8 <= self.storage.as_ref().len());
+        for i in 0..(bit_width as usize) {
+            let mask = 1 << i;
+            let val_bit_is_set = val & mask == mask;
+            let index = if cfg!(target_endian = "big") {
+                bit_width as usize - 1 - i
+            } else {
+                i
+            };
+            self.set_bit(index + bit_offset, val_bit_is_set);
+        }
+    }
+}
+pub const TTF_DONE: u32 = 0;
+pub const TTF_ERR_NOMEM: u32 = 1;
+pub const TTF_ERR_SIZE: u32 = 2;
+pub const TTF_ERR_OPEN: u32 = 3;
+pub const TTF_ERR_VER: u32 = 4;
+pub const TTF_ERR_FMT: u32 = 5;
+pub const TTF_ERR_NOTAB: u32 = 6;
+pub const TTF_ERR_CSUM: u32 = 7;
+pub const TTF_ERR_UTAB: u32 = 8;
+pub const TTF_ERR_MESHER: u32 = 9;
+pub const TTF_ERR_NO_OUTLINE: u32 = 10;
+pub const TTF_ERR_WRITING: u32 = 11;
+pub const TTF_QUALITY_LOW: u32 = 10;
+pub const TTF_QUALITY_NORMAL: u32 = 20;
+pub const TTF_QUALITY_HIGH: u32 = 50;
+pub const TTF_FEATURES_DFLT: u32 = 0;
+pub const TTF_FEATURE_IGN_ERR: u32 = 1;
+pub type ttf_t = ttf_file;
+pub type ttf_glyph_t = ttf_glyph;
+pub type ttf_outline_t = ttf_outline;
+pub type ttf_point_t = ttf_point;
+pub type ttf_mesh_t = ttf_mesh;
+pub type ttf_mesh3d_t = ttf_mesh3d;
+#[repr(C)]
+#[derive(Debug, Copy, Clone)]
+pub struct ttf_file__bindgen_ty_1__bindgen_ty_1 {
+    pub _bitfield_align_1: [u8; 0],
+    pub _bitfield_1: __BindgenBitfieldUnit<[u8; 1usize]>,
+}
+impl ttf_file__bindgen_ty_1__bindgen_ty_1 {
+    #[inline]
+    pub fn bold(&self) -> u8 {
+        unsafe { ::std::mem::transmute(self._bitfield_1.get(0usize, 1u8) as u8) }
+    }
+    #[inline]
+    pub fn set_bold(&mut self, val: u8) {
+        unsafe {
+            let val: u8 = ::std::mem::transmute(val);
+            self._bitfield_1.set(0usize, 1u8, val as u64)
+        }
+    }
+    #[inline]
+    pub fn italic(&self) -> u8 {
+        unsafe { ::std::mem::transmute(self._bitfield_1.get(1usize, 1u8) as u8) }
+    }
+    #[inline]
+    pub fn set_italic(&mut self, val: u8) {
+        unsafe {
+            let val: u8 = ::std::mem::transmute(val);
+            self._bitfield_1.set(1usize, 1u8, val as u64)
+        }
+    }
+    #[inline]
+    pub fn underline(&self) -> u8 {
+        unsafe { ::std::mem::transmute(self._bitfield_1.get(2usize, 1u8) as u8) }
+    }
+    #[inline]
+    pub fn set_underline(&mut self, val: u8) {
+        unsafe {
+            let val: u8 = ::std::mem::transmute(val);
+            self._bitfield_1.set(2usize, 1u8, val as u64)
+        }
+    }
+    #[inline]
+    pub fn outline(&self) -> u8 {
+        unsafe { ::std::mem::transmute(self._bitfield_1.get(3usize, 1u8) as u8) }
+    }
+    #[inline]
+    pub fn set_outline(&mut self, val: u8) {
+        unsafe {
+            let val: u8 = ::std::mem::transmute(val);
+            self._bitfield_1.set(3usize, 1u8, val as u64)
+        }
+    }
+    #[inline]
+    pub fn shadow(&self) -> u8 {
+        unsafe { ::std::mem::transmute(self._bitfield_1.get(4usize, 1u8) as u8) }
+    }
+    #[inline]
+    pub fn set_shadow(&mut self, val: u8) {
+        unsafe {
+            let val: u8 = ::std::mem::transmute(val);
+            self._bitfield_1.set(4usize, 1u8, val as u64)
+        }
+    }
+    #[inline]
+    pub fn condensed(&self) -> u8 {
+        unsafe { ::std::mem::transmute(self._bitfield_1.get(5usize, 1u8) as u8) }
+    }
+    #[inline]
+    pub fn set_condensed(&mut self, val: u8) {
+        unsafe {
+            let val: u8 = ::std::mem::transmute(val);
+            self._bitfield_1.set(5usize, 1u8, val as u64)
+        }
+    }
+    #[inline]
+    pub fn extended(&self) -> u8 {
+        unsafe { ::std::mem::transmute(self._bitfield_1.get(6usize, 1u8) as u8) }
+    }
+    #[inline]
+    pub fn set_extended(&mut self, val: u8) {
+        unsafe {
+            let val: u8 = ::std::mem::transmute(val);
+            self._bitfield_1.set(6usize, 1u8, val as u64)
+        }
+    }
+    #[inline]
+    pub fn new_bitfield_1(
+        bold: u8,
+        italic: u8,
+        underline: u8,
+        outline: u8,
+        shadow: u8,
+        condensed: u8,
+        extended: u8,
+    ) -> __BindgenBitfieldUnit<[u8; 1usize]> {
+        let mut __bindgen_bitfield_unit: __BindgenBitfieldUnit<[u8; 1usize]> = Default::default();
+        __bindgen_bitfield_unit.set(0usize, 1u8, {
+            let bold: u8 = unsafe { ::std::mem::transmute(bold) };
+            bold as u64
+        });
+        __bindgen_bitfield_unit.set(1usize, 1u8, {
+            let italic: u8 = unsafe { ::std::mem::transmute(italic) };
+            italic as u64
+        });
+        __bindgen_bitfield_unit.set(2usize, 1u8, {
+            let underline: u8 = unsafe { ::std::mem::transmute(underline) };
+            underline as u64
+        });
+        __bindgen_bitfield_unit.set(3usize, 1u8, {
+            let outline: u8 = unsafe { ::std::mem::transmute(outline) };
+            outline as u64
+        });
+        __bindgen_bitfield_unit.set(4usize, 1u8, {
+            let shadow: u8 = unsafe { ::std::mem::transmute(shadow) };
+            shadow as u64
+        });
+        __bindgen_bitfield_unit.set(5usize, 1u8, {
+            let condensed: u8 = unsafe { ::std::mem::transmute(condensed) };
+            condensed as u64
+        });
+        __bindgen_bitfield_unit.set(6usize, 1u8, {
+            let extended: u8 = unsafe { ::std::mem::transmute(extended) };
+            extended as u64
+        });
+        __bindgen_bitfield_unit
+    }
+}
+#[repr(C)]
+#[derive(Debug, Copy, Clone)]
+pub struct ttf_file__bindgen_ty_1 {
+    pub rev: f32,
+    pub macStyle: ttf_file__bindgen_ty_1__bindgen_ty_1,
+}
+#[repr(C)]
+#[derive(Debug, Copy, Clone)]
+pub struct ttf_file__bindgen_ty_2__bindgen_ty_1 {
+    pub _bitfield_align_1: [u16; 0],
+    pub _bitfield_1: __BindgenBitfieldUnit<[u8; 2usize]>,
+}
+impl ttf_file__bindgen_ty_2__bindgen_ty_1 {
+    #[inline]
+    pub fn italic(&self) -> u16 {
+        unsafe { ::std::mem::transmute(self._bitfield_1.get(0usize, 1u8) as u16) }
+    }
+    #[inline]
+    pub fn set_italic(&mut self, val: u16) {
+        unsafe {
+            let val: u16 = ::std::mem::transmute(val);
+            self._bitfield_1.set(0usize, 1u8, val as u64)
+        }
+    }
+    #[inline]
+    pub fn underscore(&self) -> u16 {
+        unsafe { ::std::mem::transmute(self._bitfield_1.get(1usize, 1u8) as u16) }
+    }
+    #[inline]
+    pub fn set_underscore(&mut self, val: u16) {
+        unsafe {
+            let val: u16 = ::std::mem::transmute(val);
+            self._bitfield_1.set(1usize, 1u8, val as u64)
+        }
+    }
+    #[inline]
+    pub fn negative(&self) -> u16 {
+        unsafe { ::std::mem::transmute(self._bitfield_1.get(2usize, 1u8) as u16) }
+    }
+    #[inline]
+    pub fn set_negative(&mut self, val: u16) {
+        unsafe {
+            let val: u16 = ::std::mem::transmute(val);
+            self._bitfield_1.set(2usize, 1u8, val as u64)
+        }
+    }
+    #[inline]
+    pub fn outlined(&self) -> u16 {
+        unsafe { ::std::mem::transmute(self._bitfield_1.get(3usize, 1u8) as u16) }
+    }
+    #[inline]
+    pub fn set_outlined(&mut self, val: u16) {
+        unsafe {
+            let val: u16 = ::std::mem::transmute(val);
+            self._bitfield_1.set(3usize, 1u8, val as u64)
+        }
+    }
+    #[inline]
+    pub fn strikeout(&self) -> u16 {
+        unsafe { ::std::mem::transmute(self._bitfield_1.get(4usize, 1u8) as u16) }
+    }
+    #[inline]
+    pub fn set_strikeout(&mut self, val: u16) {
+        unsafe {
+            let val: u16 = ::std::mem::transmute(val);
+            self._bitfield_1.set(4usize, 1u8, val as u64)
+        }
+    }
+    #[inline]
+    pub fn bold(&self) -> u16 {
+        unsafe { ::std::mem::transmute(self._bitfield_1.get(5usize, 1u8) as u16) }
+    }
+    #[inline]
+    pub fn set_bold(&mut self, val: u16) {
+        unsafe {
+            let val: u16 = ::std::mem::transmute(val);
+            self._bitfield_1.set(5usize, 1u8, val as u64)
+        }
+    }
+    #[inline]
+    pub fn regular(&self) -> u16 {
+        unsafe { ::std::mem::transmute(self._bitfield_1.get(6usize, 1u8) as u16) }
+    }
+    #[inline]
+    pub fn set_regular(&mut self, val: u16) {
+        unsafe {
+            let val: u16 = ::std::mem::transmute(val);
+            self._bitfield_1.set(6usize, 1u8, val as u64)
+        }
+    }
+    #[inline]
+    pub fn utm(&self) -> u16 {
+        unsafe { ::std::mem::transmute(self._bitfield_1.get(7usize, 1u8) as u16) }
+    }
+    #[inline]
+    pub fn set_utm(&mut self, val: u16) {
+        unsafe {
+            let val: u16 = ::std::mem::transmute(val);
+            self._bitfield_1.set(7usize, 1u8, val as u64)
+        }
+    }
+    #[inline]
+    pub fn oblique(&self) -> u16 {
+        unsafe { ::std::mem::transmute(self._bitfield_1.get(8usize, 1u8) as u16) }
+    }
+    #[inline]
+    pub fn set_oblique(&mut self, val: u16) {
+        unsafe {
+            let val: u16 = ::std::mem::transmute(val);
+            self._bitfield_1.set(8usize, 1u8, val as u64)
+        }
+    }
+    #[inline]
+    pub fn new_bitfield_1(
+        italic: u16,
+        underscore: u16,
+        negative: u16,
+        outlined: u16,
+        strikeout: u16,
+        bold: u16,
+        regular: u16,
+        utm: u16,
+        oblique: u16,
+    ) -> __BindgenBitfieldUnit<[u8; 2usize]> {
+        let mut __bindgen_bitfield_unit: __BindgenBitfieldUnit<[u8; 2usize]> = Default::default();
+        __bindgen_bitfield_unit.set(0usize, 1u8, {
+            let italic: u16 = unsafe { ::std::mem::transmute(italic) };
+            italic as u64
+        });
+        __bindgen_bitfield_unit.set(1usize, 1u8, {
+            let underscore: u16 = unsafe { ::std::mem::transmute(underscore) };
+            underscore as u64
+        });
+        __bindgen_bitfield_unit.set(2usize, 1u8, {
+            let negative: u16 = unsafe { ::std::mem::transmute(negative) };
+            negative as u64
+        });
+        __bindgen_bitfield_unit.set(3usize, 1u8, {
+            let outlined: u16 = unsafe { ::std::mem::transmute(outlined) };
+            outlined as u64
+        });
+        __bindgen_bitfield_unit.set(4usize, 1u8, {
+            let strikeout: u16 = unsafe { ::std::mem::transmute(strikeout) };
+            strikeout as u64
+        });
+        __bindgen_bitfield_unit.set(5usize, 1u8, {
+            let bold: u16 = unsafe { ::std::mem::transmute(bold) };
+            bold as u64
+        });
+        __bindgen_bitfield_unit.set(6usize, 1u8, {
+            let regular: u16 = unsafe { ::std::mem::transmute(regular) };
+            regular as u64
+        });
+        __bindgen_bitfield_unit.set(7usize, 1u8, {
+            let utm: u16 = unsafe { ::std::mem::transmute(utm) };
+            utm as u64
+        });
+        __bindgen_bitfield_unit.set(8usize, 1u8, {
+            let oblique: u16 = unsafe { ::std::mem::transmute(oblique) };
+            oblique as u64
+        });
+        __bindgen_bitfield_unit
+    }
+}
+#[repr(C)]
+#[derive(Debug, Copy, Clone)]
+pub struct ttf_file__bindgen_ty_2 {
+    pub xAvgCharWidth: f32,
+    pub usWeightClass: u16,
+    pub usWidthClass: u16,
+    pub fsType: u16,
+    pub ySubscriptXSize: f32,
+    pub ySubscriptYSize: f32,
+    pub ySubscriptXOffset: f32,
+    pub ySubscriptYOffset: f32,
+    pub ySuperscriptXSize: f32,
+    pub ySuperscriptYSize: f32,
+    pub ySuperscriptXOffset: f32,
+    pub ySuperscriptYOffset: f32,
+    pub yStrikeoutSize: f32,
+    pub yStrikeoutPosition: f32,
+    pub sFamilyClass: i16,
+    pub panose: [u8; 10usize],
+    pub fsSelection: ttf_file__bindgen_ty_2__bindgen_ty_1,
+    pub sTypoAscender: f32,
+    pub sTypoDescender: f32,
+    pub sTypoLineGap: f32,
+    pub usWinAscent: f32,
+    pub usWinDescent: f32,
+}
+#[repr(C)]
+#[derive(Debug, Copy, Clone)]
+pub struct ttf_file__bindgen_ty_3 {
+    pub copyright: *const ::std::os::raw::c_char,
+    pub family: *const ::std::os::raw::c_char,
+    pub subfamily: *const ::std::os::raw::c_char,
+    pub unique_id: *const ::std::os::raw::c_char,
+    pub full_name: *const ::std::os::raw::c_char,
+    pub version: *const ::std::os::raw::c_char,
+    pub ps_name: *const ::std::os::raw::c_char,
+    pub trademark: *const ::std::os::raw::c_char,
+    pub manufacturer: *const ::std::os::raw::c_char,
+    pub designer: *const ::std::os::raw::c_char,
+    pub description: *const ::std::os::raw::c_char,
+    pub url_vendor: *const ::std::os::raw::c_char,
+    pub url_designer: *const ::std::os::raw::c_char,
+    pub license_desc: *const ::std::os::raw::c_char,
+    pub locense_url: *const ::std::os::raw::c_char,
+    pub sample_text: *const ::std::os::raw::c_char,
+}
+#[repr(C)]
+#[derive(Debug, Copy, Clone)]
+pub struct ttf_file__bindgen_ty_4 {
+    pub ascender: f32,
+    pub descender: f32,
+    pub lineGap: f32,
+    pub advanceWidthMax: f32,
+    pub minLSideBearing: f32,
+    pub minRSideBearing: f32,
+    pub xMaxExtent: f32,
+    pub caretSlope: f32,
+}
+#[repr(C)]
+#[derive(Debug, Copy, Clone)]
+pub struct ttf_file {
+    pub nchars: ::std::os::raw::c_int,
+    pub nglyphs: ::std::os::raw::c_int,
+    pub chars: *mut u16,
+    pub char2glyph: *mut u16,
+    pub glyphs: *mut ttf_glyph_t,
+    pub filename: *const ::std::os::raw::c_char,
+    pub glyf_csum: u32,
+    pub ubranges: [u32; 6usize],
+    pub head: ttf_file__bindgen_ty_1,
+    pub os2: ttf_file__bindgen_ty_2,
+    pub names: ttf_file__bindgen_ty_3,
+    pub hhea: ttf_file__bindgen_ty_4,
+    pub user_data: *mut ::std::os::raw::c_void,
+}
+#[repr(C)]
+#[derive(Debug, Copy, Clone)]
+pub struct ttf_glyph {
+    pub index: ::std::os::raw::c_int,
+    pub symbol: ::std::os::raw::c_int,
+    pub npoints: ::std::os::raw::c_int,
+    pub ncontours: ::std::os::raw::c_int,
+    pub _bitfield_align_1: [u32; 0],
+    pub _bitfield_1: __BindgenBitfieldUnit<[u8; 4usize]>,
+    pub xbounds: [f32; 2usize],
+    pub ybounds: [f32; 2usize],
+    pub advance: f32,
+    pub lbearing: f32,
+    pub rbearing: f32,
+    pub outline: *mut ttf_outline_t,
+    pub user_data: *mut ::std::os::raw::c_void,
+}
+impl ttf_glyph {
+    #[inline]
+    pub fn composite(&self) -> u32 {
+        unsafe { ::std::mem::transmute(self._bitfield_1.get(0usize, 1u8) as u32) }
+    }
+    #[inline]
+    pub fn set_composite(&mut self, val: u32) {
+        unsafe {
+            let val: u32 = ::std::mem::transmute(val);
+            self._bitfield_1.set(0usize, 1u8, val as u64)
+        }
+    }
+    #[inline]
+    pub fn new_bitfield_1(composite: u32) -> __BindgenBitfieldUnit<[u8; 4usize]> {
+        let mut __bindgen_bitfield_unit: __BindgenBitfieldUnit<[u8; 4usize]> = Default::default();
+        __bindgen_bitfield_unit.set(0usize, 1u8, {
+            let composite: u32 = unsafe { ::std::mem::transmute(composite) };
+            composite as u64
+        });
+        __bindgen_bitfield_unit
+    }
+}
+#[repr(C)]
+#[derive(Debug, Copy, Clone)]
+pub struct ttf_point {
+    pub x: f32,
+    pub y: f32,
+    pub _bitfield_align_1: [u32; 0],
+    pub _bitfield_1: __BindgenBitfieldUnit<[u8; 4usize]>,
+}
+impl ttf_point {
+    #[inline]
+    pub fn spl(&self) -> u32 {
+        unsafe { ::std::mem::transmute(self._bitfield_1.get(0usize, 1u8) as u32) }
+    }
+    #[inline]
+    pub fn set_spl(&mut self, val: u32) {
+        unsafe {
+            let val: u32 = ::std::mem::transmute(val);
+            self._bitfield_1.set(0usize, 1u8, val as u64)
+        }
+    }
+    #[inline]
+    pub fn onc(&self) -> u32 {
+        unsafe { ::std::mem::transmute(self._bitfield_1.get(1usize, 1u8) as u32) }
+    }
+    #[inline]
+    pub fn set_onc(&mut self, val: u32) {
+        unsafe {
+            let val: u32 = ::std::mem::transmute(val);
+            self._bitfield_1.set(1usize, 1u8, val as u64)
+        }
+    }
+    #[inline]
+    pub fn res(&self) -> u32 {
+        unsafe { ::std::mem::transmute(self._bitfield_1.get(2usize, 30u8) as u32) }
+    }
+    #[inline]
+    pub fn set_res(&mut self, val: u32) {
+        unsafe {
+            let val: u32 = ::std::mem::transmute(val);
+            self._bitfield_1.set(2usize, 30u8, val as u64)
+        }
+    }
+    #[inline]
+    pub fn new_bitfield_1(spl: u32, onc: u32, res: u32) -> __BindgenBitfieldUnit<[u8; 4usize]> {
+        let mut __bindgen_bitfield_unit: __BindgenBitfieldUnit<[u8; 4usize]> = Default::default();
+        __bindgen_bitfield_unit.set(0usize, 1u8, {
+            let spl: u32 = unsafe { ::std::mem::transmute(spl) };
+            spl as u64
+        });
+        __bindgen_bitfield_unit.set(1usize, 1u8, {
+            let onc: u32 = unsafe { ::std::mem::transmute(onc) };
+            onc as u64
+        });
+        __bindgen_bitfield_unit.set(2usize, 30u8, {
+            let res: u32 = unsafe { ::std::mem::transmute(res) };
+            res as u64
+        });
+        __bindgen_bitfield_unit
+    }
+}
+#[repr(C)]
+#[derive(Debug, Copy, Clone)]
+pub struct ttf_outline__bindgen_ty_1 {
+    pub length: ::std::os::raw::c_int,
+    pub subglyph_id: ::std::os::raw::c_int,
+    pub subglyph_order: ::std::os::raw::c_int,
+    pub pt: *mut ttf_point_t,
+}
+#[repr(C)]
+#[derive(Debug, Copy, Clone)]
+pub struct ttf_outline {
+    pub total_points: ::std::os::raw::c_int,
+    pub ncontours: ::std::os::raw::c_int,
+    pub cont: [ttf_outline__bindgen_ty_1; 1usize],
+}
+#[repr(C)]
+#[derive(Debug, Copy, Clone)]
+pub struct ttf_mesh__bindgen_ty_1 {
+    pub x: f32,
+    pub y: f32,
+}
+#[repr(C)]
+#[derive(Debug, Copy, Clone)]
+pub struct ttf_mesh__bindgen_ty_2 {
+    pub v1: ::std::os::raw::c_int,
+    pub v2: ::std::os::raw::c_int,
+    pub v3: ::std::os::raw::c_int,
+}
+#[repr(C)]
+#[derive(Debug, Copy, Clone)]
+pub struct ttf_mesh {
+    pub nvert: ::std::os::raw::c_int,
+    pub nfaces: ::std::os::raw::c_int,
+    pub vert: *mut ttf_mesh__bindgen_ty_1,
+    pub faces: *mut ttf_mesh__bindgen_ty_2,
+    pub outline: *mut ttf_outline_t,
+}
+#[repr(C)]
+#[derive(Debug, Copy, Clone)]
+pub struct ttf_mesh3d__bindgen_ty_1 {
+    pub x: f32,
+    pub y: f32,
+    pub z: f32,
+}
+#[repr(C)]
+#[derive(Debug, Copy, Clone)]
+pub struct ttf_mesh3d__bindgen_ty_2 {
+    pub v1: ::std::os::raw::c_int,
+    pub v2: ::std::os::raw::c_int,
+    pub v3: ::std::os::raw::c_int,
+}
+#[repr(C)]
+#[derive(Debug, Copy, Clone)]
+pub struct ttf_mesh3d__bindgen_ty_3 {
+    pub x: f32,
+    pub y: f32,
+    pub z: f32,
+}
+#[repr(C)]
+#[derive(Debug, Copy, Clone)]
+pub struct ttf_mesh3d {
+    pub nvert: ::std::os::raw::c_int,
+    pub nfaces: ::std::os::raw::c_int,
+    pub vert: *mut ttf_mesh3d__bindgen_ty_1,
+    pub faces: *mut ttf_mesh3d__bindgen_ty_2,
+    pub normals: *mut ttf_mesh3d__bindgen_ty_3,
+    pub outline: *mut ttf_outline_t,
+}
+extern "C" {
+    pub fn ttf_list_fonts(
+        directories: *mut *const ::std::os::raw::c_char,
+        dir_count: ::std::os::raw::c_int,
+        file_mask: *const ::std::os::raw::c_char,
+    ) -> *mut *mut ttf_t;
+}
+extern "C" {
+    pub fn ttf_list_system_fonts(file_mask: *const ::std::os::raw::c_char) -> *mut *mut ttf_t;
+}
+extern "C" {
+    pub fn ttf_list_match(
+        list: *mut *mut ttf_t,
+        deflt: *mut ttf_t,
+        requirements: *const ::std::os::raw::c_char,
+        ...
+    ) -> *mut ttf_t;
+}
+extern "C" {
+    pub fn ttf_free_list(list: *mut *mut ttf_t);
+}
+extern "C" {
+    pub fn ttf_load_from_file(
+        filename: *const ::std::os::raw::c_char,
+        output: *mut *mut ttf_t,
+        headers_only: bool,
+    ) -> ::std::os::raw::c_int;
+}
+extern "C" {
+    pub fn ttf_load_from_mem(
+        data: *const u8,
+        size: ::std::os::raw::c_int,
+        output: *mut *mut ttf_t,
+        headers_only: bool,
+    ) -> ::std::os::raw::c_int;
+}
+extern "C" {
+    pub fn ttf_find_glyph(ttf: *const ttf_t, utf16: u16) -> ::std::os::raw::c_int;
+}
+extern "C" {
+    pub fn ttf_linear_outline(glyph: *const ttf_glyph_t, quality: u8) -> *mut ttf_outline_t;
+}
+extern "C" {
+    pub fn ttf_outline_evenodd_base(
+        outline: *const ttf_outline_t,
+        point: *const f32,
+        subglyph_order: ::std::os::raw::c_int,
+        dist: *mut f32,
+    ) -> ::std::os::raw::c_int;
+}
+extern "C" {
+    pub fn ttf_outline_evenodd(
+        outline: *const ttf_outline_t,
+        point: *const f32,
+        subglyph_order: ::std::os::raw::c_int,
+    ) -> bool;
+}
+extern "C" {
+    pub fn ttf_outline_contour_info(
+        outline: *const ttf_outline_t,
+        subglyph_order: ::std::os::raw::c_int,
+        contour: ::std::os::raw::c_int,
+        nested_to: *mut ::std::os::raw::c_int,
+    ) -> ::std::os::raw::c_int;
+}
+extern "C" {
+    pub fn ttf_free_outline(outline: *mut ttf_outline_t);
+}
+extern "C" {
+    pub fn ttf_glyph2mesh(
+        glyph: *mut ttf_glyph_t,
+        output: *mut *mut ttf_mesh_t,
+        quality: u8,
+        features: ::std::os::raw::c_int,
+    ) -> ::std::os::raw::c_int;
+}
+extern "C" {
+    pub fn ttf_glyph2mesh3d(
+        glyph: *mut ttf_glyph_t,
+        output: *mut *mut ttf_mesh3d_t,
+        quality: u8,
+        features: ::std::os::raw::c_int,
+        depth: f32,
+    ) -> ::std::os::raw::c_int;
+}
+extern "C" {
+    pub fn ttf_free_mesh(mesh: *mut ttf_mesh_t);
+}
+extern "C" {
+    pub fn ttf_free_mesh3d(mesh: *mut ttf_mesh3d_t);
+}
+extern "C" {
+    pub fn ttf_export_to_obj(
+        ttf: *mut ttf_t,
+        file_name: *const ::std::os::raw::c_char,
+        quality: u8,
+    ) -> ::std::os::raw::c_int;
+}
+extern "C" {
+    pub fn ttf_free(ttf: *mut ttf_t);
+}
@@ -1,5 +1,34 @@
 #![allow(non_upper_case_globals)]
 #![allow(non_camel_case_types)]
 #![allow(non_snake_case)]
+#![allow(clippy::useless_transmute)]
+#![allow(clippy::too_many_arguments)]
 
+#[cfg(feature = "regenerate-bindings")]
 include!(concat!(env!("OUT_DIR"), "/bindings.rs"));
+
+#[cfg(not(feature = "regenerate-bindings"))]
+include!("bindings.rs");
+
+#[cfg(all(target_arch = "wasm32", target_os = "unknown"))]
+mod wasm;
+
+#[cfg(all(test, feature = "regenerate-bindings"))]
+mod tests {
+    use std::{fs, path::PathBuf};
+
+    #[test]
+    fn test_checked_in_bindings_match_header() {
+        let generated = include_str!(concat!(env!("OUT_DIR"), "/bindings.rs"));
+        let path = PathBuf::from(env!("CARGO_MANIFEST_DIR")).join("src/bindings.rs");
+        let checked_in = fs::read_to_string(&path)
+            .unwrap_or_else(|err| panic!("could not read {}: {}", path.display(), err));
+
+        assert!(
+            generated == checked_in,
+            "{} is out of date with ttf2mesh.h, copy the generated bindings from {}/bindings.rs",
+            path.display(),
+            env!("OUT_DIR"),
+        );
+    }
+}
@@ -0,0 +1,79 @@
+//! `malloc` and friends for the C library on `wasm32-unknown-unknown`, see `wasm/libc.c`.
+//! Allocations go through the Rust global allocator, with their size stored in front of them.
+//!
+//! The symbols are prefixed with `ttf2mesh_`, so that they don't clash with the allocator of
+//! another C library linked into the module; `build.rs` defines `malloc` as `ttf2mesh_malloc`
+//! and so on for the C sources
+use std::{
+    alloc::{self, Layout},
+    os::raw::c_void,
+    ptr,
+};
+
+/// Alignment of `max_align_t`, which is also the size of the header
+const ALIGN: usize = 16;
+
+fn layout(size: usize) -> Option<Layout> {
+    Layout::from_size_align(size.checked_add(ALIGN)?, ALIGN).ok()
+}
+
+/// Store the size in the header of a block, returning the pointer after the header
+unsafe fn with_header(block: *mut u8, size: usize) -> *mut c_void {
+    if block.is_null() {
+        return ptr::null_mut();
+    }
+
+    (block as *mut usize).write(size);
+    block.add(ALIGN) as *mut c_void
+}
+
+/// Start and size of the block of a pointer returned by `malloc`
+unsafe fn block(ptr: *mut c_void) -> (*mut u8, usize) {
+    let block = (ptr as *mut u8).sub(ALIGN);
+    (block, (block as *mut usize).read())
+}
+
+#[no_mangle]
+pub unsafe extern "C" fn ttf2mesh_malloc(size: usize) -> *mut c_void {
+    match layout(size) {
+        Some(layout) => with_header(alloc::alloc(layout), size),
+        None => ptr::null_mut(),
+    }
+}
+
+#[no_mangle]
+pub unsafe extern "C" fn ttf2mesh_calloc(count: usize, size: usize) -> *mut c_void {
+    let size = match count.checked_mul(size) {
+        Some(size) => size,
+        None => return ptr::null_mut(),
+    };
+
+    match layout(size) {
+        Some(layout) => with_header(alloc::alloc_zeroed(layout), size),
+        None => ptr::null_mut(),
+    }
+}
+
+#[no_mangle]
+pub unsafe extern "C" fn ttf2mesh_realloc(ptr: *mut c_void, size: usize) -> *mut c_void {
+    if ptr.is_null() {
+        return ttf2mesh_malloc(size);
+    }
+
+    let (block, old_size) = block(ptr);
+    match layout(size) {
+        Some(new_layout) => with_header(
+            alloc::realloc(block, layout(old_size).unwrap(), new_layout.size()),
+            size,
+        ),
+        None => ptr::null_mut(),
+    }
+}
+
+#[no_mangle]
+pub unsafe extern "C" fn ttf2mesh_free(ptr: *mut c_void) {
+    if !ptr.is_null() {
+        let (block, size) = block(ptr);
+        alloc::dealloc(block, layout(size).unwrap());
+    }
+}
@@ -0,0 +1,258 @@
+/*
+ * The parts of the C library which ttf2mesh uses, for wasm32-unknown-unknown. wasi-libc can't
+ * be linked there: it imports the WASI system calls (wasi_snapshot_preview1), which browsers
+ * don't provide. Only the declarations of its headers are used.
+ *
+ * Memory is allocated by the Rust global allocator (ttf2mesh_malloc and friends in src/wasm.rs,
+ * which build.rs maps malloc, calloc, realloc and free to), and memcpy, memset and the math
+ * functions come from Rust's compiler-builtins. There is no file system: opening files and
+ * directories fails. Formatted output is not supported either: the printf family writes nothing
+ * and returns -1, so the diagnostics of the C library are dropped.
+ */
+#include <ctype.h>
+#include <dirent.h>
+#include <stdarg.h>
+#include <stdio.h>
+#include <stdlib.h>
+#include <string.h>
+#include <sys/stat.h>
+
+/* ctype.h may define these as macros */
+#undef isalnum
+#undef isalpha
+#undef isdigit
+#undef islower
+#undef isspace
+#undef isupper
+#undef tolower
+#undef toupper
+
+FILE *const stdin = NULL;
+FILE *const stdout = NULL;
+FILE *const stderr = NULL;
+
+/* string.h */
+
+size_t strlen(const char *s)
+{
+    const char *end = s;
+    while (*end) end++;
+    return end - s;
+}
+
+size_t strnlen(const char *s, size_t n)
+{
+    size_t len = 0;
+    while (len < n && s[len]) len++;
+    return len;
+}
+
+int strcmp(const char *a, const char *b)
+{
+    while (*a && *a == *b) a++, b++;
+    return (unsigned char)*a - (unsigned char)*b;
+}
+
+int strncmp(const char *a, const char *b, size_t n)
+{
+    for (; n > 0; n--, a++, b++)
+        if (*a != *b || !*a) return (unsigned char)*a - (unsigned char)*b;
+    return 0;
+}
+
+char *strcpy(char *restrict dst, const char *restrict src)
+{
+    char *d = dst;
+    while ((*d++ = *src++));
+    return dst;
+}
+
+char *strncpy(char *restrict dst, const char *restrict src, size_t n)
+{
+    size_t i = 0;
+    for (; i < n && src[i]; i++) dst[i] = src[i];
+    for (; i < n; i++) dst[i] = 0;
+    return dst;
+}
+
+char *strcat(char *restrict dst, const char *restrict src)
+{
+    strcpy(dst + strlen(dst), src);
+    return dst;
+}
+
+char *strchr(const char *s, int c)
+{
+    for (;; s++) {
+        if (*s == (char)c) return (char *)s;
+        if (!*s) return NULL;
+    }
+}
+
+char *strrchr(const char *s, int c)
+{
+    const char *found = NULL;
+    for (;; s++) {
+        if (*s == (char)c) found = s;
+        if (!*s) return (char *)found;
+    }
+}
+
+char *strstr(const char *haystack, const char *needle)
+{
+    size_t len = strlen(needle);
+    for (; *haystack; haystack++)
+        if (strncmp(haystack, needle, len) == 0) return (char *)haystack;
+    return len == 0 ? (char *)haystack : NULL;
+}
+
+char *strdup(const char *s)
+{
+    char *copy = malloc(strlen(s) + 1);
+    return copy ? strcpy(copy, s) : NULL;
+}
+
+/* ctype.h, for the C locale */
+
+int isdigit(int c) { return (unsigned)c - '0' < 10; }
+int islower(int c) { return (unsigned)c - 'a' < 26; }
+int isupper(int c) { return (unsigned)c - 'A' < 26; }
+int isalpha(int c) { return islower(c) || isupper(c); }
+int isalnum(int c) { return isalpha(c) || isdigit(c); }
+int isspace(int c) { return c == ' ' || (unsigned)c - '\t' < 5; }
+int tolower(int c) { return isupper(c) ? c + 'a' - 'A' : c; }
+int toupper(int c) { return islower(c) ? c - 'a' + 'A' : c; }
+
+/* stdlib.h */
+
+int abs(int x) { return x < 0 ? -x : x; }
+
+long strtol(const char *restrict s, char **restrict end, int base)
+{
+    long value = 0;
+    int negative = 0;
+
+    while (isspace(*s)) s++;
+    if (*s == '-' || *s == '+') negative = *s++ == '-';
+    if (base == 0) base = 10;
+    for (;; s++) {
+        int digit = isdigit(*s) ? *s - '0' : isalpha(*s) ? tolower(*s) - 'a' + 10 : base;
+        if (digit >= base) break;
+        value = value * base + digit;
+    }
+    if (end) *end = (char *)s;
+    return negative ? -value : value;
+}
+
+int atoi(const char *s) { return (int)strtol(s, NULL, 10); }
+
+static void swap(char *a, char *b, size_t size)
+{
+    for (size_t i = 0; i < size; i++) {
+        char t = a[i];
+        a[i] = b[i];
+        b[i] = t;
+    }
+}
+
+/* heapsort: no recursion and no allocation */
+static void sift_down(char *base, size_t root, size_t count, size_t size,
+                      int (*compare)(const void *, const void *))
+{
+    for (size_t child; (child = 2 * root + 1) < count; root = child) {
+        if (child + 1 < count && compare(base + child * size, base + (child + 1) * size) < 0)
+            child++;
+        if (compare(base + root * size, base + child * size) >= 0) return;
+        swap(base + root * size, base + child * size, size);
+    }
+}
+
+void qsort(void *base, size_t count, size_t size, int (*compare)(const void *, const void *))
+{
+    char *b = base;
+
+    for (size_t i = count / 2; i-- > 0;) sift_down(b, i, count, size, compare);
+    for (size_t end = count; end-- > 1;) {
+        swap(b, b + end * size, size);
+        sift_down(b, 0, end, size, compare);
+    }
+}
+
+void abort(void) { __builtin_trap(); }
+void exit(int status) { (void)status; __builtin_trap(); }
+
+void __assert_fail(const char *expr, const char *file, int line, const char *func)
+{
+    (void)expr, (void)file, (void)line, (void)func;
+    __builtin_trap();
+}
+
+/* stdio.h, dirent.h and sys/stat.h: no file system */
+
+FILE *fopen(const char *restrict path, const char *restrict mode)
+{
+    (void)path, (void)mode;
+    return NULL;
+}
+
+int fclose(FILE *f) { (void)f; return EOF; }
+int fseek(FILE *f, long offset, int whence) { (void)f, (void)offset, (void)whence; return -1; }
+long ftell(FILE *f) { (void)f; return -1; }
+
+size_t fread(void *restrict ptr, size_t size, size_t count, FILE *restrict f)
+{
+    (void)ptr, (void)size, (void)count, (void)f;
+    return 0;
+}
+
+size_t fwrite(const void *restrict ptr, size_t size, size_t count, FILE *restrict f)
+{
+    (void)ptr, (void)size, (void)count, (void)f;
+    return 0;
+}
+
+int fputc(int c, FILE *f) { (void)c, (void)f; return EOF; }
+int fputs(const char *restrict s, FILE *restrict f) { (void)s, (void)f; return EOF; }
+
+int vsnprintf(char *restrict s, size_t n, const char *restrict format, va_list args)
+{
+    (void)format, (void)args;
+    if (n > 0) s[0] = 0;
+    return -1;
+}
+
+int snprintf(char *restrict s, size_t n, const char *restrict format, ...)
+{
+    (void)format;
+    if (n > 0) s[0] = 0;
+    return -1;
+}
+
+int sprintf(char *restrict s, const char *restrict format, ...)
+{
+    (void)format;
+    s[0] = 0;
+    return -1;
+}
+
+int fprintf(FILE *restrict f, const char *restrict format, ...)
+{
+    (void)f, (void)format;
+    return -1;
+}
+
+int printf(const char *restrict format, ...)
+{
+    (void)format;
+    return -1;
+}
+
+DIR *opendir(const char *path) { (void)path; return NULL; }
+struct dirent *readdir(DIR *dir) { (void)dir; return NULL; }
+int closedir(DIR *dir) { (void)dir; return -1; }
+
+int stat(const char *restrict path, struct stat *restrict st)
+{
+    (void)path, (void)st;
+    return -1;
+}